@@ -59,4 +59,15 @@ impl CommandView {
             ..Default::default()
         }
     }
+
+    /// Copy `commands` to the clipboard as text, in the style of the event command list.
+    pub fn copy_as_text(
+        ctx: &egui::Context,
+        db: &luminol_config::command_db::CommandDB,
+        data: &luminol_core::Data,
+        commands: &[luminol_data::rpg::EventCommand],
+    ) {
+        let text = luminol_core::command_text::Printer::new(db, data).list_to_string(commands);
+        ctx.output_mut(|o| o.copied_text = text);
+    }
 }
@@ -47,6 +47,13 @@ pub struct MapView {
 
     pub darken_unselected_layers: bool,
 
+    /// The rectangle of tiles selected with the selection tool, in map coordinates.
+    /// Both corners of the rectangle are inclusive.
+    pub selection_rect: Option<egui::Rect>,
+
+    /// The results of the walkability analysis, if the walkability overlay is enabled.
+    pub walkability: Option<WalkabilityOverlay>,
+
     pub scale: f32,
     pub previous_scale: f32,
 
@@ -55,6 +62,18 @@ pub struct MapView {
     pub event_rects: Vec<egui::Rect>,
 }
 
+#[derive(Clone, Debug, Default)]
+pub struct WalkabilityOverlay {
+    /// The tile the analysis started from.
+    pub start: (usize, usize),
+    /// Whether or not the player can walk to each tile from `start`, indexed by `x + y * xsize`.
+    pub reachable: Vec<bool>,
+    /// The shortest path from `start` to the destination tile, including both ends.
+    pub path: Vec<(usize, usize)>,
+    /// IDs of the events the player can trigger but can't walk to.
+    pub unreachable_events: Vec<usize>,
+}
+
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, Default)]
 pub enum SelectedLayer {
     #[default]
@@ -74,7 +93,7 @@ impl MapView {
         let tileset = &tilesets.data[map.tileset_id];
 
         let mut passages = luminol_data::Table2::new(map.data.xsize(), map.data.ysize());
-        luminol_graphics::collision::calculate_passages(
+        luminol_data::tilemap::calculate_passages(
             &tileset.passages,
             &tileset.priorities,
             &map.data,
@@ -139,6 +158,10 @@ impl MapView {
 
             darken_unselected_layers: true,
 
+            selection_rect: None,
+
+            walkability: None,
+
             hover_tile: None,
 
             selected_event_is_hovered: false,
@@ -572,6 +595,86 @@ impl MapView {
                 .paint_overlay(graphics_state.clone(), ui.painter(), canvas_rect);
         }
 
+        // Draw the walkability overlay
+        if let Some(walkability) = &self.walkability {
+            let tile_rect = |x: usize, y: usize| {
+                egui::Rect::from_min_size(
+                    map_rect.min + egui::vec2(x as f32, y as f32) * tile_size,
+                    egui::Vec2::splat(tile_size),
+                )
+            };
+
+            // Only the tiles that are on screen are drawn, with each horizontal run of reachable
+            // tiles drawn as a single rectangle
+            // The analysis is from the previous frame, so it's skipped if the map was resized since
+            let visible_rect = canvas_rect.intersect(map_rect);
+            if visible_rect.is_positive()
+                && walkability.reachable.len() == map.data.xsize() * map.data.ysize()
+            {
+                let min = (visible_rect.min - map_rect.min) / tile_size;
+                let max = (visible_rect.max - map_rect.min) / tile_size;
+                let x_range = min.x.floor() as usize..(max.x.ceil() as usize).min(map.data.xsize());
+                let y_range = min.y.floor() as usize..(max.y.ceil() as usize).min(map.data.ysize());
+
+                for y in y_range {
+                    let mut run_start = None;
+                    for x in x_range.clone().chain(std::iter::once(x_range.end)) {
+                        let is_reachable =
+                            x < x_range.end && walkability.reachable[x + y * map.data.xsize()];
+                        match (run_start, is_reachable) {
+                            (None, true) => run_start = Some(x),
+                            (Some(start), false) => {
+                                ui.painter().rect_filled(
+                                    tile_rect(start, y).union(tile_rect(x - 1, y)),
+                                    0.,
+                                    egui::Color32::from_rgba_unmultiplied(0, 255, 0, 40),
+                                );
+                                run_start = None;
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+
+            if walkability.path.len() > 1 {
+                ui.painter().add(egui::Shape::line(
+                    walkability
+                        .path
+                        .iter()
+                        .map(|&(x, y)| tile_rect(x, y).center())
+                        .collect(),
+                    egui::Stroke::new(3., egui::Color32::GOLD),
+                ));
+            }
+            if let Some(&(x, y)) = walkability.path.last() {
+                ui.painter().rect_stroke(
+                    tile_rect(x, y),
+                    5.,
+                    egui::Stroke::new(2., egui::Color32::GOLD),
+                );
+            }
+
+            for event in walkability
+                .unreachable_events
+                .iter()
+                .filter_map(|&id| map.events.get(id))
+            {
+                ui.painter().rect_stroke(
+                    tile_rect(event.x as usize, event.y as usize),
+                    5.,
+                    egui::Stroke::new(2., egui::Color32::RED),
+                );
+            }
+
+            let (x, y) = walkability.start;
+            ui.painter().rect_stroke(
+                tile_rect(x, y),
+                5.,
+                egui::Stroke::new(2., egui::Color32::GREEN),
+            );
+        }
+
         // Do we display the visible region?
         if self.visible_display {
             // Determine the visible region.
@@ -607,6 +710,25 @@ impl MapView {
             }
         }
 
+        // Draw the rectangle selected with the selection tool
+        if let Some(selection_rect) = self.selection_rect {
+            let selection_rect = egui::Rect::from_min_max(
+                map_rect.min + (selection_rect.min.to_vec2() * tile_size),
+                map_rect.min + ((selection_rect.max.to_vec2() + egui::Vec2::splat(1.)) * tile_size),
+            )
+            .intersect(map_rect);
+            ui.painter().rect_filled(
+                selection_rect,
+                0.,
+                egui::Color32::from_rgba_unmultiplied(255, 255, 255, 24),
+            );
+            ui.painter().rect_stroke(
+                selection_rect,
+                0.,
+                egui::Stroke::new(2., egui::Color32::LIGHT_BLUE),
+            );
+        }
+
         // Display cursor.
         if matches!(self.selected_layer, SelectedLayer::Tiles(_)) {
             ui.painter().rect_stroke(
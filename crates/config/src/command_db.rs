@@ -50,6 +50,10 @@ static ACE_DEFAULT: Lazy<Vec<CommandDescription>> = Lazy::new(|| {
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct CommandDB {
     /// Default commands
+    ///
+    /// Not saved with the project, so that projects always use the commands shipped with this
+    /// version of Luminol. Restored by [`CommandDB::load_defaults`] after deserializing.
+    #[serde(skip)]
     default: Vec<CommandDescription>,
     /// User defined commands
     // FIXME: visible to user?
@@ -58,15 +62,22 @@ pub struct CommandDB {
 
 impl CommandDB {
     pub fn new(ver: RMVer) -> Self {
-        Self {
-            default: match ver {
-                RMVer::XP => &*XP_DEFAULT,
-                RMVer::VX => &*VX_DEFAULT,
-                RMVer::Ace => &*ACE_DEFAULT,
-            }
-            .clone(),
+        let mut command_db = Self {
+            default: vec![],
             user: vec![],
+        };
+        command_db.load_defaults(ver);
+        command_db
+    }
+
+    /// Replaces the default commands with the ones for `ver`.
+    pub fn load_defaults(&mut self, ver: RMVer) {
+        self.default = match ver {
+            RMVer::XP => &*XP_DEFAULT,
+            RMVer::VX => &*VX_DEFAULT,
+            RMVer::Ace => &*ACE_DEFAULT,
         }
+        .clone();
     }
 
     pub fn get(&self, code: u16) -> Option<&CommandDescription> {
@@ -92,3 +103,49 @@ impl CommandDB {
         self.len() == 0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use luminol_data::{commands::ParameterKind, rpg::EventCommand, ParameterType};
+
+    #[test]
+    fn default_xp_commands_describe_database_ids() {
+        let command_db = CommandDB::new(RMVer::XP);
+        assert!(command_db.get(101).is_some());
+
+        // Change Items with a variable operand: the item id and the variable are both found
+        let command = EventCommand {
+            code: 126,
+            indent: 0,
+            parameters: vec![
+                ParameterType::Integer(3),
+                ParameterType::Integer(0),
+                ParameterType::Integer(1),
+                ParameterType::Integer(7),
+            ],
+            guid: 0,
+        };
+        let mut found = vec![];
+        command_db
+            .get(126)
+            .unwrap()
+            .walk_parameters(&command, |index, kind, _| match kind {
+                ParameterKind::Item => found.push((index, "item")),
+                ParameterKind::Variable => found.push((index, "variable")),
+                _ => {}
+            });
+        assert_eq!(found, [(0, "item"), (3, "variable")]);
+    }
+
+    #[test]
+    fn saved_projects_get_the_current_defaults() {
+        // Older versions saved the default commands, which were empty, with the project
+        let mut command_db: CommandDB = ron::from_str("(default: [], user: [])").unwrap();
+        command_db.load_defaults(RMVer::XP);
+        assert!(command_db.get(126).is_some());
+
+        let saved = ron::to_string(&CommandDB::new(RMVer::XP)).unwrap();
+        assert!(!saved.contains("default"));
+    }
+}
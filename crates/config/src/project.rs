@@ -45,6 +45,8 @@ pub struct Project {
     pub editor_ver: RMVer,
     pub playtest_exe: String,
     pub prefer_rgssad: bool,
+    /// How many snapshots of `Data/` to keep in `.luminol/backups`. `0` disables backups.
+    pub backup_count: usize,
 }
 
 impl Default for Project {
@@ -57,6 +59,7 @@ impl Default for Project {
             editor_ver: RMVer::XP,
             playtest_exe: "game".to_string(),
             prefer_rgssad: false,
+            backup_count: 10,
         }
     }
 }
@@ -0,0 +1,263 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+//! Snapshots of the files in `Data/` taken right before a save overwrites them.
+
+use anyhow::Context;
+use luminol_filesystem::FileSystem;
+
+/// Directory inside the project that snapshots are stored in, one subdirectory per save.
+pub const BACKUP_DIR: &str = ".luminol/backups";
+
+/// A snapshot of the data files that were overwritten by one save.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    /// The name of the snapshot's directory, which is the time it was taken in UTC.
+    pub name: String,
+    /// The names of the files in the snapshot, e.g. `Actors.rxdata` or `Map001.rxdata`.
+    pub files: Vec<String>,
+}
+
+impl Snapshot {
+    pub fn path(&self) -> camino::Utf8PathBuf {
+        camino::Utf8Path::new(BACKUP_DIR).join(&self.name)
+    }
+}
+
+/// Copies the given files from `Data/` into a new snapshot and removes the oldest snapshots so
+/// that at most `keep` remain. Files that don't exist yet are skipped.
+pub fn create(
+    filesystem: &impl FileSystem,
+    filenames: &[String],
+    keep: usize,
+) -> anyhow::Result<()> {
+    if keep == 0 {
+        return Ok(());
+    }
+
+    let data_dir = camino::Utf8Path::new("Data");
+    let filenames: Vec<_> = filenames
+        .iter()
+        .filter_map(
+            |filename| match filesystem.exists(data_dir.join(filename)) {
+                Ok(true) => Some(Ok(filename)),
+                Ok(false) => None,
+                Err(e) => Some(Err(e)),
+            },
+        )
+        .collect::<Result<_, _>>()?;
+    if filenames.is_empty() {
+        return Ok(());
+    }
+
+    let timestamp = format_timestamp(unix_time());
+    let mut path = camino::Utf8Path::new(BACKUP_DIR).join(&timestamp);
+    let mut suffix = 1;
+    while filesystem.exists(&path)? {
+        suffix += 1;
+        // Padded so that snapshots taken in the same second still sort by name
+        path = camino::Utf8Path::new(BACKUP_DIR).join(format!("{timestamp}_{suffix:03}"));
+    }
+    filesystem.create_dir(&path)?;
+
+    for filename in filenames {
+        let bytes = filesystem
+            .read(data_dir.join(filename))
+            .with_context(|| format!("while backing up {filename}"))?;
+        filesystem
+            .write(path.join(filename), bytes)
+            .with_context(|| format!("while backing up {filename}"))?;
+    }
+
+    let mut snapshots = list(filesystem)?;
+    while snapshots.len() > keep {
+        let oldest = snapshots.pop().expect("snapshots should not be empty");
+        filesystem
+            .remove_dir(oldest.path())
+            .with_context(|| format!("while removing old backup {}", oldest.name))?;
+    }
+
+    Ok(())
+}
+
+/// Lists all snapshots, newest first.
+pub fn list(filesystem: &impl FileSystem) -> anyhow::Result<Vec<Snapshot>> {
+    if !filesystem.exists(BACKUP_DIR)? {
+        return Ok(vec![]);
+    }
+
+    let mut snapshots = Vec::new();
+    for entry in filesystem.read_dir(BACKUP_DIR)? {
+        if entry.metadata().is_file {
+            continue;
+        }
+
+        let mut files: Vec<_> = filesystem
+            .read_dir(entry.path())?
+            .into_iter()
+            .filter(|file| file.metadata().is_file)
+            .map(|file| file.file_name().to_string())
+            .collect();
+        files.sort_unstable();
+
+        snapshots.push(Snapshot {
+            name: entry.file_name().to_string(),
+            files,
+        });
+    }
+    // Snapshot names are timestamps, so sorting them by name sorts them by age
+    snapshots.sort_unstable_by(|a, b| b.name.cmp(&a.name));
+
+    Ok(snapshots)
+}
+
+/// Loads files from a snapshot into the data cache. They are marked as modified rather than
+/// written to `Data/` directly, so the restore only takes effect once the project is saved.
+pub fn restore(
+    data: &crate::Data,
+    filesystem: &impl FileSystem,
+    config: &luminol_config::project::Config,
+    snapshot: &Snapshot,
+    filenames: &[String],
+) -> anyhow::Result<()> {
+    let path = snapshot.path();
+    for filename in filenames {
+        data.restore(filesystem, &path.join(filename), filename, config)?;
+    }
+    Ok(())
+}
+
+/// Restores every data file to how it was right before the save that took `snapshots[index]`,
+/// where `snapshots` is newest first like [`list`] returns them. Returns the names of the files
+/// that were restored.
+///
+/// A snapshot only has the files that its save overwrote. Every other file still had the version
+/// that a later save overwrote, so it's restored from the oldest newer snapshot that has it. Files
+/// that haven't been saved since then are left as they are.
+pub fn restore_point_in_time(
+    data: &crate::Data,
+    filesystem: &impl FileSystem,
+    config: &luminol_config::project::Config,
+    snapshots: &[Snapshot],
+    index: usize,
+) -> anyhow::Result<Vec<String>> {
+    let mut sources = std::collections::BTreeMap::new();
+    for snapshot in snapshots[..=index].iter().rev() {
+        for filename in &snapshot.files {
+            sources.entry(filename.as_str()).or_insert(snapshot);
+        }
+    }
+
+    for (filename, snapshot) in &sources {
+        data.restore(
+            filesystem,
+            &snapshot.path().join(filename),
+            filename,
+            config,
+        )?;
+    }
+    Ok(sources.into_keys().map(str::to_string).collect())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn unix_time() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn unix_time() -> u64 {
+    (js_sys::Date::now() / 1000.) as u64
+}
+
+/// Formats seconds since the Unix epoch as `YYYY-MM-DD_HH-MM-SS` in UTC.
+fn format_timestamp(secs: u64) -> String {
+    let days = (secs / 86400) as i64;
+    let secs_of_day = secs % 86400;
+
+    // Converts days since the epoch to a civil date (https://howardhinnant.github.io/date_algorithms.html)
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}_{:02}-{:02}-{:02}",
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn restores_point_in_time() {
+        let (filesystem, config, mut data) = crate::project_manager::test_project();
+
+        let save = |data: &mut crate::Data, actor: Option<&str>, item: Option<&str>| {
+            if let Some(actor) = actor {
+                let mut actors = data.actors();
+                actors.data[0].name = actor.to_string();
+                actors.modified = true;
+            }
+            if let Some(item) = item {
+                let mut items = data.items();
+                items.data[0].name = item.to_string();
+                items.modified = true;
+            }
+            data.save(&filesystem, &config).unwrap();
+        };
+        save(&mut data, Some("Actor 1"), None);
+        save(&mut data, None, Some("Item 2"));
+        save(&mut data, Some("Actor 3"), None);
+
+        // All three saves usually happen within the same second
+        let snapshots = super::list(&filesystem).unwrap();
+        assert_eq!(snapshots.len(), 3);
+        assert_eq!(snapshots[1].files, ["Items.rxdata"]);
+
+        // Right before the second save, the actor was already renamed but the item wasn't
+        let restored =
+            super::restore_point_in_time(&data, &filesystem, &config, &snapshots, 1).unwrap();
+        assert_eq!(restored, ["Actors.rxdata", "Items.rxdata"]);
+        assert_eq!(data.actors().data[0].name, "Actor 1");
+        assert_eq!(data.items().data[0].name, "");
+    }
+
+    #[test]
+    fn timestamps() {
+        assert_eq!(super::format_timestamp(0), "1970-01-01_00-00-00");
+        assert_eq!(
+            super::format_timestamp(1_700_000_000),
+            "2023-11-14_22-13-20"
+        );
+    }
+}
@@ -0,0 +1,42 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+/// Data copied from one editor that can be pasted into another one.
+#[derive(Default)]
+pub struct Clipboard {
+    /// Tiles and events copied from a map with the selection tool.
+    pub map: Option<MapClipboard>,
+}
+
+/// A rectangle of tiles, and optionally the events inside of it, copied from a map.
+#[derive(Debug, Clone)]
+pub struct MapClipboard {
+    /// The copied tiles. The width and height of this table are the size of the copied rectangle.
+    pub tiles: luminol_data::Table3,
+    /// If this is true, `tiles` contains every layer of the map it was copied from.
+    /// Otherwise, it only contains one layer that should be pasted into the selected layer.
+    pub all_layers: bool,
+    /// The copied events. Their positions are relative to the top-left corner of the rectangle.
+    pub events: Vec<luminol_data::rpg::Event>,
+}
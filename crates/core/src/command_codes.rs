@@ -0,0 +1,73 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+//! Codes of the RPG Maker XP event commands that are handled specially, rather than through the
+//! command database.
+
+/// Show Text, whose first line is parameter 0.
+pub const SHOW_TEXT: u16 = 101;
+/// Show Choices, whose choices are an array in parameter 0.
+pub const SHOW_CHOICES: u16 = 102;
+/// Each following line of a Show Text command.
+pub const SHOW_TEXT_CONTINUED: u16 = 401;
+/// One choice of a Show Choices command, with the choice index in parameter 0 and its text in parameter 1.
+pub const WHEN_CHOICE: u16 = 402;
+/// Change Windowskin, with the windowskin's name in parameter 0.
+pub const CHANGE_WINDOWSKIN: u16 = 131;
+/// Change Battle BGM, with the audio file in parameter 0.
+pub const CHANGE_BATTLE_BGM: u16 = 132;
+/// Change Battle End ME, with the audio file in parameter 0.
+pub const CHANGE_BATTLE_END_ME: u16 = 133;
+/// Transfer Player. Parameter 0 is 0 if the destination is given directly, with the map id, x and
+/// y in parameters 1 to 3, or 1 if they're taken from the variables with those ids.
+pub const TRANSFER_PLAYER: u16 = 201;
+/// Change Map Settings, with what to change in parameter 0 and the graphic's name in parameter 1.
+pub const CHANGE_MAP_SETTINGS: u16 = 204;
+/// Set Move Route, with the move route in parameter 1.
+pub const SET_MOVE_ROUTE: u16 = 209;
+/// Execute Transition, with the transition graphic's name in parameter 0.
+pub const EXECUTE_TRANSITION: u16 = 222;
+/// Show Picture, with the picture's name in parameter 1.
+pub const SHOW_PICTURE: u16 = 231;
+/// Play BGM, with the audio file in parameter 0.
+pub const PLAY_BGM: u16 = 241;
+/// Play BGS, with the audio file in parameter 0.
+pub const PLAY_BGS: u16 = 245;
+/// Play ME, with the audio file in parameter 0.
+pub const PLAY_ME: u16 = 249;
+/// Play SE, with the audio file in parameter 0.
+pub const PLAY_SE: u16 = 250;
+/// Change Actor Graphic, with the character's name in parameter 1 and the battler's in parameter 3.
+pub const CHANGE_ACTOR_GRAPHIC: u16 = 322;
+/// Each move command of a Set Move Route, repeated for display, in parameter 0.
+pub const MOVE_ROUTE_CONTINUATION: u16 = 509;
+
+/// The move command that turns on the switch with the (1-based) id in parameter 0.
+pub const MOVE_SWITCH_ON: u16 = 27;
+/// The move command that turns off the switch with the (1-based) id in parameter 0.
+pub const MOVE_SWITCH_OFF: u16 = 28;
+/// The move command that changes the character's graphic, with its name in parameter 0.
+pub const MOVE_CHANGE_GRAPHIC: u16 = 41;
+/// The move command that plays an SE, with the audio file in parameter 0.
+pub const MOVE_PLAY_SE: u16 = 44;
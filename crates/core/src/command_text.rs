@@ -0,0 +1,443 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use luminol_data::commands::{CommandKind, ParameterKind};
+use luminol_data::rpg;
+use luminol_data::ParameterType;
+
+use crate::command_codes::{SHOW_CHOICES, SHOW_TEXT, SHOW_TEXT_CONTINUED};
+use crate::localization::escape_csv;
+use crate::references::{Location, Target};
+
+/// Formats event commands as text, in the style of the event command list.
+pub struct Printer<'a> {
+    command_db: &'a luminol_config::command_db::CommandDB,
+    data: &'a crate::Data,
+    /// Codes that continue a multi-line command.
+    continuation_codes: HashSet<u16>,
+    /// Codes that end a branch.
+    end_codes: HashSet<u16>,
+}
+
+impl<'a> Printer<'a> {
+    pub fn new(
+        command_db: &'a luminol_config::command_db::CommandDB,
+        data: &'a crate::Data,
+    ) -> Self {
+        let mut continuation_codes = HashSet::new();
+        let mut end_codes = HashSet::new();
+        for description in command_db.iter() {
+            match description.kind {
+                CommandKind::Multi { code, .. } => {
+                    continuation_codes.insert(code);
+                }
+                CommandKind::Branch { end_code, .. } => {
+                    end_codes.insert(end_code);
+                }
+                CommandKind::Single(_) => {}
+            }
+        }
+
+        Self {
+            command_db,
+            data,
+            continuation_codes,
+            end_codes,
+        }
+    }
+
+    /// Format a whole command list, one command per line.
+    pub fn list_to_string(&self, list: &[rpg::EventCommand]) -> String {
+        let mut text = String::new();
+        for command in list {
+            let _ = writeln!(
+                text,
+                "{}{}",
+                "  ".repeat(command.indent),
+                self.command_to_string(command)
+            );
+        }
+        text
+    }
+
+    /// Format a single command, without indentation.
+    pub fn command_to_string(&self, command: &rpg::EventCommand) -> String {
+        if command.code == 0 {
+            return "@>".to_string();
+        }
+
+        if self.continuation_codes.contains(&command.code) {
+            let text = command
+                .parameters
+                .first()
+                .map(value_to_string)
+                .unwrap_or_default();
+            return format!(" :  : {text}");
+        }
+
+        let Some(description) = self.command_db.get(command.code) else {
+            if self.end_codes.contains(&command.code) {
+                return " : Branch End".to_string();
+            }
+            let parameters = command
+                .parameters
+                .iter()
+                .map(value_to_string)
+                .collect::<Vec<_>>();
+            return format!("@>Command {}: {}", command.code, parameters.join(", "));
+        };
+
+        let parameters = if let CommandKind::Multi { .. } = description.kind {
+            command
+                .parameters
+                .first()
+                .map(value_to_string)
+                .into_iter()
+                .collect()
+        } else {
+            let mut parameters = Vec::new();
+            description.walk_parameters(command, |_, kind, value| {
+                parameters.push(self.parameter_to_string(kind, value));
+            });
+            parameters
+        };
+
+        let prefix = if self.end_codes.contains(&command.code) {
+            " : "
+        } else {
+            "@>"
+        };
+        if parameters.is_empty() {
+            format!("{prefix}{}", description.name)
+        } else {
+            format!("{prefix}{}: {}", description.name, parameters.join(", "))
+        }
+    }
+
+    fn parameter_to_string(&self, kind: &ParameterKind, value: &ParameterType) -> String {
+        let target = match kind {
+            ParameterKind::Switch => Some((Target::Switch, 4)),
+            ParameterKind::Variable => Some((Target::Variable, 4)),
+            ParameterKind::CommonEvent => Some((Target::CommonEvent, 3)),
+            ParameterKind::Actor => Some((Target::Actor, 3)),
+            ParameterKind::Class => Some((Target::Class, 3)),
+            ParameterKind::Skill => Some((Target::Skill, 3)),
+            ParameterKind::Item => Some((Target::Item, 3)),
+            ParameterKind::Weapon => Some((Target::Weapon, 3)),
+            ParameterKind::Armor => Some((Target::Armor, 3)),
+            ParameterKind::Enemy => Some((Target::Enemy, 3)),
+            ParameterKind::Troop => Some((Target::Troop, 3)),
+            ParameterKind::State => Some((Target::State, 3)),
+            ParameterKind::Animation => Some((Target::Animation, 3)),
+            _ => None,
+        };
+
+        if let (Some((target, width)), ParameterType::Integer(id)) = (target, value) {
+            let name = target
+                .entry_name(self.data, *id as usize)
+                .unwrap_or_default();
+            return format!("[{id:0>width$}: {name}]");
+        }
+
+        match (kind, value) {
+            (ParameterKind::IntBool, ParameterType::Integer(i)) => {
+                if *i != 0 { "ON" } else { "OFF" }.to_string()
+            }
+            (ParameterKind::Enum { variants }, ParameterType::Integer(i)) => variants
+                .iter()
+                .find(|(_, v)| *v as i32 == *i)
+                .map_or_else(|| i.to_string(), |(name, _)| name.clone()),
+            _ => value_to_string(value),
+        }
+    }
+}
+
+fn value_to_string(value: &ParameterType) -> String {
+    match value {
+        ParameterType::Integer(i) => i.to_string(),
+        ParameterType::String(s) => s.clone(),
+        ParameterType::Float(f) => f.to_string(),
+        ParameterType::Bool(b) => b.to_string(),
+        ParameterType::Array(values) => values
+            .iter()
+            .map(value_to_string)
+            .collect::<Vec<_>>()
+            .join(", "),
+        ParameterType::AudioFile(audio) => audio
+            .name
+            .as_ref()
+            .map_or_else(|| "(None)".to_string(), |name| name.to_string()),
+        ParameterType::None => String::new(),
+        value => format!("{value:?}"),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display)]
+pub enum DialogueKind {
+    Text,
+    Choices,
+}
+
+/// A message or set of choices shown to the player.
+#[derive(Debug, Clone)]
+pub struct Dialogue {
+    pub location: Location,
+    /// The line of the command list the dialogue starts on.
+    pub line: usize,
+    pub kind: DialogueKind,
+    /// The text, with one line (or one choice) per line.
+    pub text: String,
+}
+
+/// Collect every message and set of choices in the project, in order.
+///
+/// This loads every map in the project, skipping the ones that fail to load.
+pub fn dialogue(
+    data: &crate::Data,
+    filesystem: &impl luminol_filesystem::FileSystem,
+) -> Vec<Dialogue> {
+    let mut dialogue = Vec::new();
+
+    let mut map_ids = data.map_infos().data.keys().copied().collect::<Vec<_>>();
+    map_ids.sort_unstable();
+    for map_id in map_ids {
+        let Ok(map) = data.try_get_or_load_map(map_id, filesystem) else {
+            continue;
+        };
+        for (event_id, event) in map.events.iter() {
+            for (page, event_page) in event.pages.iter().enumerate() {
+                let location = Location::MapEvent {
+                    map_id,
+                    event_id,
+                    page,
+                };
+                list_dialogue(&event_page.list, location, &mut dialogue);
+            }
+        }
+    }
+
+    for (index, common_event) in data.common_events().data.iter().enumerate() {
+        list_dialogue(
+            &common_event.list,
+            Location::CommonEvent { index },
+            &mut dialogue,
+        );
+    }
+
+    for (index, troop) in data.troops().data.iter().enumerate() {
+        for (page, troop_page) in troop.pages.iter().enumerate() {
+            list_dialogue(
+                &troop_page.list,
+                Location::Troop { index, page },
+                &mut dialogue,
+            );
+        }
+    }
+
+    dialogue
+}
+
+fn list_dialogue(list: &[rpg::EventCommand], location: Location, dialogue: &mut Vec<Dialogue>) {
+    let string = |command: &rpg::EventCommand| {
+        command
+            .parameters
+            .first()
+            .and_then(|p| p.as_string())
+            .cloned()
+            .unwrap_or_default()
+    };
+
+    let mut commands = list.iter().enumerate().peekable();
+    while let Some((line, command)) = commands.next() {
+        match command.code {
+            SHOW_TEXT => {
+                let mut text = string(command);
+                while let Some((_, next)) = commands.next_if(|(_, c)| c.code == SHOW_TEXT_CONTINUED)
+                {
+                    text.push('\n');
+                    text.push_str(&string(next));
+                }
+                dialogue.push(Dialogue {
+                    location,
+                    line,
+                    kind: DialogueKind::Text,
+                    text,
+                });
+            }
+            SHOW_CHOICES => {
+                let text = command
+                    .parameters
+                    .first()
+                    .and_then(|p| p.as_array())
+                    .map(|choices| {
+                        choices
+                            .iter()
+                            .map(value_to_string)
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    })
+                    .unwrap_or_default();
+                dialogue.push(Dialogue {
+                    location,
+                    line,
+                    kind: DialogueKind::Choices,
+                    text,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Format dialogue as a CSV file with a header row.
+pub fn dialogue_to_csv(dialogue: &[Dialogue]) -> String {
+    let mut csv = String::from("Location,Line,Kind,Text\r\n");
+    for entry in dialogue {
+        let _ = write!(
+            csv,
+            "{},{},{},{}\r\n",
+            escape_csv(&entry.location.to_string()),
+            entry.line + 1,
+            entry.kind,
+            escape_csv(&entry.text)
+        );
+    }
+    csv
+}
+
+/// Format dialogue as a Markdown document, with a heading for each location.
+pub fn dialogue_to_markdown(dialogue: &[Dialogue]) -> String {
+    let mut markdown = String::from("# Dialogue\n");
+    let mut last_location = None;
+    for entry in dialogue {
+        if last_location != Some(entry.location) {
+            let _ = write!(markdown, "\n## {}\n", entry.location);
+            last_location = Some(entry.location);
+        }
+
+        markdown.push('\n');
+        match entry.kind {
+            DialogueKind::Text => {
+                for line in entry.text.lines() {
+                    let _ = writeln!(markdown, "> {line}  ");
+                }
+            }
+            DialogueKind::Choices => {
+                for choice in entry.text.lines() {
+                    let _ = writeln!(markdown, "- {choice}");
+                }
+            }
+        }
+    }
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(code: u16, indent: usize, parameters: Vec<ParameterType>) -> rpg::EventCommand {
+        rpg::EventCommand {
+            code,
+            indent,
+            parameters,
+            guid: 0,
+        }
+    }
+
+    #[test]
+    fn prints_commands_from_the_database() {
+        let command_db = luminol_config::command_db::CommandDB::new(luminol_config::RMVer::XP);
+        let data = crate::Data::from_defaults();
+        let printer = Printer::new(&command_db, &data);
+
+        let list = [
+            command(
+                111,
+                0,
+                vec![
+                    ParameterType::Integer(0),
+                    ParameterType::Integer(5),
+                    ParameterType::Integer(0),
+                ],
+            ),
+            command(
+                121,
+                1,
+                vec![
+                    ParameterType::Integer(1),
+                    ParameterType::Integer(1),
+                    ParameterType::Integer(1),
+                ],
+            ),
+            command(0, 1, vec![]),
+            command(412, 0, vec![]),
+        ];
+        let text = printer.list_to_string(&list);
+        let lines = text.lines().collect::<Vec<_>>();
+        assert!(lines[0].starts_with("@>Conditional Branch: Switch, [0005: "));
+        assert!(lines[0].ends_with(", ON"));
+        assert!(lines[1].starts_with("  @>Control Switches: [0001: "));
+        assert!(lines[1].ends_with(", OFF"));
+        assert_eq!(lines[2], "  @>");
+        assert_eq!(lines[3], " : Branch End");
+    }
+
+    #[test]
+    fn exports_dialogue() {
+        let list = [
+            command(SHOW_TEXT, 0, vec!["Hello, \"world\"".into()]),
+            command(SHOW_TEXT_CONTINUED, 0, vec!["Second line".into()]),
+            command(
+                SHOW_CHOICES,
+                0,
+                vec![
+                    ParameterType::Array(vec!["Yes".into(), "No".into()]),
+                    ParameterType::Integer(1),
+                ],
+            ),
+        ];
+        let mut dialogue = Vec::new();
+        list_dialogue(&list, Location::CommonEvent { index: 0 }, &mut dialogue);
+        assert_eq!(dialogue.len(), 2);
+        assert_eq!(dialogue[0].text, "Hello, \"world\"\nSecond line");
+        assert_eq!(dialogue[1].line, 2);
+
+        assert_eq!(
+            dialogue_to_csv(&dialogue),
+            "Location,Line,Kind,Text\r\n\
+             Common event 001,1,Text,\"Hello, \"\"world\"\"\nSecond line\"\r\n\
+             Common event 001,3,Choices,\"Yes\nNo\"\r\n"
+        );
+        assert_eq!(
+            dialogue_to_markdown(&dialogue),
+            "# Dialogue\n\n## Common event 001\n\n\
+             > Hello, \"world\"  \n> Second line  \n\n\
+             - Yes\n- No\n"
+        );
+    }
+}
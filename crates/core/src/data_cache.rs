@@ -58,12 +58,11 @@ pub enum Data {
 
 fn read_data<T>(
     filesystem: &impl luminol_filesystem::FileSystem,
-    filename: impl AsRef<camino::Utf8Path>,
+    path: impl AsRef<camino::Utf8Path>,
 ) -> anyhow::Result<T>
 where
     T: serde::de::DeserializeOwned,
 {
-    let path = camino::Utf8PathBuf::from("Data").join(filename);
     let data = filesystem.read(path)?;
 
     alox_48::from_bytes(&data).map_err(anyhow::Error::from)
@@ -72,22 +71,19 @@ where
 fn write_data(
     data: &impl serde::Serialize,
     filesystem: &impl luminol_filesystem::FileSystem,
-    filename: impl AsRef<camino::Utf8Path>,
+    path: impl AsRef<camino::Utf8Path>,
 ) -> anyhow::Result<()> {
-    let path = camino::Utf8PathBuf::from("Data").join(filename);
-
     let bytes = alox_48::to_bytes(data)?;
     filesystem.write(path, bytes).map_err(anyhow::Error::from)
 }
 
 fn read_nil_padded<T>(
     filesystem: &impl luminol_filesystem::FileSystem,
-    filename: impl AsRef<camino::Utf8Path>,
+    path: impl AsRef<camino::Utf8Path>,
 ) -> anyhow::Result<Vec<T>>
 where
     T: serde::de::DeserializeOwned,
 {
-    let path = camino::Utf8PathBuf::from("Data").join(filename);
     let data = filesystem.read(path)?;
 
     let mut de = alox_48::Deserializer::new(&data)?;
@@ -95,25 +91,26 @@ where
     luminol_data::helpers::nil_padded::deserialize(&mut de).map_err(anyhow::Error::from)
 }
 
+fn nil_padded_bytes(data: &[impl serde::Serialize]) -> anyhow::Result<Vec<u8>> {
+    let mut ser = alox_48::Serializer::new();
+
+    luminol_data::helpers::nil_padded::serialize(data, &mut ser)?;
+    Ok(ser.output)
+}
+
 fn write_nil_padded(
     data: &[impl serde::Serialize],
     filesystem: &impl luminol_filesystem::FileSystem,
-    filename: impl AsRef<camino::Utf8Path>,
+    path: impl AsRef<camino::Utf8Path>,
 ) -> anyhow::Result<()> {
-    let path = camino::Utf8PathBuf::from("Data").join(filename);
-
-    let mut ser = alox_48::Serializer::new();
-
-    luminol_data::helpers::nil_padded::serialize(data, &mut ser)?;
-    filesystem
-        .write(path, ser.output)
-        .map_err(anyhow::Error::from)
+    let bytes = nil_padded_bytes(data)?;
+    filesystem.write(path, bytes).map_err(anyhow::Error::from)
 }
 
 macro_rules! load {
     ($fs:ident, $type:ident) => {
         RefCell::new(rpg::$type {
-            data: read_nil_padded($fs, format!("{}.rxdata", stringify!($type)))
+            data: read_nil_padded($fs, format!("Data/{}.rxdata", stringify!($type)))
                 .context(format!("while reading {}.rxdata", stringify!($type)))?,
             ..Default::default()
         })
@@ -123,7 +120,7 @@ macro_rules! from_defaults {
     ($parent:ident, $child:ident) => {
         RefCell::new(rpg::$parent {
             data: vec![rpg::$child::default()],
-            ..Default::default()
+            modified: true,
         })
     };
 }
@@ -134,12 +131,78 @@ macro_rules! save {
         let modified = borrowed.modified;
         if modified {
             borrowed.modified = false;
-            write_nil_padded(&borrowed.data, $fs, format!("{}.rxdata", stringify!($type)))
-                .context(format!("while saving {}.rxdata", stringify!($type)))?;
+            write_nil_padded(
+                &borrowed.data,
+                $fs,
+                format!("Data/{}.rxdata", stringify!($type)),
+            )
+            .context(format!("while saving {}.rxdata", stringify!($type)))?;
         }
         modified
     }};
 }
+macro_rules! write_modified {
+    ($fs:ident, $dir:ident, $written:ident, $type:ident, $field:ident) => {{
+        let borrowed = $field.borrow();
+        if borrowed.modified {
+            let filename = concat!(stringify!($type), ".rxdata");
+            write_nil_padded(&borrowed.data, $fs, $dir.join(filename)).context(concat!(
+                "while writing ",
+                stringify!($type),
+                ".rxdata"
+            ))?;
+            $written.push(filename.to_string());
+        }
+    }};
+}
+macro_rules! load_file {
+    ($self:ident, $fs:ident, $path:ident, $filename:ident, $modified:ident, $($type:ident, $getter:ident),* $(,)?) => {$(
+        if $filename == concat!(stringify!($type), ".rxdata") {
+            let data = read_nil_padded($fs, $path)
+                .with_context(|| format!("while reading {}", $path))?;
+            let mut container = $self.$getter();
+            container.data = data;
+            container.modified = $modified;
+            return Ok(());
+        }
+    )*};
+}
+macro_rules! serialize_file {
+    ($self:ident, $filename:ident, $($type:ident, $getter:ident),* $(,)?) => {$(
+        if $filename == concat!(stringify!($type), ".rxdata") {
+            return nil_padded_bytes(&$self.$getter().data).map(Some);
+        }
+    )*};
+}
+/// Calls a macro with its arguments followed by the name and getter of every nil padded table.
+macro_rules! for_each_table {
+    ($macro:ident!($($args:ident),*)) => {
+        $macro!(
+            $($args,)*
+            Actors, actors,
+            Animations, animations,
+            Armors, armors,
+            Classes, classes,
+            CommonEvents, common_events,
+            Enemies, enemies,
+            Items, items,
+            Skills, skills,
+            States, states,
+            Tilesets, tilesets,
+            Troops, troops,
+            Weapons, weapons,
+        )
+    };
+}
+
+/// Parses the id out of a map's filename, like `Map001.rxdata`.
+pub fn map_id_from_filename(filename: &str) -> Option<usize> {
+    filename
+        .strip_prefix("Map")
+        .and_then(|s| s.strip_suffix(".rxdata"))
+        .and_then(|s| s.parse().ok())
+}
+
 impl Data {
     /// Load all data required when opening a project.
     /// Does not load config. That is expected to have been loaded beforehand.
@@ -149,12 +212,12 @@ impl Data {
         config: &mut luminol_config::project::Config,
     ) -> anyhow::Result<()> {
         let map_infos = RefCell::new(rpg::MapInfos {
-            data: read_data(filesystem, "MapInfos.rxdata")
+            data: read_data(filesystem, "Data/MapInfos.rxdata")
                 .context("while reading MapInfos.rxdata")?,
             ..Default::default()
         });
 
-        let mut system = read_data::<rpg::System>(filesystem, "System.rxdata")
+        let mut system = read_data::<rpg::System>(filesystem, "Data/System.rxdata")
             .context("while reading System.rxdata")?;
         system.magic_number = rand::random();
 
@@ -168,7 +231,7 @@ impl Data {
         ];
 
         for script_path in scripts_paths {
-            match read_data(filesystem, format!("{script_path}.rxdata")) {
+            match read_data(filesystem, format!("Data/{script_path}.rxdata")) {
                 Ok(s) => {
                     config.project.scripts_path = script_path;
                     scripts = Some(rpg::Scripts {
@@ -216,16 +279,19 @@ impl Data {
         *self = Self::Unloaded;
     }
 
+    /// Creates the data for a new project.
+    /// Everything is marked as modified, so the first save writes out every data file.
     pub fn from_defaults() -> Self {
         let mut map_infos = std::collections::HashMap::with_capacity(16);
         map_infos.insert(1, rpg::MapInfo::default());
         let map_infos = RefCell::new(rpg::MapInfos {
             data: map_infos,
-            ..Default::default()
+            modified: true,
         });
 
         let system = rpg::System {
             magic_number: rand::random(),
+            modified: true,
             ..Default::default()
         };
         let system = RefCell::new(system);
@@ -233,11 +299,17 @@ impl Data {
         let scripts = vec![]; // FIXME legality of providing defualt scripts is unclear
         let scripts = RefCell::new(rpg::Scripts {
             data: scripts,
-            ..Default::default()
+            modified: true,
         });
 
         let mut maps = std::collections::HashMap::with_capacity(32);
-        maps.insert(1, rpg::Map::default());
+        maps.insert(
+            1,
+            rpg::Map {
+                modified: true,
+                ..Default::default()
+            },
+        );
         let maps = RefCell::new(maps);
 
         Self::Loaded {
@@ -271,6 +343,15 @@ impl Data {
         filesystem: &impl luminol_filesystem::FileSystem,
         config: &luminol_config::project::Config,
     ) -> anyhow::Result<()> {
+        let mut filenames = self.modified_files(config);
+        // Unless it was edited itself, System.rxdata is only rewritten to change its magic number,
+        // so it isn't backed up and restoring a point in time doesn't roll it back
+        if !self.system().modified {
+            filenames.retain(|filename| filename != "System.rxdata");
+        }
+        crate::backups::create(filesystem, &filenames, config.project.backup_count)
+            .context("while backing up data before saving")?;
+
         let Self::Loaded {
             actors,
             animations,
@@ -313,7 +394,7 @@ impl Data {
             if map_infos.modified {
                 modified = true;
                 map_infos.modified = false;
-                write_data(&map_infos.data, filesystem, "MapInfos.rxdata")
+                write_data(&map_infos.data, filesystem, "Data/MapInfos.rxdata")
                     .context("while saving MapInfos.rxdata")?;
             }
         }
@@ -326,7 +407,7 @@ impl Data {
                 write_data(
                     &scripts.data,
                     filesystem,
-                    format!("{}.rxdata", config.project.scripts_path),
+                    format!("Data/{}.rxdata", config.project.scripts_path),
                 )?;
             }
         }
@@ -337,7 +418,7 @@ impl Data {
                 if map.modified {
                     modified = true;
                     map.modified = false;
-                    write_data(map, filesystem, format!("Map{id:0>3}.rxdata"))
+                    write_data(map, filesystem, format!("Data/Map{id:0>3}.rxdata"))
                         .with_context(|| format!("while saving map {id:0>3}"))
                 } else {
                     Ok(())
@@ -350,13 +431,242 @@ impl Data {
             if system.modified || modified {
                 system.modified = false;
                 system.magic_number = rand::random();
-                write_data(system, filesystem, "System.rxdata")
+                write_data(system, filesystem, "Data/System.rxdata")
                     .context("while saving System.rxdata")?;
             }
         }
 
         Ok(())
     }
+
+    /// Names of the files in `Data/` that the next save will overwrite.
+    pub fn modified_files(&self, config: &luminol_config::project::Config) -> Vec<String> {
+        let mut filenames: Vec<_> = [
+            ("Actors.rxdata", self.actors().modified),
+            ("Animations.rxdata", self.animations().modified),
+            ("Armors.rxdata", self.armors().modified),
+            ("Classes.rxdata", self.classes().modified),
+            ("CommonEvents.rxdata", self.common_events().modified),
+            ("Enemies.rxdata", self.enemies().modified),
+            ("Items.rxdata", self.items().modified),
+            ("Skills.rxdata", self.skills().modified),
+            ("States.rxdata", self.states().modified),
+            ("Tilesets.rxdata", self.tilesets().modified),
+            ("Troops.rxdata", self.troops().modified),
+            ("Weapons.rxdata", self.weapons().modified),
+            ("MapInfos.rxdata", self.map_infos().modified),
+        ]
+        .into_iter()
+        .filter(|(_, modified)| *modified)
+        .map(|(filename, _)| filename.to_string())
+        .collect();
+
+        if self.scripts().modified {
+            filenames.push(format!("{}.rxdata", config.project.scripts_path));
+        }
+
+        let Self::Loaded { maps, .. } = self else {
+            panic!("project not loaded")
+        };
+        filenames.extend(
+            maps.borrow()
+                .iter()
+                .filter(|(_, map)| map.modified)
+                .map(|(id, _)| format!("Map{id:0>3}.rxdata")),
+        );
+
+        // System.rxdata is rewritten whenever anything else is
+        if !filenames.is_empty() || self.system().modified {
+            filenames.push("System.rxdata".to_string());
+        }
+
+        filenames
+    }
+
+    /// Write every modified table and map into `directory` without marking anything as saved.
+    /// Returns the names of the files that were written.
+    pub fn write_modified(
+        &self,
+        filesystem: &impl luminol_filesystem::FileSystem,
+        directory: &camino::Utf8Path,
+        config: &luminol_config::project::Config,
+    ) -> anyhow::Result<Vec<String>> {
+        let Self::Loaded {
+            actors,
+            animations,
+            armors,
+            classes,
+            common_events,
+            enemies,
+            items,
+            map_infos,
+            scripts,
+            skills,
+            states,
+            tilesets,
+            troops,
+            weapons,
+            system,
+            maps,
+        } = self
+        else {
+            panic!("project not loaded")
+        };
+
+        let mut written = Vec::new();
+
+        write_modified!(filesystem, directory, written, Actors, actors);
+        write_modified!(filesystem, directory, written, Animations, animations);
+        write_modified!(filesystem, directory, written, Armors, armors);
+        write_modified!(filesystem, directory, written, Classes, classes);
+        write_modified!(filesystem, directory, written, CommonEvents, common_events);
+        write_modified!(filesystem, directory, written, Enemies, enemies);
+        write_modified!(filesystem, directory, written, Items, items);
+        write_modified!(filesystem, directory, written, Skills, skills);
+        write_modified!(filesystem, directory, written, States, states);
+        write_modified!(filesystem, directory, written, Tilesets, tilesets);
+        write_modified!(filesystem, directory, written, Troops, troops);
+        write_modified!(filesystem, directory, written, Weapons, weapons);
+
+        {
+            let map_infos = map_infos.borrow();
+            if map_infos.modified {
+                write_data(
+                    &map_infos.data,
+                    filesystem,
+                    directory.join("MapInfos.rxdata"),
+                )
+                .context("while writing MapInfos.rxdata")?;
+                written.push("MapInfos.rxdata".to_string());
+            }
+        }
+
+        {
+            let scripts = scripts.borrow();
+            if scripts.modified {
+                let filename = format!("{}.rxdata", config.project.scripts_path);
+                write_data(&scripts.data, filesystem, directory.join(&filename))
+                    .with_context(|| format!("while writing {filename}"))?;
+                written.push(filename);
+            }
+        }
+
+        {
+            let maps = maps.borrow();
+            for (id, map) in maps.iter().filter(|(_, map)| map.modified) {
+                let filename = format!("Map{id:0>3}.rxdata");
+                write_data(map, filesystem, directory.join(&filename))
+                    .with_context(|| format!("while writing map {id:0>3}"))?;
+                written.push(filename);
+            }
+        }
+
+        {
+            let system = system.borrow();
+            if system.modified {
+                write_data(&*system, filesystem, directory.join("System.rxdata"))
+                    .context("while writing System.rxdata")?;
+                written.push("System.rxdata".to_string());
+            }
+        }
+
+        Ok(written)
+    }
+
+    /// Replace the table or map that is stored in `Data/<filename>` with the copy at `path`,
+    /// and mark it as modified so it is written out on the next save.
+    pub fn restore(
+        &self,
+        filesystem: &impl luminol_filesystem::FileSystem,
+        path: &camino::Utf8Path,
+        filename: &str,
+        config: &luminol_config::project::Config,
+    ) -> anyhow::Result<()> {
+        self.load_file(filesystem, path, filename, config, true)
+    }
+
+    /// Replace a table or map in the cache with the one in `Data/<filename>`, discarding any
+    /// unsaved changes to it. Used when the file was changed outside of Luminol.
+    pub fn reload(
+        &self,
+        filesystem: &impl luminol_filesystem::FileSystem,
+        filename: &str,
+        config: &luminol_config::project::Config,
+    ) -> anyhow::Result<()> {
+        let path = camino::Utf8Path::new("Data").join(filename);
+        self.load_file(filesystem, &path, filename, config, false)
+    }
+
+    fn load_file(
+        &self,
+        filesystem: &impl luminol_filesystem::FileSystem,
+        path: &camino::Utf8Path,
+        filename: &str,
+        config: &luminol_config::project::Config,
+        modified: bool,
+    ) -> anyhow::Result<()> {
+        for_each_table!(load_file!(self, filesystem, path, filename, modified));
+
+        if filename == "MapInfos.rxdata" {
+            let data = read_data(filesystem, path).context("while reading MapInfos.rxdata")?;
+            let mut map_infos = self.map_infos();
+            map_infos.data = data;
+            map_infos.modified = modified;
+        } else if filename == "System.rxdata" {
+            let mut system: rpg::System =
+                read_data(filesystem, path).context("while reading System.rxdata")?;
+            system.modified = modified;
+            *self.system() = system;
+        } else if filename == format!("{}.rxdata", config.project.scripts_path) {
+            let data =
+                read_data(filesystem, path).with_context(|| format!("while reading {filename}"))?;
+            let mut scripts = self.scripts();
+            scripts.data = data;
+            scripts.modified = modified;
+        } else if let Some(id) = map_id_from_filename(filename) {
+            let mut map: rpg::Map = read_data(filesystem, path)
+                .with_context(|| format!("while reading map {id:0>3}"))?;
+            map.modified = modified;
+            let Self::Loaded { maps, .. } = self else {
+                panic!("project not loaded")
+            };
+            maps.borrow_mut().insert(id, map);
+        } else {
+            anyhow::bail!("{filename} is not a known data file");
+        }
+
+        Ok(())
+    }
+
+    /// Serialize the cached version of the table or map stored in `Data/<filename>`.
+    /// Returns `None` for maps that haven't been loaded.
+    pub fn serialize_file(
+        &self,
+        filename: &str,
+        config: &luminol_config::project::Config,
+    ) -> anyhow::Result<Option<Vec<u8>>> {
+        for_each_table!(serialize_file!(self, filename));
+
+        let bytes = if filename == "MapInfos.rxdata" {
+            alox_48::to_bytes(&self.map_infos().data)?
+        } else if filename == "System.rxdata" {
+            alox_48::to_bytes(&*self.system())?
+        } else if filename == format!("{}.rxdata", config.project.scripts_path) {
+            alox_48::to_bytes(&self.scripts().data)?
+        } else if let Some(id) = map_id_from_filename(filename) {
+            let Self::Loaded { maps, .. } = self else {
+                panic!("project not loaded")
+            };
+            match maps.borrow().get(&id) {
+                Some(map) => alox_48::to_bytes(map)?,
+                None => return Ok(None),
+            }
+        } else {
+            anyhow::bail!("{filename} is not a known data file");
+        };
+
+        Ok(Some(bytes))
+    }
 }
 
 macro_rules! nested_ref_getter {
@@ -407,11 +717,34 @@ impl Data {
         RefMut::map(maps_ref, |maps| {
             // FIXME
             maps.entry(id).or_insert_with(|| {
-                read_data(filesystem, format!("Map{id:0>3}.rxdata")).expect("failed to load map")
+                read_data(filesystem, format!("Data/Map{id:0>3}.rxdata"))
+                    .expect("failed to load map")
             })
         })
     }
 
+    /// Load a map, returning an error instead of panicking if it is missing or can't be read.
+    #[allow(clippy::panic)]
+    pub fn try_get_or_load_map(
+        &self,
+        id: usize,
+        filesystem: &impl luminol_filesystem::FileSystem,
+    ) -> anyhow::Result<RefMut<'_, rpg::Map>> {
+        let mut maps_ref = match self {
+            Self::Loaded { maps, .. } => maps.borrow_mut(),
+            Self::Unloaded => panic!("project not loaded"),
+        };
+        if let std::collections::hash_map::Entry::Vacant(entry) = maps_ref.entry(id) {
+            entry.insert(
+                read_data(filesystem, format!("Data/Map{id:0>3}.rxdata"))
+                    .with_context(|| format!("while reading Map{id:0>3}.rxdata"))?,
+            );
+        }
+        Ok(RefMut::map(maps_ref, |maps| {
+            maps.get_mut(&id).expect("map not loaded")
+        }))
+    }
+
     pub fn get_map(&self, id: usize) -> RefMut<'_, rpg::Map> {
         let maps_ref = match self {
             Self::Loaded { maps, .. } => maps.borrow_mut(),
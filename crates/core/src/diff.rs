@@ -0,0 +1,869 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+//! Structural diffs and three-way merges of marshalled data files.
+//!
+//! These work on the raw marshal values rather than the editor's types,
+//! so every file in `Data/` is handled the same way.
+
+use std::borrow::Borrow;
+
+use alox_48::Value;
+
+/// A step from a value to one of its children.
+#[derive(Debug, Clone)]
+pub enum Segment {
+    /// An instance variable of an object.
+    Field(String),
+    /// An element of an array.
+    Index(usize),
+    /// The value for a key in a hash.
+    Key(Value),
+}
+
+/// Where a change or conflict is inside a file.
+#[derive(Debug, Clone, Default)]
+pub struct Path(pub Vec<Segment>);
+
+impl Path {
+    fn join(&self, segment: Segment) -> Self {
+        let mut path = self.clone();
+        path.0.push(segment);
+        path
+    }
+}
+
+impl std::fmt::Display for Path {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0.is_empty() {
+            return f.write_str("(root)");
+        }
+
+        for (i, segment) in self.0.iter().enumerate() {
+            match segment {
+                Segment::Field(name) => {
+                    if i > 0 {
+                        f.write_str(".")?;
+                    }
+                    f.write_str(name.trim_start_matches('@'))?;
+                }
+                Segment::Index(index) => write!(f, "[{index}]")?,
+                Segment::Key(key) => write!(f, "[{}]", value_to_string(key))?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A single difference between two versions of a file.
+#[derive(Debug, Clone)]
+pub struct Change {
+    pub path: Path,
+    pub description: String,
+}
+
+/// Which side of a merge to keep for a conflict.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Side {
+    #[default]
+    Ours,
+    Theirs,
+}
+
+/// A value that was changed differently on both sides of a merge.
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    pub path: Path,
+    pub description: String,
+    /// The value to use when keeping our side, or `None` if our side deleted it.
+    pub ours: Option<Value>,
+    /// The value to use when keeping their side, or `None` if their side deleted it.
+    pub theirs: Option<Value>,
+}
+
+/// The result of a three-way merge.
+#[derive(Debug, Clone)]
+pub struct Merge {
+    /// The merged value, with our side kept for every conflict.
+    pub value: Value,
+    pub conflicts: Vec<Conflict>,
+}
+
+impl Merge {
+    /// Returns the merged value with each conflict resolved to the side at the same index in `sides`.
+    /// Conflicts without a side keep ours.
+    pub fn resolve(&self, sides: &[Side]) -> Value {
+        let mut value = self.value.clone();
+        for (conflict, side) in self.conflicts.iter().zip(sides) {
+            if *side == Side::Theirs {
+                set_at(&mut value, &conflict.path.0, conflict.theirs.clone());
+            }
+        }
+        value
+    }
+
+    /// Marshals the merged value with each conflict resolved as in [`Merge::resolve`].
+    pub fn to_bytes(&self, sides: &[Side]) -> anyhow::Result<Vec<u8>> {
+        alox_48::to_bytes(&self.resolve(sides)).map_err(anyhow::Error::from)
+    }
+}
+
+/// Lists the differences between two marshalled files.
+pub fn diff_bytes(old: &[u8], new: &[u8]) -> anyhow::Result<Vec<Change>> {
+    let old: Value = alox_48::from_bytes(old)?;
+    let new: Value = alox_48::from_bytes(new)?;
+    Ok(diff(&old, &new))
+}
+
+/// Lists the differences between two values.
+///
+/// Tables are summarized per layer, and event command lists are compared line by line.
+pub fn diff(old: &Value, new: &Value) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_value(&Path::default(), old, new, &mut changes);
+    changes
+}
+
+/// Merges two marshalled files that were both changed from `base`.
+pub fn merge_bytes(base: &[u8], ours: &[u8], theirs: &[u8]) -> anyhow::Result<Merge> {
+    let base: Value = alox_48::from_bytes(base)?;
+    let ours: Value = alox_48::from_bytes(ours)?;
+    let theirs: Value = alox_48::from_bytes(theirs)?;
+    Ok(merge(&base, &ours, &theirs))
+}
+
+/// Merges two values that were both changed from `base`.
+///
+/// Changes made on only one side are applied. Objects and hashes are merged field by field,
+/// arrays element by element and tables cell by cell; anything else changed on both sides is a conflict.
+pub fn merge(base: &Value, ours: &Value, theirs: &Value) -> Merge {
+    let mut conflicts = Vec::new();
+    let value = merge_value(
+        &Path::default(),
+        Some(base),
+        Some(ours),
+        Some(theirs),
+        &mut conflicts,
+    )
+    .unwrap_or(Value::Nil);
+    Merge { value, conflicts }
+}
+
+/// Converts a value to a short, human readable string.
+pub fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::Nil => "nil".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Integer(i) => i.to_string(),
+        Value::Float(f) => f.to_string(),
+        Value::String(s) => {
+            let s = s.to_string_lossy();
+            if s.chars().count() > 48 {
+                format!("{:?}…", s.chars().take(48).collect::<String>())
+            } else {
+                format!("{s:?}")
+            }
+        }
+        Value::Symbol(s) => {
+            let s: &str = s.borrow();
+            format!(":{s}")
+        }
+        Value::Array(array) if array.len() <= 8 => format!(
+            "[{}]",
+            array
+                .iter()
+                .map(value_to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Value::Array(array) => format!("[{} elements]", array.len()),
+        Value::Hash(hash) => format!("{{{} entries}}", hash.len()),
+        Value::Object(object) => {
+            let class: &str = object.class.borrow();
+            format!("#<{class}>")
+        }
+        Value::Userdata(userdata) => match table_size(userdata) {
+            Some([x, y, z]) => format!("Table({x}×{y}×{z})"),
+            None => {
+                let class: &str = userdata.class.borrow();
+                format!("#<{class}>")
+            }
+        },
+        _ => "(unsupported value)".to_string(),
+    }
+}
+
+fn diff_value(path: &Path, old: &Value, new: &Value, changes: &mut Vec<Change>) {
+    if old == new {
+        return;
+    }
+
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) if old.class == new.class => {
+            for (key, old_value) in old.fields.iter() {
+                let name: &str = key.borrow();
+                let path = path.join(Segment::Field(name.to_string()));
+                match new.fields.get(name) {
+                    Some(new_value) => diff_value(&path, old_value, new_value, changes),
+                    None => changes.push(Change {
+                        path,
+                        description: format!("removed {}", value_to_string(old_value)),
+                    }),
+                }
+            }
+            for (key, new_value) in new.fields.iter() {
+                let name: &str = key.borrow();
+                if old.fields.get(name).is_none() {
+                    changes.push(Change {
+                        path: path.join(Segment::Field(name.to_string())),
+                        description: format!("added {}", value_to_string(new_value)),
+                    });
+                }
+            }
+        }
+
+        (Value::Array(old), Value::Array(new)) if is_command_list(old) || is_command_list(new) => {
+            diff_commands(path, old, new, changes);
+        }
+
+        (Value::Array(old), Value::Array(new)) => {
+            for index in 0..old.len().max(new.len()) {
+                let path = path.join(Segment::Index(index));
+                match (old.get(index), new.get(index)) {
+                    (Some(old), Some(new)) => diff_value(&path, old, new, changes),
+                    (Some(old), None) => changes.push(Change {
+                        path,
+                        description: format!("removed {}", value_to_string(old)),
+                    }),
+                    (None, Some(new)) => changes.push(Change {
+                        path,
+                        description: format!("added {}", value_to_string(new)),
+                    }),
+                    (None, None) => unreachable!(),
+                }
+            }
+        }
+
+        (Value::Hash(old), Value::Hash(new)) => {
+            for (key, old_value) in old.iter() {
+                let path = path.join(Segment::Key(key.clone()));
+                match new.get(key) {
+                    Some(new_value) => diff_value(&path, old_value, new_value, changes),
+                    None => changes.push(Change {
+                        path,
+                        description: format!("removed {}", value_to_string(old_value)),
+                    }),
+                }
+            }
+            for (key, new_value) in new.iter() {
+                if !old.contains_key(key) {
+                    changes.push(Change {
+                        path: path.join(Segment::Key(key.clone())),
+                        description: format!("added {}", value_to_string(new_value)),
+                    });
+                }
+            }
+        }
+
+        (Value::Userdata(old), Value::Userdata(new)) => match (table_size(old), table_size(new)) {
+            (Some(old_size), Some(new_size)) if old_size == new_size => {
+                diff_table(path, old_size, &old.data, &new.data, changes);
+            }
+            (Some([ox, oy, oz]), Some([nx, ny, nz])) => changes.push(Change {
+                path: path.clone(),
+                description: format!("resized from {ox}×{oy}×{oz} to {nx}×{ny}×{nz}"),
+            }),
+            _ => changes.push(Change {
+                path: path.clone(),
+                description: "changed".to_string(),
+            }),
+        },
+
+        _ => changes.push(Change {
+            path: path.clone(),
+            description: format!("{} → {}", value_to_string(old), value_to_string(new)),
+        }),
+    }
+}
+
+/// Compares two command lists with a longest common subsequence, so that inserting
+/// a command doesn't show every command after it as changed.
+fn diff_commands(path: &Path, old: &[Value], new: &[Value], changes: &mut Vec<Change>) {
+    let width = new.len() + 1;
+    let mut lengths = vec![0u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i * width + j] = if old[i] == new[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if j < new.len()
+            && (i == old.len() || lengths[i * width + j + 1] >= lengths[(i + 1) * width + j])
+        {
+            changes.push(Change {
+                path: path.clone(),
+                description: format!("line {}: + {}", j + 1, command_to_string(&new[j])),
+            });
+            j += 1;
+        } else {
+            changes.push(Change {
+                path: path.clone(),
+                description: format!("line {}: - {}", i + 1, command_to_string(&old[i])),
+            });
+            i += 1;
+        }
+    }
+}
+
+fn diff_table(
+    path: &Path,
+    [xsize, ysize, zsize]: [usize; 3],
+    old: &[u8],
+    new: &[u8],
+    changes: &mut Vec<Change>,
+) {
+    let layer_size = (xsize * ysize).max(1);
+    // Changed cell count and bounding box of the changes in each layer
+    let mut layers = vec![(0usize, usize::MAX, usize::MAX, 0usize, 0usize); zsize.max(1)];
+    for (index, (old, new)) in table_cells(old).zip(table_cells(new)).enumerate() {
+        if old == new {
+            continue;
+        }
+        let (x, y, z) = (
+            index % xsize.max(1),
+            (index % layer_size) / xsize.max(1),
+            index / layer_size,
+        );
+        if let Some(layer) = layers.get_mut(z) {
+            layer.0 += 1;
+            layer.1 = layer.1.min(x);
+            layer.2 = layer.2.min(y);
+            layer.3 = layer.3.max(x);
+            layer.4 = layer.4.max(y);
+        }
+    }
+
+    for (z, (count, min_x, min_y, max_x, max_y)) in layers.into_iter().enumerate() {
+        if count == 0 {
+            continue;
+        }
+        let description = if ysize <= 1 && zsize <= 1 {
+            format!("{count} of {xsize} entries changed")
+        } else if zsize <= 1 {
+            format!("{count} cells changed between ({min_x}, {min_y}) and ({max_x}, {max_y})")
+        } else {
+            format!(
+                "layer {}: {count} tiles changed between ({min_x}, {min_y}) and ({max_x}, {max_y})",
+                z + 1
+            )
+        };
+        changes.push(Change {
+            path: path.clone(),
+            description,
+        });
+    }
+}
+
+fn merge_value(
+    path: &Path,
+    base: Option<&Value>,
+    ours: Option<&Value>,
+    theirs: Option<&Value>,
+    conflicts: &mut Vec<Conflict>,
+) -> Option<Value> {
+    if ours == theirs || base == theirs {
+        return ours.cloned();
+    }
+    if base == ours {
+        return theirs.cloned();
+    }
+
+    let (Some(base), Some(ours), Some(theirs)) = (base, ours, theirs) else {
+        return conflict(path, base, ours, theirs, conflicts);
+    };
+
+    match (base, ours, theirs) {
+        (Value::Object(b), Value::Object(o), Value::Object(t))
+            if b.class == o.class && o.class == t.class =>
+        {
+            let mut keys = Vec::new();
+            for key in b
+                .fields
+                .keys()
+                .chain(o.fields.keys())
+                .chain(t.fields.keys())
+            {
+                if !keys.contains(&key) {
+                    keys.push(key);
+                }
+            }
+
+            let mut fields = o.fields.clone();
+            fields.clear();
+            for key in keys {
+                let name: &str = key.borrow();
+                if let Some(value) = merge_value(
+                    &path.join(Segment::Field(name.to_string())),
+                    b.fields.get(name),
+                    o.fields.get(name),
+                    t.fields.get(name),
+                    conflicts,
+                ) {
+                    fields.insert(key.clone(), value);
+                }
+            }
+
+            Some(Value::Object(alox_48::Object {
+                class: o.class.clone(),
+                fields,
+            }))
+        }
+
+        // Inserting or removing commands shifts every command after it,
+        // so command lists are only merged line by line if no side did either
+        (Value::Array(b), Value::Array(o), Value::Array(t))
+            if (is_command_list(b) || is_command_list(o) || is_command_list(t))
+                && !(b.len() == o.len() && o.len() == t.len()) =>
+        {
+            conflict(path, Some(base), Some(ours), Some(theirs), conflicts)
+        }
+
+        (Value::Array(b), Value::Array(o), Value::Array(t)) => {
+            let conflict_count = conflicts.len();
+            let mut merged = Vec::with_capacity(o.len().max(t.len()));
+            let mut ended = false;
+            for index in 0..b.len().max(o.len()).max(t.len()) {
+                match merge_value(
+                    &path.join(Segment::Index(index)),
+                    b.get(index),
+                    o.get(index),
+                    t.get(index),
+                    conflicts,
+                ) {
+                    Some(value) if !ended => merged.push(value),
+                    // An element was removed from the middle of the array
+                    Some(_) => {
+                        conflicts.truncate(conflict_count);
+                        return conflict(path, Some(base), Some(ours), Some(theirs), conflicts);
+                    }
+                    None => ended = true,
+                }
+            }
+
+            // Resolving a conflict over whether an element exists would shift every element
+            // after it, so the whole array conflicts instead
+            let depth = path.0.len() + 1;
+            if conflicts[conflict_count..].iter().any(|conflict| {
+                conflict.path.0.len() == depth
+                    && (conflict.ours.is_none() || conflict.theirs.is_none())
+            }) {
+                conflicts.truncate(conflict_count);
+                return conflict(path, Some(base), Some(ours), Some(theirs), conflicts);
+            }
+            Some(Value::Array(merged))
+        }
+
+        (Value::Hash(b), Value::Hash(o), Value::Hash(t)) => {
+            let mut keys = Vec::new();
+            for key in b.keys().chain(o.keys()).chain(t.keys()) {
+                if !keys.contains(&key) {
+                    keys.push(key);
+                }
+            }
+
+            let mut merged = o.clone();
+            merged.clear();
+            for key in keys {
+                if let Some(value) = merge_value(
+                    &path.join(Segment::Key(key.clone())),
+                    b.get(key),
+                    o.get(key),
+                    t.get(key),
+                    conflicts,
+                ) {
+                    merged.insert(key.clone(), value);
+                }
+            }
+            Some(Value::Hash(merged))
+        }
+
+        (Value::Userdata(b), Value::Userdata(o), Value::Userdata(t))
+            if table_size(b).is_some()
+                && b.data.len() == o.data.len()
+                && o.data.len() == t.data.len()
+                && b.data.get(..TABLE_HEADER_LEN) == o.data.get(..TABLE_HEADER_LEN)
+                && o.data.get(..TABLE_HEADER_LEN) == t.data.get(..TABLE_HEADER_LEN) =>
+        {
+            // Cells changed on both sides keep ours in `merged` and theirs in `alternative`
+            let mut merged = o.data.clone();
+            let mut alternative = o.data.clone();
+            let mut conflicting = 0;
+            for start in (TABLE_HEADER_LEN..merged.len().saturating_sub(1)).step_by(2) {
+                let cell = start..start + 2;
+                let (b, o, t) = (
+                    &b.data[cell.clone()],
+                    &o.data[cell.clone()],
+                    &t.data[cell.clone()],
+                );
+                if o == t || b == t {
+                    continue;
+                }
+                if b != o {
+                    conflicting += 1;
+                } else {
+                    merged[cell.clone()].copy_from_slice(t);
+                }
+                alternative[cell].copy_from_slice(t);
+            }
+
+            let table = |data| {
+                Value::Userdata(alox_48::Userdata {
+                    class: o.class.clone(),
+                    data,
+                })
+            };
+            if conflicting > 0 {
+                conflicts.push(Conflict {
+                    path: path.clone(),
+                    description: format!("{conflicting} cells changed differently on both sides"),
+                    ours: Some(table(merged.clone())),
+                    theirs: Some(table(alternative)),
+                });
+            }
+            Some(table(merged))
+        }
+
+        _ => conflict(path, Some(base), Some(ours), Some(theirs), conflicts),
+    }
+}
+
+/// Records a conflict and keeps our side.
+fn conflict(
+    path: &Path,
+    base: Option<&Value>,
+    ours: Option<&Value>,
+    theirs: Option<&Value>,
+    conflicts: &mut Vec<Conflict>,
+) -> Option<Value> {
+    let description = match (base, ours, theirs) {
+        (None, Some(_), Some(_)) => "added differently on both sides".to_string(),
+        (_, None, Some(_)) => "removed on our side but changed on theirs".to_string(),
+        (_, Some(_), None) => "changed on our side but removed on theirs".to_string(),
+        (_, Some(ours), Some(theirs)) => {
+            let (ours, theirs) = (value_to_string(ours), value_to_string(theirs));
+            if ours == theirs {
+                "changed differently on both sides".to_string()
+            } else {
+                format!("ours: {ours}, theirs: {theirs}")
+            }
+        }
+        (_, None, None) => "removed on both sides".to_string(),
+    };
+
+    conflicts.push(Conflict {
+        path: path.clone(),
+        description,
+        ours: ours.cloned(),
+        theirs: theirs.cloned(),
+    });
+    ours.cloned()
+}
+
+fn child_mut<'a>(value: &'a mut Value, segment: &Segment) -> Option<&'a mut Value> {
+    match (value, segment) {
+        (Value::Object(object), Segment::Field(name)) => object.fields.get_mut(name.as_str()),
+        (Value::Array(array), Segment::Index(index)) => array.get_mut(*index),
+        (Value::Hash(hash), Segment::Key(key)) => hash.get_mut(key),
+        _ => None,
+    }
+}
+
+/// Replaces the value at `path`, removing it if `new` is `None`.
+fn set_at(value: &mut Value, path: &[Segment], new: Option<Value>) {
+    let Some((last, parents)) = path.split_last() else {
+        if let Some(new) = new {
+            *value = new;
+        }
+        return;
+    };
+    let Some(parent) = parents
+        .iter()
+        .try_fold(value, |value, segment| child_mut(value, segment))
+    else {
+        return;
+    };
+
+    match (parent, last, new) {
+        (Value::Object(object), Segment::Field(name), Some(new)) => {
+            match object.fields.get_mut(name.as_str()) {
+                Some(slot) => *slot = new,
+                None => {
+                    object.fields.insert(name.as_str().into(), new);
+                }
+            }
+        }
+        (Value::Object(object), Segment::Field(name), None) => {
+            object.fields.retain(|key, _| {
+                let key: &str = key.borrow();
+                key != name
+            });
+        }
+        (Value::Array(array), Segment::Index(index), Some(new)) => match array.get_mut(*index) {
+            Some(slot) => *slot = new,
+            None if *index == array.len() => array.push(new),
+            // Merges never leave a gap before a conflicting element
+            None => {}
+        },
+        (Value::Array(array), Segment::Index(index), None) => {
+            if *index < array.len() {
+                array.remove(*index);
+            }
+        }
+        (Value::Hash(hash), Segment::Key(key), Some(new)) => {
+            hash.insert(key.clone(), new);
+        }
+        (Value::Hash(hash), Segment::Key(key), None) => hash.retain(|k, _| k != key),
+        _ => {}
+    }
+}
+
+fn is_command_list(array: &[Value]) -> bool {
+    array.first().is_some_and(|value| {
+        matches!(value, Value::Object(object)
+            if object.class == "RPG::EventCommand" || object.class == "RPG::MoveCommand")
+    })
+}
+
+fn command_to_string(command: &Value) -> String {
+    let Value::Object(object) = command else {
+        return value_to_string(command);
+    };
+    let indent = match object.fields.get("indent") {
+        Some(Value::Integer(indent)) => usize::try_from(i64::from(*indent)).unwrap_or_default(),
+        _ => 0,
+    };
+    let code = object
+        .fields
+        .get("code")
+        .map_or_else(String::new, value_to_string);
+    let parameters = object
+        .fields
+        .get("parameters")
+        .map_or_else(String::new, value_to_string);
+    format!("{}{code} {parameters}", "  ".repeat(indent))
+}
+
+/// The dimension count, sizes and cell count at the start of a table's data.
+const TABLE_HEADER_LEN: usize = 20;
+
+fn table_size(userdata: &alox_48::Userdata) -> Option<[usize; 3]> {
+    if userdata.class != "Table" {
+        return None;
+    }
+    let word = |index: usize| {
+        userdata
+            .data
+            .get(index * 4..index * 4 + 4)
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(|bytes| u32::from_le_bytes(bytes) as usize)
+    };
+    Some([word(1)?, word(2)?, word(3)?])
+}
+
+fn table_cells(data: &[u8]) -> impl Iterator<Item = i16> + '_ {
+    data.get(TABLE_HEADER_LEN..)
+        .unwrap_or_default()
+        .chunks_exact(2)
+        .map(|bytes| i16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use luminol_data::rpg;
+
+    fn value(data: &impl serde::Serialize) -> Value {
+        alox_48::from_bytes(&alox_48::to_bytes(data).unwrap()).unwrap()
+    }
+
+    fn resolved<T: serde::de::DeserializeOwned>(merge: &Merge, sides: &[Side]) -> T {
+        alox_48::from_bytes(&merge.to_bytes(sides).unwrap()).unwrap()
+    }
+
+    fn item(name: &str, price: i32) -> rpg::Item {
+        rpg::Item {
+            name: name.to_string(),
+            price,
+            ..Default::default()
+        }
+    }
+
+    fn command(code: u16, text: &str) -> rpg::EventCommand {
+        rpg::EventCommand {
+            code,
+            indent: 0,
+            parameters: vec![text.into()],
+            guid: 0,
+        }
+    }
+
+    #[test]
+    fn merges_objects_field_by_field() {
+        let base = value(&item("Potion", 50));
+
+        let merge = merge(
+            &base,
+            &value(&item("Hi-Potion", 50)),
+            &value(&item("Potion", 100)),
+        );
+        assert!(merge.conflicts.is_empty());
+        let merged: rpg::Item = resolved(&merge, &[]);
+        assert_eq!((merged.name.as_str(), merged.price), ("Hi-Potion", 100));
+
+        let merge = super::merge(
+            &base,
+            &value(&item("Potion", 60)),
+            &value(&item("Potion", 70)),
+        );
+        assert_eq!(merge.conflicts.len(), 1);
+        assert_eq!(merge.conflicts[0].path.to_string(), "price");
+        assert_eq!(resolved::<rpg::Item>(&merge, &[]).price, 60);
+        assert_eq!(resolved::<rpg::Item>(&merge, &[Side::Theirs]).price, 70);
+    }
+
+    #[test]
+    fn merges_hashes_key_by_key() {
+        let hash = |entries: &[(i32, &str)]| {
+            value(&std::collections::HashMap::<i32, String>::from_iter(
+                entries.iter().map(|&(k, v)| (k, v.to_string())),
+            ))
+        };
+        let merge = merge(
+            &hash(&[(1, "a"), (2, "b")]),
+            &hash(&[(1, "A"), (2, "b"), (3, "c")]),
+            &hash(&[(1, "a")]),
+        );
+        assert!(merge.conflicts.is_empty());
+        let merged: std::collections::HashMap<i32, String> = resolved(&merge, &[]);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[&1], "A");
+        assert_eq!(merged[&3], "c");
+    }
+
+    #[test]
+    fn merges_tables_cell_by_cell() {
+        let table = |cells: [i16; 4]| {
+            let mut table = luminol_data::Table1::new(4);
+            table.as_mut_slice().copy_from_slice(&cells);
+            value(&table)
+        };
+        let merge = merge(
+            &table([0, 0, 0, 0]),
+            &table([1, 0, 0, 3]),
+            &table([0, 2, 0, 5]),
+        );
+        assert_eq!(merge.conflicts.len(), 1);
+        let ours: luminol_data::Table1 = resolved(&merge, &[]);
+        assert_eq!(ours.as_slice(), [1, 2, 0, 3]);
+        let theirs: luminol_data::Table1 = resolved(&merge, &[Side::Theirs]);
+        assert_eq!(theirs.as_slice(), [1, 2, 0, 5]);
+    }
+
+    #[test]
+    fn merges_arrays_element_by_element() {
+        let merge = merge(
+            &value(&vec![1, 2, 3]),
+            &value(&vec![1, 9, 3]),
+            &value(&vec![1, 2, 3, 4]),
+        );
+        assert!(merge.conflicts.is_empty());
+        assert_eq!(resolved::<Vec<i32>>(&merge, &[]), [1, 9, 3, 4]);
+
+        // Ours removed elements that theirs changed
+        let merge = super::merge(
+            &value(&vec![1, 2, 3, 4]),
+            &value(&vec![1, 2]),
+            &value(&vec![1, 2, 7, 8]),
+        );
+        assert_eq!(merge.conflicts.len(), 1);
+        assert!(merge.conflicts[0].path.0.is_empty());
+        assert_eq!(resolved::<Vec<i32>>(&merge, &[]), [1, 2]);
+        assert_eq!(resolved::<Vec<i32>>(&merge, &[Side::Theirs]), [1, 2, 7, 8]);
+    }
+
+    #[test]
+    fn merges_command_lists() {
+        let base = value(&vec![command(101, "Hello"), command(101, "Bye")]);
+
+        // Both sides only changed lines in place
+        let merge = merge(
+            &base,
+            &value(&vec![command(101, "Hi"), command(101, "Bye")]),
+            &value(&vec![command(101, "Hello"), command(101, "Farewell")]),
+        );
+        assert!(merge.conflicts.is_empty());
+        assert_eq!(
+            merge.value,
+            value(&vec![command(101, "Hi"), command(101, "Farewell")])
+        );
+
+        // Inserting a line conflicts with any other change
+        let theirs = value(&vec![command(101, "Hello"), command(101, "Farewell")]);
+        let merge = super::merge(
+            &base,
+            &value(&vec![
+                command(101, "Hello"),
+                command(401, "there"),
+                command(101, "Bye"),
+            ]),
+            &theirs,
+        );
+        assert_eq!(merge.conflicts.len(), 1);
+        assert!(merge.conflicts[0].path.0.is_empty());
+        assert_eq!(merge.resolve(&[Side::Theirs]), theirs);
+    }
+
+    #[test]
+    fn diffs_command_lists_line_by_line() {
+        let changes = diff(
+            &value(&vec![command(101, "Hello"), command(101, "Bye")]),
+            &value(&vec![
+                command(101, "Hello"),
+                command(401, "there"),
+                command(101, "Bye"),
+            ]),
+        );
+        assert_eq!(changes.len(), 1);
+        assert!(changes[0].description.starts_with("line 2: + 401"));
+    }
+}
@@ -0,0 +1,211 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+//! Picking up changes made to the project by other programs, like an image editor or git.
+
+use luminol_filesystem::FileSystem;
+
+/// How often to check for changes when the operating system notifies us of them, in seconds.
+const NOTIFY_INTERVAL: f64 = 1.;
+/// How often to check for changes when the whole project has to be rescanned, in seconds.
+const POLLING_INTERVAL: f64 = 5.;
+
+#[derive(Default)]
+pub struct FileWatcher {
+    watcher: Option<luminol_filesystem::watch::Watcher>,
+    project_path: Option<camino::Utf8PathBuf>,
+    last_check: f64,
+}
+
+impl FileWatcher {
+    /// Reloads graphics that changed since the last check, and queues changed data files in
+    /// [`crate::ProjectManager::changed_data_files`]. Returns true if any were queued.
+    pub fn update(&mut self, update_state: &mut crate::UpdateState<'_>) -> bool {
+        // Start watching again whenever a different project is opened
+        let project_path = update_state.filesystem.project_path();
+        if project_path != self.project_path {
+            self.project_path = project_path;
+            self.watcher = None;
+            if self.project_path.is_some() {
+                match update_state.filesystem.watch() {
+                    Ok(watcher) => self.watcher = Some(watcher),
+                    Err(e) => update_state
+                        .toasts
+                        .warning(format!("Unable to watch the project for changes: {e}")),
+                }
+            }
+        }
+
+        let Some(watcher) = &mut self.watcher else {
+            return false;
+        };
+        if !matches!(update_state.data, crate::Data::Loaded { .. }) {
+            return false;
+        }
+
+        let now = update_state.ctx.input(|i| i.time);
+        let interval = if watcher.is_polling() {
+            POLLING_INTERVAL
+        } else {
+            NOTIFY_INTERVAL
+        };
+        let elapsed = now - self.last_check;
+        if elapsed < interval {
+            update_state
+                .ctx
+                .request_repaint_after(std::time::Duration::from_secs_f64(interval - elapsed));
+            return false;
+        }
+        self.last_check = now;
+
+        let changes = match update_state.filesystem.collect_changes(watcher) {
+            Ok(changes) => changes,
+            Err(e) => {
+                update_state
+                    .toasts
+                    .warning(format!("Error checking the project for changes: {e}"));
+                return false;
+            }
+        };
+
+        let mut queued = false;
+        for change in changes {
+            let path = change.path();
+            // Skip hidden directories like .git and .luminol, which Luminol writes to itself
+            if path.iter().any(|component| component.starts_with('.')) {
+                continue;
+            }
+
+            match path.iter().next() {
+                Some(dir) if dir.eq_ignore_ascii_case("Graphics") => {
+                    reload_graphic(update_state, path);
+                }
+                Some(dir) if dir.eq_ignore_ascii_case("Data") => {
+                    if !matches!(change, luminol_filesystem::watch::Change::Removed(_)) {
+                        queued |= queue_data_file(update_state, path);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        queued
+    }
+}
+
+/// Reloads the textures and tileset atlases that use the graphic at `path`, after it changed.
+pub fn reload_graphic(update_state: &mut crate::UpdateState<'_>, path: &camino::Utf8Path) {
+    if let Err(e) = update_state
+        .graphics
+        .texture_loader
+        .reload_changed(update_state.filesystem, path)
+    {
+        update_state
+            .toasts
+            .warning(format!("Error reloading {path}: {e:#}"));
+    }
+    update_state
+        .bytes_loader
+        .forget_changed(update_state.ctx, path);
+
+    // Tilesets and autotiles are copied into atlases, which have to be rebuilt
+    let Some(parent) = path.parent() else {
+        return;
+    };
+    let is_tileset = parent.as_str().eq_ignore_ascii_case("Graphics/Tilesets");
+    let is_autotile = parent.as_str().eq_ignore_ascii_case("Graphics/Autotiles");
+    if !is_tileset && !is_autotile {
+        return;
+    }
+
+    let matches = |name: &str| {
+        camino::Utf8Path::new(name)
+            .with_extension("")
+            .as_str()
+            .eq_ignore_ascii_case(path.with_extension("").file_name().unwrap_or_default())
+    };
+    let tilesets = update_state.data.tilesets();
+    for tileset in tilesets.data.iter().filter(|tileset| {
+        if is_tileset {
+            tileset
+                .tileset_name
+                .as_ref()
+                .is_some_and(|name| matches(name.as_str()))
+        } else {
+            tileset
+                .autotile_names
+                .iter()
+                .any(|name| matches(name.as_str()))
+        }
+    }) {
+        if let Err(e) = update_state.graphics.atlas_loader.reload_changed(
+            &update_state.graphics,
+            update_state.filesystem,
+            tileset,
+        ) {
+            update_state.toasts.warning(format!(
+                "Error reloading the atlas for tileset {:0>3}: {e:#}",
+                tileset.id
+            ));
+        }
+    }
+}
+
+/// Queues a data file to be reloaded if it's different from the cached version.
+fn queue_data_file(update_state: &mut crate::UpdateState<'_>, path: &camino::Utf8Path) -> bool {
+    let Some(config) = update_state.project_config.as_ref() else {
+        return false;
+    };
+    if path.parent().map(camino::Utf8Path::as_str) != Some("Data") {
+        return false;
+    }
+    let Some(filename) = path.file_name() else {
+        return false;
+    };
+    if update_state
+        .project_manager
+        .changed_data_files
+        .iter()
+        .any(|queued| queued == filename)
+    {
+        return false;
+    }
+
+    // Files Luminol saved itself are identical to the cached version, and maps that were never
+    // opened aren't cached at all, so neither needs reloading
+    let Ok(Some(cached)) = update_state.data.serialize_file(filename, config) else {
+        return false;
+    };
+    match update_state.filesystem.read(path) {
+        Ok(bytes) if bytes == cached => return false,
+        Ok(_) => {}
+        Err(_) => return false,
+    }
+
+    update_state
+        .project_manager
+        .changed_data_files
+        .push(filename.to_string());
+    true
+}
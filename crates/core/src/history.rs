@@ -0,0 +1,394 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+//! The project-wide undo history.
+//!
+//! Editors push an [`Edit`] holding the previous state of whatever they changed,
+//! tagged with the id of the tab or window that made the change.
+//! Edits are undone strictly in the order they were made, and the undo and redo shortcuts only
+//! apply while the tab or window that made the latest edit is the one the user last clicked in.
+
+use std::cell::RefMut;
+use std::collections::VecDeque;
+
+use luminol_data::rpg;
+
+use crate::Data;
+
+/// The maximum number of edits that can be undone.
+const HISTORY_SIZE: usize = 100;
+
+/// A reversible change to the data cache.
+pub trait Edit {
+    /// Swaps the edited data with the copy stored in this edit.
+    ///
+    /// Swapping twice restores both, so this both undoes and redoes the edit.
+    fn swap(&mut self, data: &Data);
+}
+
+type Getter<T, K> = for<'a> fn(&'a Data, K) -> Option<RefMut<'a, T>>;
+
+/// An edit storing the previous value of one part of the data cache, found by `key`.
+///
+/// If that part doesn't exist anymore when the edit is undone or redone, nothing happens.
+pub struct Swap<T, K = usize> {
+    get: Getter<T, K>,
+    key: K,
+    value: T,
+}
+
+impl<T, K: Copy> Edit for Swap<T, K> {
+    fn swap(&mut self, data: &Data) {
+        if let Some(mut current) = (self.get)(data, self.key) {
+            std::mem::swap(&mut *current, &mut self.value);
+        }
+    }
+}
+
+macro_rules! entry_swaps {
+    ($($name:ident, $getter:ident, $typ:ident),* $(,)?) => {
+        $(
+            impl Swap<rpg::$typ> {
+                #[doc = concat!("Stores the previous value of the `", stringify!($typ), "` at `index`.")]
+                pub fn $name(index: usize, previous: rpg::$typ) -> Self {
+                    fn get(data: &Data, index: usize) -> Option<RefMut<'_, rpg::$typ>> {
+                        RefMut::filter_map(data.$getter(), |container| {
+                            container.modified = true;
+                            container.data.get_mut(index)
+                        })
+                        .ok()
+                    }
+
+                    Self {
+                        get,
+                        key: index,
+                        value: previous,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+entry_swaps! {
+    common_event, common_events, CommonEvent,
+    item, items, Item,
+}
+
+impl Swap<Vec<rpg::Script>> {
+    /// Stores the previous list of scripts, for edits that insert, delete or reorder them.
+    pub fn scripts(previous: Vec<rpg::Script>) -> Self {
+        fn get(data: &Data, _: usize) -> Option<RefMut<'_, Vec<rpg::Script>>> {
+            Some(RefMut::map(data.scripts(), |scripts| {
+                scripts.modified = true;
+                &mut scripts.data
+            }))
+        }
+
+        Self {
+            get,
+            key: 0,
+            value: previous,
+        }
+    }
+}
+
+impl Swap<String> {
+    /// Stores the previous name of the script at `index`.
+    pub fn script_name(index: usize, previous: String) -> Self {
+        fn get(data: &Data, index: usize) -> Option<RefMut<'_, String>> {
+            RefMut::filter_map(data.scripts(), |scripts| {
+                scripts.modified = true;
+                scripts.data.get_mut(index).map(|script| &mut script.name)
+            })
+            .ok()
+        }
+
+        Self {
+            get,
+            key: index,
+            value: previous,
+        }
+    }
+
+    /// Stores the previous text of the script at `index`.
+    pub fn script_text(index: usize, previous: String) -> Self {
+        fn get(data: &Data, index: usize) -> Option<RefMut<'_, String>> {
+            RefMut::filter_map(data.scripts(), |scripts| {
+                scripts.modified = true;
+                scripts
+                    .data
+                    .get_mut(index)
+                    .map(|script| &mut script.script_text)
+            })
+            .ok()
+        }
+
+        Self {
+            get,
+            key: index,
+            value: previous,
+        }
+    }
+
+    /// Stores the previous name of the map with the given id.
+    pub fn map_name(map_id: usize, previous: String) -> Self {
+        fn get(data: &Data, map_id: usize) -> Option<RefMut<'_, String>> {
+            RefMut::filter_map(data.map_infos(), |map_infos| {
+                map_infos.modified = true;
+                map_infos.data.get_mut(&map_id).map(|info| &mut info.name)
+            })
+            .ok()
+        }
+
+        Self {
+            get,
+            key: map_id,
+            value: previous,
+        }
+    }
+}
+
+impl Swap<String, (usize, usize)> {
+    /// Stores the previous name of the event with the id `event_id` on the map `map_id`, which
+    /// has to be loaded.
+    pub fn event_name(map_id: usize, event_id: usize, previous: String) -> Self {
+        fn get(data: &Data, (map_id, event_id): (usize, usize)) -> Option<RefMut<'_, String>> {
+            RefMut::filter_map(data.get_map(map_id), |map| {
+                map.modified = true;
+                map.events.get_mut(event_id).map(|event| &mut event.name)
+            })
+            .ok()
+        }
+
+        Self {
+            get,
+            key: (map_id, event_id),
+            value: previous,
+        }
+    }
+}
+
+/// An edit in the history.
+pub struct Entry {
+    /// The id of the tab or window that made the edit.
+    pub scope: egui::Id,
+    pub description: String,
+    /// Consecutive edits with the same key are kept as one entry, so that typing into a field
+    /// is undone in one step.
+    merge_key: Option<egui::Id>,
+    edit: Box<dyn Edit>,
+}
+
+/// The undo and redo history of the project.
+#[derive(Default)]
+pub struct History {
+    undo: VecDeque<Entry>,
+    redo: Vec<Entry>,
+    /// The tab or window the user last clicked in.
+    focus: Option<egui::Id>,
+}
+
+impl History {
+    /// Adds an edit that was just made in the tab or window with the id `scope`.
+    pub fn push(
+        &mut self,
+        scope: egui::Id,
+        description: impl Into<String>,
+        edit: impl Edit + 'static,
+    ) {
+        self.push_entry(Entry {
+            scope,
+            description: description.into(),
+            merge_key: None,
+            edit: Box::new(edit),
+        });
+    }
+
+    /// Like [`History::push`], but if the last edit was pushed with the same `merge_key`
+    /// this edit is merged into it instead of being added.
+    ///
+    /// Use the id of a text field as the key to undo everything typed into it at once.
+    pub fn push_merged(
+        &mut self,
+        scope: egui::Id,
+        merge_key: egui::Id,
+        description: impl Into<String>,
+        edit: impl Edit + 'static,
+    ) {
+        if self.redo.is_empty()
+            && self
+                .undo
+                .back()
+                .is_some_and(|entry| entry.merge_key == Some(merge_key))
+        {
+            return;
+        }
+
+        self.push_entry(Entry {
+            scope,
+            description: description.into(),
+            merge_key: Some(merge_key),
+            edit: Box::new(edit),
+        });
+    }
+
+    fn push_entry(&mut self, entry: Entry) {
+        self.redo.clear();
+        if self.undo.len() == HISTORY_SIZE {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(entry);
+    }
+
+    /// Makes the tab or window with the id `scope` the target of undo and redo shortcuts
+    /// if the user clicked inside `ui` this frame.
+    pub fn focus_on_click(&mut self, ui: &egui::Ui, scope: egui::Id) {
+        if ui.ui_contains_pointer() && ui.input(|i| i.pointer.any_pressed()) {
+            self.focus = Some(scope);
+        }
+    }
+
+    /// Returns whether undo and redo shortcuts apply to the tab or window with the id `scope`.
+    pub fn is_focused(&self, scope: egui::Id) -> bool {
+        self.focus.is_none() || self.focus == Some(scope)
+    }
+
+    /// Undoes the last edit, if it was made in the focused tab or window.
+    /// Returns `false` if there was nothing to undo.
+    ///
+    /// Edits are always undone in the order they were made, since later edits can depend on
+    /// earlier ones. Older edits in the focused tab or window have to wait until the newer ones
+    /// elsewhere are undone, e.g. from the history window.
+    pub fn undo(&mut self, data: &Data) -> bool {
+        if !self
+            .undo
+            .back()
+            .is_some_and(|entry| self.is_focused(entry.scope))
+        {
+            return false;
+        }
+
+        let mut entry = self.undo.pop_back().expect("undo history is empty");
+        entry.edit.swap(data);
+        self.redo.push(entry);
+        true
+    }
+
+    /// Redoes the last undone edit, if it was made in the focused tab or window.
+    /// Returns `false` if there was nothing to redo.
+    pub fn redo(&mut self, data: &Data) -> bool {
+        if !self
+            .redo
+            .last()
+            .is_some_and(|entry| self.is_focused(entry.scope))
+        {
+            return false;
+        }
+
+        let mut entry = self.redo.pop().expect("redo history is empty");
+        entry.edit.swap(data);
+        self.undo.push_back(entry);
+        true
+    }
+
+    /// Undoes or redoes edits in any tab or window until `len` edits are left to undo.
+    pub fn travel_to(&mut self, len: usize, data: &Data) {
+        while self.undo.len() > len {
+            let Some(mut entry) = self.undo.pop_back() else {
+                break;
+            };
+            entry.edit.swap(data);
+            self.redo.push(entry);
+        }
+        while self.undo.len() < len {
+            let Some(mut entry) = self.redo.pop() else {
+                break;
+            };
+            entry.edit.swap(data);
+            self.undo.push_back(entry);
+        }
+    }
+
+    /// The edits that can be undone, oldest first.
+    pub fn undo_entries(&self) -> impl DoubleEndedIterator<Item = &Entry> + ExactSizeIterator {
+        self.undo.iter()
+    }
+
+    /// The edits that can be redone, next to be redone first.
+    pub fn redo_entries(&self) -> impl DoubleEndedIterator<Item = &Entry> + ExactSizeIterator {
+        self.redo.iter().rev()
+    }
+
+    /// Removes every edit, e.g. when the project is closed or data is changed in bulk, since the
+    /// indices stored in edits may point at different entries afterwards.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.focus = None;
+    }
+}
+
+/// Returns whether the undo shortcut was pressed.
+pub fn is_undo_pressed(input: &egui::InputState) -> bool {
+    input.modifiers.command && !input.modifiers.shift && input.key_pressed(egui::Key::Z)
+}
+
+/// Returns whether one of the redo shortcuts was pressed.
+pub fn is_redo_pressed(input: &egui::InputState) -> bool {
+    input.modifiers.command
+        && (input.modifiers.shift || input.key_pressed(egui::Key::Y))
+        && (!input.modifiers.shift || input.key_pressed(egui::Key::Z))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undo_is_last_in_first_out() {
+        let data = Data::from_defaults();
+        let mut history = History::default();
+        let (a, b) = (egui::Id::new("a"), egui::Id::new("b"));
+
+        data.scripts().data = vec![rpg::Script {
+            name: String::new(),
+            script_text: String::new(),
+        }];
+        let previous = std::mem::replace(&mut data.scripts().data[0].name, "One".to_string());
+        history.push(a, "Rename", Swap::script_name(0, previous));
+        let previous = std::mem::replace(&mut data.scripts().data[0].name, "Two".to_string());
+        history.push(b, "Rename", Swap::script_name(0, previous));
+
+        assert!(history.undo(&data));
+        assert_eq!(data.scripts().data[0].name, "One");
+        assert!(history.redo(&data));
+        assert_eq!(data.scripts().data[0].name, "Two");
+
+        // Edits to entries that no longer exist are skipped instead of panicking
+        data.scripts().data.clear();
+        history.travel_to(0, &data);
+        assert!(data.scripts().data.is_empty());
+    }
+}
@@ -0,0 +1,264 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+//! Importing images into the project's `Graphics` folder.
+
+use anyhow::Context;
+use camino::Utf8PathBuf;
+use luminol_filesystem::project::Source;
+use luminol_filesystem::watch::Change;
+use luminol_filesystem::FileSystem as _;
+
+/// The kinds of graphics RPG Maker XP loads, each from its own folder.
+#[derive(
+    Default,
+    strum::EnumIter,
+    strum::Display,
+    PartialEq,
+    Eq,
+    Clone,
+    Copy,
+    Debug
+)]
+pub enum Kind {
+    #[default]
+    Characters,
+    Battlers,
+    Tilesets,
+    Autotiles,
+    Icons,
+    Pictures,
+    Animations,
+    Panoramas,
+    Fogs,
+    Battlebacks,
+    Titles,
+    Gameovers,
+    Windowskins,
+    Transitions,
+}
+
+impl Kind {
+    pub fn directory(self) -> Utf8PathBuf {
+        Utf8PathBuf::from("Graphics").join(self.to_string())
+    }
+
+    /// Describes why an image of this size can't be used as this kind of graphic, if it can't.
+    pub fn size_problem(self, width: u32, height: u32) -> Option<String> {
+        match self {
+            Self::Characters if width % 4 != 0 || height % 4 != 0 => Some(format!(
+                "Character sheets are split into 4×4 frames, but {width}×{height} isn't divisible by 4"
+            )),
+            Self::Tilesets if width != 256 || height % 32 != 0 => Some(format!(
+                "Tilesets must be 256 pixels wide and a multiple of 32 pixels tall, not {width}×{height}"
+            )),
+            // Animated autotiles have their frames side by side
+            Self::Autotiles if width == 0 || width % 96 != 0 || height != 128 => Some(format!(
+                "Autotiles must be 96×128, or 128 tall and a multiple of 96 wide if animated, not {width}×{height}"
+            )),
+            Self::Icons if width != 24 || height != 24 => {
+                Some(format!("Icons must be 24×24, not {width}×{height}"))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Describes why `name` can't be used as the name of an imported image, if it can't. Images are
+/// always saved directly in their kind's folder, so the name can't contain a path.
+pub fn name_problem(name: &str) -> Option<String> {
+    if name.contains(['/', '\\']) || name.contains("..") {
+        Some(format!("\"{name}\" can't contain slashes or \"..\""))
+    } else {
+        None
+    }
+}
+
+/// Makes every pixel of the colour `key` fully transparent, for images that use a solid
+/// background colour (usually magenta) instead of an alpha channel.
+pub fn color_key_to_alpha(image: &mut image::RgbaImage, key: [u8; 3]) {
+    for pixel in image.pixels_mut() {
+        if pixel.0[..3] == key {
+            pixel.0 = [0; 4];
+        }
+    }
+}
+
+/// Decodes an image in any format `image` supports, including paletted ones, as RGBA.
+pub fn decode(bytes: &[u8]) -> anyhow::Result<image::RgbaImage> {
+    Ok(image::load_from_memory(bytes)
+        .context("while decoding the image")?
+        .to_rgba8())
+}
+
+/// The file an image named `name` would replace, if there is one.
+pub fn existing(
+    filesystem: &luminol_filesystem::project::FileSystem,
+    kind: Kind,
+    name: &str,
+) -> luminol_filesystem::Result<Option<(Utf8PathBuf, Source)>> {
+    // The path cache ignores extensions, so look up the name with one, or a name like `Hero.v2`
+    // would be taken for `Hero` with the extension `v2`
+    let Some(path) = filesystem.desensitize(kind.directory().join(format!("{name}.png")))? else {
+        return Ok(None);
+    };
+    Ok(filesystem.source_of(&path)?.map(|source| (path, source)))
+}
+
+/// Saves `image` as a PNG named `name` in the folder for `kind`, and returns its path.
+///
+/// The image is always written to the project folder, so it overrides RTP graphics with the same
+/// name. A project file with the same name but another extension is removed, since only one of
+/// them would be loaded.
+pub fn import(
+    filesystem: &luminol_filesystem::project::FileSystem,
+    image: &image::RgbaImage,
+    kind: Kind,
+    name: &str,
+) -> anyhow::Result<Utf8PathBuf> {
+    let host = filesystem
+        .host()
+        .ok_or(luminol_filesystem::Error::NotLoaded)?;
+
+    if let Some(problem) = name_problem(name) {
+        anyhow::bail!(problem);
+    }
+
+    let mut changes = Vec::new();
+    let directory = match filesystem.desensitize(kind.directory())? {
+        Some(directory) => directory,
+        None => {
+            // Caching the outermost new directory also caches everything created inside it
+            let created = match filesystem.desensitize("Graphics")? {
+                Some(_) => kind.directory(),
+                None => Utf8PathBuf::from("Graphics"),
+            };
+            changes.push(Change::Created(created));
+            kind.directory()
+        }
+    };
+    let path = directory.join(format!("{name}.png"));
+
+    if let Some((existing, Source::Project)) = existing(filesystem, kind, name)? {
+        if existing != path {
+            host.remove_file(&existing)
+                .with_context(|| format!("while removing {existing}"))?;
+            changes.push(Change::Removed(existing));
+        }
+    }
+    changes.push(Change::Created(path.clone()));
+
+    let mut bytes = Vec::new();
+    image.write_to(
+        &mut std::io::Cursor::new(&mut bytes),
+        image::ImageOutputFormat::Png,
+    )?;
+    host.create_dir(&directory)?;
+    host.write(&path, bytes)
+        .with_context(|| format!("while saving {path}"))?;
+
+    // The image was written directly to the project folder, so it may be shadowed in the cache
+    filesystem.apply_changes(&changes)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Kind;
+
+    #[test]
+    fn size_problems() {
+        assert!(Kind::Characters.size_problem(128, 192).is_none());
+        assert!(Kind::Characters.size_problem(130, 192).is_some());
+        assert!(Kind::Tilesets.size_problem(256, 1024).is_none());
+        assert!(Kind::Tilesets.size_problem(256, 1000).is_some());
+        assert!(Kind::Autotiles.size_problem(96, 128).is_none());
+        assert!(Kind::Autotiles.size_problem(384, 128).is_none());
+        assert!(Kind::Autotiles.size_problem(32, 32).is_some());
+        assert!(Kind::Icons.size_problem(24, 24).is_none());
+        assert!(Kind::Icons.size_problem(32, 32).is_some());
+        assert!(Kind::Pictures.size_problem(123, 45).is_none());
+    }
+
+    #[test]
+    fn name_problems() {
+        assert!(super::name_problem("Hero").is_none());
+        assert!(super::name_problem("Hero.v2").is_none());
+        assert!(super::name_problem("../Hero").is_some());
+        assert!(super::name_problem("Sub/Hero").is_some());
+        assert!(super::name_problem("Sub\\Hero").is_some());
+        assert!(super::name_problem("..").is_some());
+    }
+
+    #[test]
+    fn dotted_names_keep_their_extension() {
+        use luminol_filesystem::FileSystem as _;
+
+        let root = camino::Utf8PathBuf::from_path_buf(std::env::temp_dir())
+            .unwrap()
+            .join(format!("luminol-image-import-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let host = luminol_filesystem::host::FileSystem::new(&root);
+        host.create_dir("Graphics/Characters").unwrap();
+        host.write("Graphics/Characters/Hero.png", b"").unwrap();
+
+        let mut list = luminol_filesystem::list::FileSystem::new();
+        list.push(host.clone());
+        let filesystem = luminol_filesystem::project::FileSystem::Loaded {
+            filesystem: luminol_filesystem::path_cache::FileSystem::new(list).unwrap(),
+            host_filesystem: host.clone(),
+            project_path: root.clone(),
+            sources: vec![luminol_filesystem::project::Source::Project],
+        };
+
+        assert!(super::existing(&filesystem, Kind::Characters, "hero")
+            .unwrap()
+            .is_some());
+        assert!(super::existing(&filesystem, Kind::Characters, "Hero.v2")
+            .unwrap()
+            .is_none());
+
+        let image = image::RgbaImage::new(4, 4);
+        let path = super::import(&filesystem, &image, Kind::Characters, "Hero.v2").unwrap();
+        assert_eq!(path, "Graphics/Characters/Hero.v2.png");
+        let exists = host.exists("Graphics/Characters/Hero.png").unwrap();
+
+        std::fs::remove_dir_all(&root).unwrap();
+        assert!(exists);
+    }
+
+    #[test]
+    fn color_key() {
+        let mut image = image::RgbaImage::from_fn(2, 1, |x, _| {
+            if x == 0 {
+                image::Rgba([255, 0, 255, 255])
+            } else {
+                image::Rgba([10, 20, 30, 255])
+            }
+        });
+        super::color_key_to_alpha(&mut image, [255, 0, 255]);
+        assert_eq!(image.get_pixel(0, 0).0, [0; 4]);
+        assert_eq!(image.get_pixel(1, 0).0, [10, 20, 30, 255]);
+    }
+}
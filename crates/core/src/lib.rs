@@ -34,7 +34,29 @@ pub mod modal;
 pub use modal::Modal;
 
 mod data_cache;
-pub use data_cache::Data;
+pub use data_cache::{map_id_from_filename, Data};
+
+pub mod backups;
+pub mod command_codes;
+pub mod command_text;
+pub mod diff;
+pub mod file_watcher;
+pub mod image_import;
+pub mod localization;
+pub mod portability;
+pub mod references;
+pub mod reindex;
+pub mod rtp_assets;
+pub mod text_search;
+pub mod validate;
+
+mod clipboard;
+pub use clipboard::{Clipboard, MapClipboard};
+
+pub mod history;
+pub use history::History;
+
+pub mod recovery;
 
 /// Toasts to be displayed for errors, information, etc.
 mod toasts;
@@ -68,6 +90,8 @@ pub struct UpdateState<'res> {
     pub global_config: &'res mut luminol_config::global::Config,
 
     pub toolbar: &'res mut ToolbarState,
+    pub clipboard: &'res mut Clipboard,
+    pub history: &'res mut History,
 
     pub modified: ModifiedState,
     pub project_manager: &'res mut ProjectManager,
@@ -125,6 +149,11 @@ pub enum Pencil {
     Circle,
     Rectangle,
     Fill,
+    Line,
+    Scatter,
+    #[strum(to_string = "Replace All")]
+    ReplaceAll,
+    Select,
 }
 
 impl<'res> UpdateState<'res> {
@@ -145,6 +174,8 @@ impl<'res> UpdateState<'res> {
             project_config: self.project_config,
             global_config: self.global_config,
             toolbar: self.toolbar,
+            clipboard: self.clipboard,
+            history: self.history,
             modified: self.modified.clone(),
             project_manager: self.project_manager,
         }
@@ -167,11 +198,27 @@ impl<'res> UpdateState<'res> {
             project_config: self.project_config,
             global_config: self.global_config,
             toolbar: self.toolbar,
+            clipboard: self.clipboard,
+            history: self.history,
             modified: self.modified.clone(),
             project_manager: self.project_manager,
         }
     }
 
+    /// Undoes the last edit, if it was made in the tab or window the user last clicked in.
+    pub fn undo(&mut self) {
+        if self.history.undo(self.data) {
+            self.modified.set(true);
+        }
+    }
+
+    /// Redoes the last undone edit, if it was made in the tab or window the user last clicked in.
+    pub fn redo(&mut self) {
+        if self.history.redo(self.data) {
+            self.modified.set(true);
+        }
+    }
+
     pub fn manage_projects(&mut self, show_modal: bool) {
         let mut should_close = false;
         let mut should_save = false;
@@ -231,6 +278,10 @@ impl<'res> UpdateState<'res> {
                 {
                     Ok(_) => {
                         self.modified.set(false);
+                        if let Err(e) = recovery::clear(self.filesystem) {
+                            self.toasts
+                                .warning(format!("Failed to clear autosaved data: {e:#}"));
+                        }
                         self.toasts.info("Saved project successfully!")
                     }
                     Err(e) => {
@@ -283,8 +334,9 @@ impl<'res> UpdateState<'res> {
                     ));
                     // FIXME we should probably load rtps from the RTP/<rtp> paths on non-wasm targets
                     #[cfg(not(target_arch = "wasm32"))]
-                    self.toasts
-                        .info(format!("You may want to set an RTP path for {missing_rtp}"));
+                    self.toasts.info(format!(
+                        "You may want to set an RTP path for {missing_rtp} in File > RTP Manager"
+                    ));
                     #[cfg(target_arch = "wasm32")]
                     self
                         .toasts
@@ -303,6 +355,16 @@ impl<'res> UpdateState<'res> {
                         "Successfully opened {:?}",
                         self.filesystem.project_path().expect("project not open")
                     ));
+
+                    match recovery::find(self.filesystem) {
+                        Ok(files) if !files.is_empty() => {
+                            self.project_manager.recovered_files = Some(files);
+                        }
+                        Ok(_) => {}
+                        Err(e) => self
+                            .toasts
+                            .warning(format!("Failed to check for autosaved data: {e:#}")),
+                    }
                 }
             }
             Some(Err(why)) => {
@@ -344,9 +406,19 @@ impl<'res> UpdateState<'res> {
         self.edit_windows.clean(|w| !w.requires_filesystem());
         self.edit_tabs.clean(|t| !t.requires_filesystem());
         self.audio.clear_sinks(); // audio loads files borrows from the filesystem. unloading while they are playing is a crash
+        if self.modified.get() && self.filesystem.project_loaded() {
+            // The user chose to discard their changes, so the autosave shouldn't be offered again
+            if let Err(e) = recovery::clear(self.filesystem) {
+                self.toasts
+                    .warning(format!("Failed to clear autosaved data: {e:#}"));
+            }
+        }
+        self.project_manager.recovered_files = None;
+        self.project_manager.changed_data_files.clear();
         self.filesystem.unload_project();
         *self.project_config = None;
         self.data.unload();
+        self.history.clear();
         self.modified.set(false);
     }
 }
@@ -0,0 +1,492 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use crate::references::Location;
+use crate::text_search::Field;
+
+/// A player-facing string in the project.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    /// Where the string comes from, such as `map/001/event/002/page/1/message/3/line/1`.
+    pub key: String,
+    pub text: String,
+}
+
+/// A translated string, read back from a translation file.
+#[derive(Debug, Clone)]
+pub struct Translation {
+    /// The text at the time it was extracted.
+    pub source: String,
+    pub translation: String,
+}
+
+/// The result of [`import`].
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    /// How many strings were replaced by their translation.
+    pub translated: usize,
+    /// How many strings in the file have no translation yet.
+    pub untranslated: usize,
+    /// Keys of strings that changed since they were extracted, and were left alone.
+    pub changed: Vec<String>,
+    /// Keys in the file that no longer exist in the project.
+    pub missing: Vec<String>,
+}
+
+/// The stable key of a piece of text, or None if it is not player-facing.
+pub fn key(location: Location, field: Field) -> Option<String> {
+    let location = match location {
+        Location::Map { map_id } => format!("map/{map_id:0>3}"),
+        Location::MapEvent {
+            map_id,
+            event_id,
+            page,
+        } => format!("map/{map_id:0>3}/event/{event_id:0>3}/page/{}", page + 1),
+        Location::CommonEvent { index } => format!("common_event/{:0>3}", index + 1),
+        Location::Troop { index, page } => {
+            format!("troop/{:0>3}/page/{}", index + 1, page + 1)
+        }
+        Location::Script { .. } => return None,
+        Location::Database { target, index } => {
+            let target = target.to_string().to_lowercase().replace(' ', "_");
+            format!("{target}/{:0>3}", index + 1)
+        }
+        Location::Element { id } => format!("element/{id:0>3}"),
+        Location::System => "system".to_string(),
+    };
+
+    let field = match field {
+        Field::Name => "name".to_string(),
+        Field::Description => "description".to_string(),
+        // Keyed by message instead of command line, so that adding commands elsewhere in the list
+        // does not invalidate existing translations
+        Field::Text {
+            message,
+            message_line,
+            ..
+        } => format!("message/{}/line/{}", message + 1, message_line + 1),
+        Field::Choice { choices, index, .. } => {
+            format!("choices/{}/choice/{}", choices + 1, index + 1)
+        }
+        Field::Script => return None,
+        Field::Word(word) => format!("words/{word}"),
+    };
+
+    Some(format!("{location}/{field}"))
+}
+
+/// Collect every non-empty player-facing string in the project.
+///
+/// Choices repeated in When branches are only collected once, from their Show Choices command.
+/// This loads every map in the project.
+pub fn extract(data: &crate::Data, filesystem: &impl luminol_filesystem::FileSystem) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    let mut seen = HashSet::new();
+    crate::text_search::visit_text(data, filesystem, |location, field, text| {
+        if let Some(key) =
+            key(location, field).filter(|key| !text.is_empty() && seen.insert(key.clone()))
+        {
+            entries.push(Entry {
+                key,
+                text: text.clone(),
+            });
+        }
+        false
+    });
+    entries
+}
+
+/// Replace every string in the project that has a translation.
+///
+/// Strings that no longer match the text they were extracted from are reported and left alone.
+/// This loads every map in the project.
+pub fn import(
+    data: &crate::Data,
+    filesystem: &impl luminol_filesystem::FileSystem,
+    translations: &HashMap<String, Translation>,
+) -> ImportReport {
+    let mut report = ImportReport::default();
+    let mut seen = HashSet::new();
+    crate::text_search::visit_text(data, filesystem, |location, field, text| {
+        let Some((key, translation)) =
+            key(location, field).and_then(|key| translations.get_key_value(&key))
+        else {
+            return false;
+        };
+        seen.insert(key.as_str());
+
+        if translation.source != *text {
+            report.changed.push(key.clone());
+            false
+        } else if translation.translation.is_empty() {
+            report.untranslated += 1;
+            false
+        } else {
+            *text = translation.translation.clone();
+            report.translated += 1;
+            true
+        }
+    });
+
+    report.missing = translations
+        .keys()
+        .filter(|key| !seen.contains(key.as_str()))
+        .cloned()
+        .collect();
+    report.missing.sort_unstable();
+
+    report
+}
+
+/// Format entries as a gettext PO template, using the keys as message contexts.
+pub fn to_po(entries: &[Entry]) -> String {
+    let mut po =
+        String::from("msgid \"\"\nmsgstr \"\"\n\"Content-Type: text/plain; charset=UTF-8\\n\"\n");
+    for entry in entries {
+        let _ = write!(
+            po,
+            "\n#: {}\nmsgctxt \"{}\"\nmsgid \"{}\"\nmsgstr \"\"\n",
+            entry.key,
+            escape_po(&entry.key),
+            escape_po(&entry.text)
+        );
+    }
+    po
+}
+
+/// Format entries as a CSV file with `Key`, `Source` and an empty `Translation` column.
+pub fn to_csv(entries: &[Entry]) -> String {
+    let mut csv = String::from("Key,Source,Translation\r\n");
+    for entry in entries {
+        let _ = write!(
+            csv,
+            "{},{},\r\n",
+            escape_csv(&entry.key),
+            escape_csv(&entry.text)
+        );
+    }
+    csv
+}
+
+/// Read a PO file written by [`to_po`] and then translated.
+pub fn parse_po(text: &str) -> anyhow::Result<HashMap<String, Translation>> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Keyword {
+        Context,
+        Id,
+        Str,
+    }
+
+    let mut translations = HashMap::new();
+    let mut context = String::new();
+    let mut id = String::new();
+    let mut string = String::new();
+    let mut current = None;
+
+    let mut finish = |context: &mut String, id: &mut String, string: &mut String| {
+        if !context.is_empty() {
+            translations.insert(
+                std::mem::take(context),
+                Translation {
+                    source: std::mem::take(id),
+                    translation: std::mem::take(string),
+                },
+            );
+        }
+        id.clear();
+        string.clear();
+    };
+
+    for (line_number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        let (keyword, rest) = if let Some(rest) = line.strip_prefix("msgctxt ") {
+            // A new context starts a new message
+            finish(&mut context, &mut id, &mut string);
+            (Some(Keyword::Context), rest)
+        } else if let Some(rest) = line.strip_prefix("msgid ") {
+            (Some(Keyword::Id), rest)
+        } else if let Some(rest) = line.strip_prefix("msgstr ") {
+            (Some(Keyword::Str), rest)
+        } else if line.starts_with('"') {
+            (None, line)
+        } else {
+            // Comments and blank lines
+            continue;
+        };
+
+        if keyword.is_some() {
+            current = keyword;
+        }
+        let value = unescape_po(rest)
+            .ok_or_else(|| anyhow::anyhow!("invalid string on line {}", line_number + 1))?;
+        match current {
+            Some(Keyword::Context) => context.push_str(&value),
+            Some(Keyword::Id) => id.push_str(&value),
+            Some(Keyword::Str) => string.push_str(&value),
+            None => anyhow::bail!("unexpected string on line {}", line_number + 1),
+        }
+    }
+    finish(&mut context, &mut id, &mut string);
+
+    Ok(translations)
+}
+
+/// Read a CSV file written by [`to_csv`] and then translated.
+pub fn parse_csv(text: &str) -> anyhow::Result<HashMap<String, Translation>> {
+    let mut rows = parse_csv_rows(text)?.into_iter();
+    if rows.next().is_none() {
+        anyhow::bail!("the file is empty");
+    }
+
+    rows.filter(|row| row.iter().any(|field| !field.is_empty()))
+        .enumerate()
+        .map(|(index, row)| match <[String; 3]>::try_from(row) {
+            Ok([key, source, translation]) => Ok((
+                key,
+                Translation {
+                    source,
+                    translation,
+                },
+            )),
+            Err(row) => Err(anyhow::anyhow!(
+                "row {} has {} columns instead of 3",
+                index + 2,
+                row.len()
+            )),
+        })
+        .collect()
+}
+
+fn escape_po(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Unescape a quoted PO string. Returns None if it is not a valid string.
+fn unescape_po(quoted: &str) -> Option<String> {
+    let inner = quoted.strip_prefix('"')?.strip_suffix('"')?;
+    let mut text = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'n' => text.push('\n'),
+            'r' => text.push('\r'),
+            't' => text.push('\t'),
+            c @ ('\\' | '"') => text.push(c),
+            _ => return None,
+        }
+    }
+    Some(text)
+}
+
+/// Quotes a CSV field if it contains anything that would otherwise end it, as in RFC 4180.
+pub(crate) fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn parse_csv_rows(text: &str) -> anyhow::Result<Vec<Vec<String>>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted => {
+                if chars.next_if_eq(&'"').is_some() {
+                    field.push('"');
+                } else {
+                    quoted = false;
+                }
+            }
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => row.push(std::mem::take(&mut field)),
+            '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => field.push(c),
+        }
+    }
+
+    if quoted {
+        anyhow::bail!("unterminated quoted field");
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries() -> Vec<Entry> {
+        [
+            ("map/001/name", "Town"),
+            ("common_event/001/message/1/line/1", "Hello, \"world\""),
+            ("common_event/001/message/1/line/2", "Tab\tand back\\slash"),
+            ("troop/001/page/1/choices/1/choice/1", "Yes,\r\nno"),
+        ]
+        .into_iter()
+        .map(|(key, text)| Entry {
+            key: key.to_string(),
+            text: text.to_string(),
+        })
+        .collect()
+    }
+
+    #[test]
+    fn keys_are_stable() {
+        let location = Location::MapEvent {
+            map_id: 1,
+            event_id: 2,
+            page: 0,
+        };
+        let field = Field::Text {
+            line: 7,
+            message: 2,
+            message_line: 0,
+        };
+        assert_eq!(
+            key(location, field).as_deref(),
+            Some("map/001/event/002/page/1/message/3/line/1")
+        );
+        assert_eq!(
+            key(Location::Element { id: 1 }, Field::Name).as_deref(),
+            Some("element/001/name")
+        );
+        assert_eq!(key(Location::Script { index: 0 }, Field::Script), None);
+    }
+
+    #[test]
+    fn extract_keys_messages_and_choices() {
+        let (filesystem, _, data) = crate::project_manager::test_project();
+
+        let command = |code, parameters| luminol_data::rpg::EventCommand {
+            code,
+            indent: 0,
+            parameters,
+            guid: 0,
+        };
+        data.common_events().data[0].list = vec![
+            command(101, vec!["Hello".into()]),
+            command(401, vec!["there".into()]),
+            command(
+                102,
+                vec![
+                    luminol_data::ParameterType::Array(vec!["Yes".into(), "No".into()]),
+                    luminol_data::ParameterType::Integer(2),
+                ],
+            ),
+            command(
+                402,
+                vec![luminol_data::ParameterType::Integer(0), "Yes".into()],
+            ),
+            command(101, vec!["Bye".into()]),
+        ];
+
+        let entries = extract(&data, &filesystem)
+            .into_iter()
+            .filter_map(|entry| {
+                let key = entry.key.strip_prefix("common_event/001/")?.to_string();
+                Some((key, entry.text))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            [
+                ("message/1/line/1".to_string(), "Hello".to_string()),
+                ("message/1/line/2".to_string(), "there".to_string()),
+                ("choices/1/choice/1".to_string(), "Yes".to_string()),
+                ("choices/1/choice/2".to_string(), "No".to_string()),
+                ("message/2/line/1".to_string(), "Bye".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn po_round_trip() {
+        let entries = entries();
+        let translations = parse_po(&to_po(&entries)).unwrap();
+        assert_eq!(translations.len(), entries.len());
+        for entry in &entries {
+            let translation = &translations[&entry.key];
+            assert_eq!(translation.source, entry.text);
+            assert!(translation.translation.is_empty());
+        }
+
+        let translated = parse_po(
+            "msgctxt \"map/001/name\"\nmsgid \"Town\"\nmsgstr \"\"\n\"Ville\\n\"\n\"du \\\"nord\\\"\"\n",
+        )
+        .unwrap();
+        assert_eq!(translated["map/001/name"].translation, "Ville\ndu \"nord\"");
+
+        assert!(parse_po("msgid \"bad \\q escape\"").is_err());
+    }
+
+    #[test]
+    fn csv_round_trip() {
+        let entries = entries();
+        let translations = parse_csv(&to_csv(&entries)).unwrap();
+        assert_eq!(translations.len(), entries.len());
+        for entry in &entries {
+            assert_eq!(translations[&entry.key].source, entry.text);
+        }
+
+        let translated =
+            parse_csv("Key,Source,Translation\nmap/001/name,Town,\"Ville, \"\"nord\"\"\"\n")
+                .unwrap();
+        assert_eq!(translated["map/001/name"].translation, "Ville, \"nord\"");
+
+        assert!(parse_csv("Key,Source,Translation\nmap/001/name,\"Town").is_err());
+        assert!(parse_csv("Key,Source,Translation\nmap/001/name,Town\n").is_err());
+    }
+}
@@ -0,0 +1,529 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+//! Finding asset paths that only work on filesystems that ignore case.
+//!
+//! RPG Maker looks assets up without caring about case or file extensions, and so does
+//! [`luminol_filesystem::path_cache`]. Players that run on Linux, like mkxp, look them up exactly, so
+//! a project that works in the editor can still be missing graphics and audio there.
+
+use std::collections::HashMap;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use luminol_data::rpg;
+
+use crate::command_codes::{
+    CHANGE_ACTOR_GRAPHIC, CHANGE_BATTLE_BGM, CHANGE_BATTLE_END_ME, CHANGE_MAP_SETTINGS,
+    CHANGE_WINDOWSKIN, EXECUTE_TRANSITION, MOVE_CHANGE_GRAPHIC, MOVE_PLAY_SE,
+    MOVE_ROUTE_CONTINUATION, PLAY_BGM, PLAY_BGS, PLAY_ME, PLAY_SE, SET_MOVE_ROUTE, SHOW_PICTURE,
+};
+use crate::references::{Location, Target};
+
+/// Files or directories whose paths only differ by case or file extension, like `Hero.png` and
+/// `hero.PNG`. Only one of them can be found when an asset is looked up by name.
+#[derive(Debug, Clone)]
+pub struct Collision {
+    pub paths: Vec<Utf8PathBuf>,
+}
+
+/// A reference to an asset that was only found by ignoring case.
+#[derive(Debug, Clone)]
+pub struct CaseMismatch {
+    pub location: Location,
+    /// What the asset is used for.
+    pub description: String,
+    /// The path the data refers to, without a file extension.
+    pub reference: Utf8PathBuf,
+    /// The file that was found for it.
+    pub on_disk: Utf8PathBuf,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    pub collisions: Vec<Collision>,
+    pub mismatches: Vec<CaseMismatch>,
+}
+
+impl Report {
+    pub fn is_empty(&self) -> bool {
+        self.collisions.is_empty() && self.mismatches.is_empty()
+    }
+}
+
+/// Check every file in the project, and every asset its data refers to.
+///
+/// Directories starting with a `.` (like `.luminol` and `.git`) are skipped.
+/// References to assets that don't exist at all are ignored.
+/// This loads every map in the project.
+pub fn check(
+    data: &crate::Data,
+    filesystem: &impl luminol_filesystem::FileSystem,
+) -> luminol_filesystem::Result<Report> {
+    let mut files = HashMap::new();
+    collect_paths(filesystem, Utf8Path::new(""), &mut files)?;
+
+    let mut mismatches = Vec::new();
+    visit_asset_references(data, filesystem, |location, description, reference| {
+        let Some(paths) = files.get(&lookup_key(&reference)) else {
+            return;
+        };
+        let matches = |path: &Utf8PathBuf| {
+            *path == reference || path.with_extension("").as_path() == reference
+        };
+        if !paths.iter().any(matches) {
+            mismatches.push(CaseMismatch {
+                location,
+                description: description.to_string(),
+                reference,
+                on_disk: paths[0].clone(),
+            });
+        }
+    });
+
+    let mut collisions: Vec<_> = files
+        .into_values()
+        .filter(|paths| paths.len() > 1)
+        .map(|mut paths| {
+            paths.sort_unstable();
+            Collision { paths }
+        })
+        .collect();
+    collisions.sort_unstable_by(|a, b| a.paths.cmp(&b.paths));
+
+    Ok(Report {
+        collisions,
+        mismatches,
+    })
+}
+
+/// The path an asset is looked up by when case and file extensions are ignored.
+fn lookup_key(path: &Utf8Path) -> Utf8PathBuf {
+    let mut key = luminol_filesystem::path_cache::to_lowercase(path);
+    key.set_extension("");
+    key
+}
+
+/// Adds every path inside the directory at `path` to `files`, grouped by [`lookup_key`].
+fn collect_paths(
+    filesystem: &impl luminol_filesystem::FileSystem,
+    path: &Utf8Path,
+    files: &mut HashMap<Utf8PathBuf, Vec<Utf8PathBuf>>,
+) -> luminol_filesystem::Result<()> {
+    for entry in filesystem.read_dir(path)? {
+        if entry
+            .path
+            .file_name()
+            .is_some_and(|name| name.starts_with('.'))
+        {
+            continue;
+        }
+        files
+            .entry(lookup_key(&entry.path))
+            .or_default()
+            .push(entry.path.clone());
+        if !entry.metadata.is_file {
+            collect_paths(filesystem, &entry.path, files)?;
+        }
+    }
+    Ok(())
+}
+
+/// Call `f` with every asset the project's data refers to. It's given where the reference is,
+/// what the asset is used for, and the asset's path without a file extension.
+///
+/// This loads every map in the project.
+pub fn visit_asset_references(
+    data: &crate::Data,
+    filesystem: &impl luminol_filesystem::FileSystem,
+    mut f: impl FnMut(Location, &str, Utf8PathBuf),
+) {
+    let mut asset = |location: Location, description: &str, dir: &str, name: Option<&str>| {
+        if let Some(name) = name.filter(|name| !name.is_empty()) {
+            f(location, description, Utf8Path::new(dir).join(name));
+        }
+    };
+
+    for (index, actor) in data.actors().data.iter().enumerate() {
+        let location = Location::Database {
+            target: Target::Actor,
+            index,
+        };
+        let character = path_str(&actor.character_name);
+        asset(location, "Character", "Graphics/Characters", character);
+        let battler = path_str(&actor.battler_name);
+        asset(location, "Battler", "Graphics/Battlers", battler);
+    }
+
+    for (index, skill) in data.skills().data.iter().enumerate() {
+        let location = Location::Database {
+            target: Target::Skill,
+            index,
+        };
+        asset(
+            location,
+            "Icon",
+            "Graphics/Icons",
+            path_str(&skill.icon_name),
+        );
+        asset(location, "Menu SE", "Audio/SE", audio_name(&skill.menu_se));
+    }
+
+    for (index, item) in data.items().data.iter().enumerate() {
+        let location = Location::Database {
+            target: Target::Item,
+            index,
+        };
+        asset(location, "Icon", "Graphics/Icons", Some(&item.icon_name));
+        asset(location, "Menu SE", "Audio/SE", audio_name(&item.menu_se));
+    }
+
+    for (index, weapon) in data.weapons().data.iter().enumerate() {
+        let location = Location::Database {
+            target: Target::Weapon,
+            index,
+        };
+        asset(
+            location,
+            "Icon",
+            "Graphics/Icons",
+            path_str(&weapon.icon_name),
+        );
+    }
+
+    for (index, armor) in data.armors().data.iter().enumerate() {
+        let location = Location::Database {
+            target: Target::Armor,
+            index,
+        };
+        asset(
+            location,
+            "Icon",
+            "Graphics/Icons",
+            path_str(&armor.icon_name),
+        );
+    }
+
+    for (index, enemy) in data.enemies().data.iter().enumerate() {
+        let location = Location::Database {
+            target: Target::Enemy,
+            index,
+        };
+        let battler = path_str(&enemy.battler_name);
+        asset(location, "Battler", "Graphics/Battlers", battler);
+    }
+
+    for (index, animation) in data.animations().data.iter().enumerate() {
+        let location = Location::Database {
+            target: Target::Animation,
+            index,
+        };
+        let graphic = path_str(&animation.animation_name);
+        asset(location, "Graphic", "Graphics/Animations", graphic);
+        for timing in animation.timings.iter() {
+            asset(location, "Timing SE", "Audio/SE", audio_name(&timing.se));
+        }
+    }
+
+    for (index, tileset) in data.tilesets().data.iter().enumerate() {
+        let location = Location::Database {
+            target: Target::Tileset,
+            index,
+        };
+        let graphic = path_str(&tileset.tileset_name);
+        asset(location, "Tileset graphic", "Graphics/Tilesets", graphic);
+        for autotile in tileset.autotile_names.iter() {
+            asset(location, "Autotile", "Graphics/Autotiles", Some(autotile));
+        }
+        let panorama = path_str(&tileset.panorama_name);
+        asset(location, "Panorama", "Graphics/Panoramas", panorama);
+        asset(
+            location,
+            "Fog",
+            "Graphics/Fogs",
+            path_str(&tileset.fog_name),
+        );
+        let battleback = path_str(&tileset.battleback_name);
+        asset(location, "Battleback", "Graphics/Battlebacks", battleback);
+    }
+
+    {
+        let system = data.system();
+        let location = Location::System;
+        let windowskin = path_str(&system.windowskin_name);
+        asset(location, "Windowskin", "Graphics/Windowskins", windowskin);
+        asset(
+            location,
+            "Title",
+            "Graphics/Titles",
+            path_str(&system.title_name),
+        );
+        let gameover = path_str(&system.gameover_name);
+        asset(location, "Gameover", "Graphics/Gameovers", gameover);
+        let transition = path_str(&system.battle_transition);
+        asset(
+            location,
+            "Battle transition",
+            "Graphics/Transitions",
+            transition,
+        );
+        let battleback = path_str(&system.battleback_name);
+        asset(location, "Battleback", "Graphics/Battlebacks", battleback);
+        asset(
+            location,
+            "Battler",
+            "Graphics/Battlers",
+            path_str(&system.battler_name),
+        );
+
+        for (description, dir, file) in [
+            ("Title BGM", "Audio/BGM", &system.title_bgm),
+            ("Battle BGM", "Audio/BGM", &system.battle_bgm),
+            ("Battle end ME", "Audio/ME", &system.battle_end_me),
+            ("Gameover ME", "Audio/ME", &system.gameover_me),
+            ("Cursor SE", "Audio/SE", &system.cursor_se),
+            ("Decision SE", "Audio/SE", &system.decision_se),
+            ("Cancel SE", "Audio/SE", &system.cancel_se),
+            ("Buzzer SE", "Audio/SE", &system.buzzer_se),
+            ("Equip SE", "Audio/SE", &system.equip_se),
+            ("Shop SE", "Audio/SE", &system.shop_se),
+            ("Save SE", "Audio/SE", &system.save_se),
+            ("Load SE", "Audio/SE", &system.load_se),
+            ("Battle start SE", "Audio/SE", &system.battle_start_se),
+            ("Escape SE", "Audio/SE", &system.escape_se),
+            ("Actor collapse SE", "Audio/SE", &system.actor_collapse_se),
+            ("Enemy collapse SE", "Audio/SE", &system.enemy_collapse_se),
+        ] {
+            asset(location, description, dir, audio_name(file));
+        }
+    }
+
+    let mut map_ids = data.map_infos().data.keys().copied().collect::<Vec<_>>();
+    map_ids.sort_unstable();
+    for map_id in map_ids {
+        // Missing or unreadable maps are reported by validation, not here
+        let Ok(map) = data.try_get_or_load_map(map_id, filesystem) else {
+            continue;
+        };
+
+        let location = Location::Map { map_id };
+        asset(location, "BGM", "Audio/BGM", audio_name(&map.bgm));
+        asset(location, "BGS", "Audio/BGS", audio_name(&map.bgs));
+
+        for (event_id, event) in map.events.iter() {
+            for (page, event_page) in event.pages.iter().enumerate() {
+                let location = Location::MapEvent {
+                    map_id,
+                    event_id,
+                    page,
+                };
+                let graphic = path_str(&event_page.graphic.character_name);
+                asset(location, "Graphic", "Graphics/Characters", graphic);
+                for move_command in event_page.move_route.list.iter() {
+                    visit_move_command(move_command, &mut |description, dir, name| {
+                        asset(location, description, dir, name)
+                    });
+                }
+                visit_commands(&event_page.list, location, &mut asset);
+            }
+        }
+    }
+
+    for (index, common_event) in data.common_events().data.iter().enumerate() {
+        visit_commands(
+            &common_event.list,
+            Location::CommonEvent { index },
+            &mut asset,
+        );
+    }
+
+    for (index, troop) in data.troops().data.iter().enumerate() {
+        for (page, troop_page) in troop.pages.iter().enumerate() {
+            visit_commands(
+                &troop_page.list,
+                Location::Troop { index, page },
+                &mut asset,
+            );
+        }
+    }
+}
+
+fn visit_commands(
+    list: &[rpg::EventCommand],
+    location: Location,
+    asset: &mut impl FnMut(Location, &str, &str, Option<&str>),
+) {
+    for (line, command) in list.iter().enumerate() {
+        let mut asset = |description: &str, dir: &str, name: Option<&str>| {
+            let description = format!("Line {}: {description}", line + 1);
+            asset(location, &description, dir, name);
+        };
+        let string = |index: usize| {
+            command
+                .parameters
+                .get(index)
+                .and_then(|p| p.as_string())
+                .map(String::as_str)
+        };
+        let audio = |index: usize| {
+            command
+                .parameters
+                .get(index)
+                .and_then(|p| p.as_audiofile())
+                .and_then(audio_name)
+        };
+
+        match command.code {
+            CHANGE_WINDOWSKIN => asset("Windowskin", "Graphics/Windowskins", string(0)),
+            CHANGE_BATTLE_BGM => asset("Battle BGM", "Audio/BGM", audio(0)),
+            CHANGE_BATTLE_END_ME => asset("Battle end ME", "Audio/ME", audio(0)),
+            CHANGE_MAP_SETTINGS => {
+                let kind = command.parameters.first().and_then(|p| p.as_integer());
+                let (description, dir) = match kind {
+                    Some(&0) => ("Panorama", "Graphics/Panoramas"),
+                    Some(&1) => ("Fog", "Graphics/Fogs"),
+                    Some(&2) => ("Battleback", "Graphics/Battlebacks"),
+                    _ => continue,
+                };
+                asset(description, dir, string(1));
+            }
+            EXECUTE_TRANSITION => asset("Transition", "Graphics/Transitions", string(0)),
+            SHOW_PICTURE => asset("Picture", "Graphics/Pictures", string(1)),
+            PLAY_BGM => asset("BGM", "Audio/BGM", audio(0)),
+            PLAY_BGS => asset("BGS", "Audio/BGS", audio(0)),
+            PLAY_ME => asset("ME", "Audio/ME", audio(0)),
+            PLAY_SE => asset("SE", "Audio/SE", audio(0)),
+            CHANGE_ACTOR_GRAPHIC => {
+                asset("Character", "Graphics/Characters", string(1));
+                asset("Battler", "Graphics/Battlers", string(3));
+            }
+            SET_MOVE_ROUTE => {
+                let route = command.parameters.get(1).and_then(|p| p.as_moveroute());
+                for move_command in route.into_iter().flat_map(|r| r.list.iter()) {
+                    visit_move_command(move_command, &mut asset);
+                }
+            }
+            MOVE_ROUTE_CONTINUATION => {
+                if let Some(move_command) =
+                    command.parameters.first().and_then(|p| p.as_movecommand())
+                {
+                    visit_move_command(move_command, &mut asset);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn visit_move_command(
+    command: &rpg::MoveCommand,
+    asset: &mut impl FnMut(&str, &str, Option<&str>),
+) {
+    let parameter = command.parameters.first();
+    match command.code {
+        MOVE_CHANGE_GRAPHIC => {
+            let name = parameter.and_then(|p| p.as_string()).map(String::as_str);
+            asset("Move route graphic", "Graphics/Characters", name);
+        }
+        MOVE_PLAY_SE => {
+            let name = parameter
+                .and_then(|p| p.as_audiofile())
+                .and_then(audio_name);
+            asset("Move route SE", "Audio/SE", name);
+        }
+        _ => {}
+    }
+}
+
+fn path_str(path: &luminol_data::Path) -> Option<&str> {
+    path.as_ref().map(|p| p.as_str())
+}
+
+fn audio_name(audio: &rpg::AudioFile) -> Option<&str> {
+    path_str(&audio.name)
+}
+
+#[cfg(test)]
+mod tests {
+    use luminol_filesystem::{FileSystem, OpenFlags};
+
+    #[test]
+    fn finds_collisions_and_mismatches() {
+        let (filesystem, _, data) = crate::project_manager::test_project();
+        data.actors().data[0].character_name = Some("hero".into());
+
+        filesystem.create_dir("Graphics/Characters").unwrap();
+        filesystem.create_dir("Audio/SE").unwrap();
+        for path in [
+            "Graphics/Characters/Hero.png",
+            "Audio/SE/Jump.ogg",
+            "Audio/SE/jump.MID",
+        ] {
+            filesystem
+                .open_file(path, OpenFlags::Create | OpenFlags::Write)
+                .unwrap();
+        }
+        let report = super::check(&data, &filesystem).unwrap();
+
+        assert_eq!(report.collisions.len(), 1);
+        assert_eq!(
+            report.collisions[0].paths,
+            ["Audio/SE/Jump.ogg", "Audio/SE/jump.MID"]
+        );
+        assert_eq!(report.mismatches.len(), 1);
+        assert_eq!(report.mismatches[0].reference, "Graphics/Characters/hero");
+        assert_eq!(report.mismatches[0].on_disk, "Graphics/Characters/Hero.png");
+
+        data.actors().data[0].character_name = Some("Hero".into());
+        let report = super::check(&data, &filesystem).unwrap();
+        assert!(report.mismatches.is_empty());
+    }
+
+    #[test]
+    fn visits_event_move_routes_and_skips_missing_maps() {
+        let (filesystem, _, data) = crate::project_manager::test_project();
+        // Map002.rxdata is never written
+        data.map_infos().data.insert(2, Default::default());
+
+        let mut event = luminol_data::rpg::Event::new(0, 0, 1);
+        event.pages[0].move_route.list = vec![luminol_data::rpg::MoveCommand {
+            code: crate::command_codes::MOVE_CHANGE_GRAPHIC,
+            parameters: vec!["Ghost".into()],
+            guid: 0,
+        }];
+        data.get_or_load_map(1, &filesystem).events.insert(1, event);
+
+        let mut references = Vec::new();
+        super::visit_asset_references(&data, &filesystem, |location, _, path| {
+            references.push((location, path));
+        });
+        assert!(references.contains(&(
+            crate::references::Location::MapEvent {
+                map_id: 1,
+                event_id: 1,
+                page: 0
+            },
+            "Graphics/Characters/Ghost".into()
+        )));
+    }
+}
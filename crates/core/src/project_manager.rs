@@ -29,6 +29,10 @@ pub struct ProjectManager {
     pub create_project_promise: Option<poll_promise::Promise<CreateProjectPromiseResult>>,
     pub load_filesystem_promise: Option<poll_promise::Promise<FileSystemPromiseResult>>,
     pub filesystem_open_result: Option<FileSystemOpenResult>,
+    /// Autosaved files found when the project was opened, waiting to be shown to the user.
+    pub recovered_files: Option<Vec<crate::recovery::RecoveredFile>>,
+    /// Data files that were changed outside of Luminol and haven't been reloaded or ignored yet.
+    pub changed_data_files: Vec<String>,
 }
 
 pub struct CreateProjectResult {
@@ -42,6 +46,29 @@ pub type CreateProjectPromiseResult = anyhow::Result<CreateProjectResult>;
 pub type FileSystemPromiseResult = luminol_filesystem::Result<luminol_filesystem::host::FileSystem>;
 pub type FileSystemOpenResult = luminol_filesystem::Result<luminol_filesystem::project::LoadResult>;
 
+/// Lays out a new project in the root of `filesystem` and writes its default data files.
+/// The filesystem doesn't have to be on disk, so a project can be previewed in a
+/// [`luminol_filesystem::memory::FileSystem`] before the user picks where it should go.
+pub fn create_project(
+    filesystem: &impl luminol_filesystem::FileSystem,
+    config: &luminol_config::project::Config,
+) -> anyhow::Result<crate::Data> {
+    use anyhow::Context;
+
+    filesystem.create_dir("Audio")?;
+    filesystem.create_dir("Data")?;
+    filesystem.create_dir("Graphics")?;
+
+    filesystem.create_file(format!("{}.lumproj", config.project.project_name))?;
+
+    let mut data_cache = crate::Data::from_defaults();
+    data_cache
+        .save(filesystem, config)
+        .context("while writing the default data files")?;
+
+    Ok(data_cache)
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 /// Spawns a future using `poll_promise::Promise::spawn_async` on native or
 /// `poll_promise::Promise::spawn_local` on web.
@@ -68,6 +95,8 @@ impl ProjectManager {
             create_project_promise: None,
             load_filesystem_promise: None,
             filesystem_open_result: None,
+            recovered_files: None,
+            changed_data_files: Vec::new(),
         }
     }
 
@@ -92,6 +121,11 @@ impl ProjectManager {
     /// Closes the application after asking the user to save unsaved changes.
     pub fn quit(&mut self) {
         self.run_custom(|update_state| {
+            // The user chose to discard their changes, so the autosave shouldn't be offered again
+            if update_state.modified.get() && update_state.filesystem.project_loaded() {
+                let _ = crate::recovery::clear(update_state.filesystem);
+            }
+
             // Disable the modified flag so `luminol_eframe::App::on_close_event` doesn't recurse
             update_state.modified.set(false);
 
@@ -146,3 +180,38 @@ impl ProjectManager {
         });
     }
 }
+
+/// Creates a new project named "Test" in memory, for tests.
+#[cfg(test)]
+pub(crate) fn test_project() -> (
+    luminol_filesystem::memory::FileSystem,
+    luminol_config::project::Config,
+    crate::Data,
+) {
+    let filesystem = luminol_filesystem::memory::FileSystem::new();
+    let config = luminol_config::project::Config::from_project(luminol_config::project::Project {
+        project_name: "Test".to_string(),
+        ..Default::default()
+    });
+    let data = create_project(&filesystem, &config).unwrap();
+    (filesystem, config, data)
+}
+
+#[cfg(test)]
+mod tests {
+    use luminol_filesystem::FileSystem;
+
+    #[test]
+    fn create_project_in_memory() {
+        let (filesystem, mut config, _) = super::test_project();
+        assert!(filesystem.exists("Test.lumproj").unwrap());
+        assert!(filesystem.exists("Graphics").unwrap());
+        assert!(filesystem.exists("Data/Map001.rxdata").unwrap());
+
+        let mut data = crate::Data::Unloaded;
+        data.load(&filesystem, &mut config).unwrap();
+        assert_eq!(data.actors().data.len(), 1);
+        assert_eq!(data.map_infos().data.len(), 1);
+        assert!(data.modified_files(&config).is_empty());
+    }
+}
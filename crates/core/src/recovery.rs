@@ -0,0 +1,148 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+//! Periodic autosaving of unsaved data into the project's recovery directory, and restoring it
+//! the next time the project is opened if Luminol didn't get the chance to save or discard it.
+
+use luminol_filesystem::FileSystem;
+
+/// Directory inside the project that autosaved data is written to.
+pub const RECOVERY_DIR: &str = ".luminol/recovery";
+
+/// How long to wait after the project was first modified before autosaving, in seconds.
+pub const AUTOSAVE_INTERVAL: f64 = 60.;
+
+/// A file in the recovery directory that differs from the one in the project's `Data` directory.
+#[derive(Debug, Clone)]
+pub struct RecoveredFile {
+    /// The name of the file, e.g. `Actors.rxdata` or `Map001.rxdata`.
+    pub filename: String,
+    /// Whether a file with this name exists in the `Data` directory at all.
+    pub on_disk: bool,
+}
+
+impl RecoveredFile {
+    pub fn recovery_path(&self) -> camino::Utf8PathBuf {
+        camino::Utf8Path::new(RECOVERY_DIR).join(&self.filename)
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct Autosave {
+    last_autosave: Option<f64>,
+}
+
+impl Autosave {
+    /// Autosaves the project if it has had unsaved changes for longer than [`AUTOSAVE_INTERVAL`].
+    pub fn update(&mut self, update_state: &mut crate::UpdateState<'_>) {
+        let Some(config) = update_state.project_config.as_ref() else {
+            self.last_autosave = None;
+            return;
+        };
+        if !update_state.modified.get() {
+            self.last_autosave = None;
+            return;
+        }
+
+        let now = update_state.ctx.input(|i| i.time);
+        let elapsed = now - *self.last_autosave.get_or_insert(now);
+        if elapsed < AUTOSAVE_INTERVAL {
+            update_state
+                .ctx
+                .request_repaint_after(std::time::Duration::from_secs_f64(
+                    AUTOSAVE_INTERVAL - elapsed,
+                ));
+            return;
+        }
+        self.last_autosave = Some(now);
+
+        if let Err(e) = autosave(update_state.data, update_state.filesystem, config) {
+            update_state
+                .toasts
+                .error(format!("Error autosaving the project: {e:#}"));
+        }
+    }
+}
+
+/// Writes all modified data into [`RECOVERY_DIR`] without marking it as saved.
+pub fn autosave(
+    data: &crate::Data,
+    filesystem: &impl FileSystem,
+    config: &luminol_config::project::Config,
+) -> anyhow::Result<Vec<String>> {
+    filesystem.create_dir(RECOVERY_DIR)?;
+    data.write_modified(filesystem, camino::Utf8Path::new(RECOVERY_DIR), config)
+}
+
+/// Lists the files in [`RECOVERY_DIR`] whose contents differ from the project's `Data` directory.
+pub fn find(filesystem: &impl FileSystem) -> anyhow::Result<Vec<RecoveredFile>> {
+    if !filesystem.exists(RECOVERY_DIR)? {
+        return Ok(vec![]);
+    }
+
+    let mut files = Vec::new();
+    for entry in filesystem.read_dir(RECOVERY_DIR)? {
+        if !entry.metadata().is_file {
+            continue;
+        }
+        let Some(filename) = entry.path().file_name() else {
+            continue;
+        };
+
+        let data_path = camino::Utf8Path::new("Data").join(filename);
+        let on_disk = filesystem.exists(&data_path)?;
+        if on_disk && filesystem.read(&data_path)? == filesystem.read(entry.path())? {
+            continue;
+        }
+
+        files.push(RecoveredFile {
+            filename: filename.to_string(),
+            on_disk,
+        });
+    }
+    files.sort_unstable_by(|a, b| a.filename.cmp(&b.filename));
+
+    Ok(files)
+}
+
+/// Loads the given recovered files into the data cache, marking them as modified.
+pub fn restore(
+    data: &crate::Data,
+    filesystem: &impl FileSystem,
+    config: &luminol_config::project::Config,
+    files: &[RecoveredFile],
+) -> anyhow::Result<()> {
+    for file in files {
+        data.restore(filesystem, &file.recovery_path(), &file.filename, config)?;
+    }
+    Ok(())
+}
+
+/// Removes the recovery directory, if it exists.
+pub fn clear(filesystem: &impl FileSystem) -> anyhow::Result<()> {
+    if filesystem.exists(RECOVERY_DIR)? {
+        filesystem.remove_dir(RECOVERY_DIR)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,777 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+use luminol_data::commands::ParameterKind;
+use luminol_data::rpg;
+
+use crate::command_codes::{
+    MOVE_ROUTE_CONTINUATION, MOVE_SWITCH_OFF, MOVE_SWITCH_ON, SET_MOVE_ROUTE,
+};
+
+/// A kind of id that can be searched for with [`find_references`].
+#[derive(
+    Default,
+    strum::EnumIter,
+    strum::Display,
+    PartialEq,
+    Eq,
+    Hash,
+    Clone,
+    Copy,
+    Debug
+)]
+pub enum Target {
+    #[default]
+    Switch,
+    Variable,
+    #[strum(to_string = "Common Event")]
+    CommonEvent,
+    Actor,
+    Class,
+    Skill,
+    Item,
+    Weapon,
+    Armor,
+    Enemy,
+    Troop,
+    State,
+    Animation,
+    Tileset,
+}
+
+impl Target {
+    /// The name of the entry with this (1-based) id, if it exists.
+    pub fn entry_name(self, data: &crate::Data, id: usize) -> Option<String> {
+        let index = id.checked_sub(1)?;
+
+        macro_rules! name {
+            ($getter:ident) => {
+                data.$getter().data.get(index).map(|e| e.name.clone())
+            };
+        }
+
+        match self {
+            Self::Switch => data.system().switches.get(index).cloned(),
+            Self::Variable => data.system().variables.get(index).cloned(),
+            Self::CommonEvent => name!(common_events),
+            Self::Actor => name!(actors),
+            Self::Class => name!(classes),
+            Self::Skill => name!(skills),
+            Self::Item => name!(items),
+            Self::Weapon => name!(weapons),
+            Self::Armor => name!(armors),
+            Self::Enemy => name!(enemies),
+            Self::Troop => name!(troops),
+            Self::State => name!(states),
+            Self::Animation => name!(animations),
+            Self::Tileset => name!(tilesets),
+        }
+    }
+
+    /// Does a command parameter of this kind hold an id of this target?
+    pub fn matches_kind(self, kind: &ParameterKind) -> bool {
+        matches!(
+            (self, kind),
+            (Self::Switch, ParameterKind::Switch)
+                | (Self::Variable, ParameterKind::Variable)
+                | (Self::CommonEvent, ParameterKind::CommonEvent)
+                | (Self::Actor, ParameterKind::Actor)
+                | (Self::Class, ParameterKind::Class)
+                | (Self::Skill, ParameterKind::Skill)
+                | (Self::Item, ParameterKind::Item)
+                | (Self::Weapon, ParameterKind::Weapon)
+                | (Self::Armor, ParameterKind::Armor)
+                | (Self::Enemy, ParameterKind::Enemy)
+                | (Self::Troop, ParameterKind::Troop)
+                | (Self::State, ParameterKind::State)
+                | (Self::Animation, ParameterKind::Animation)
+        )
+    }
+}
+
+/// Where a reference or a piece of text was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Location {
+    /// A page of an event on a map.
+    MapEvent {
+        map_id: usize,
+        event_id: usize,
+        page: usize,
+    },
+    /// The properties of a map, such as its name or encounter list.
+    Map { map_id: usize },
+    /// A common event, by its index in the common event list.
+    CommonEvent { index: usize },
+    /// A page of a troop, by the troop's index in the troop list.
+    Troop { index: usize, page: usize },
+    /// An entry in a database, by its index in that database.
+    Database { target: Target, index: usize },
+    /// A script, by its index in the script list.
+    Script { index: usize },
+    /// An element, by its id (which is also its index in the element list.)
+    Element { id: usize },
+    /// The system settings.
+    System,
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::MapEvent {
+                map_id,
+                event_id,
+                page,
+            } => write!(
+                f,
+                "Map {map_id:0>3}, event {event_id:0>3}, page {}",
+                page + 1
+            ),
+            Self::Map { map_id } => write!(f, "Map {map_id:0>3}"),
+            Self::CommonEvent { index } => write!(f, "Common event {:0>3}", index + 1),
+            Self::Troop { index, page } => {
+                write!(f, "Troop {:0>3}, page {}", index + 1, page + 1)
+            }
+            Self::Database { target, index } => write!(f, "{target} {:0>3}", index + 1),
+            Self::Script { index } => write!(f, "Script {:0>3}", index + 1),
+            Self::Element { id } => write!(f, "Element {id:0>3}"),
+            Self::System => write!(f, "System"),
+        }
+    }
+}
+
+/// A single place where an id is used.
+#[derive(Debug, Clone)]
+pub struct Reference {
+    pub location: Location,
+    /// A short human readable description of what refers to the id.
+    pub description: String,
+}
+
+/// Called for every reference found by [`visit_references`].
+///
+/// It is given where the reference is, what refers to the id, and the id itself (starting at 1).
+/// It returns what the id should be changed to, or `None` if the entry it referred to no longer exists.
+pub type Visitor<'a> = dyn FnMut(Location, &str, usize) -> Option<usize> + 'a;
+
+/// Find every place in the project that refers to `id` of `target`.
+///
+/// `id` is the id as shown in the editor (so the first switch is 1.)
+/// Event commands are only searched if the command database has a description for them.
+pub fn find_references(
+    data: &crate::Data,
+    filesystem: &impl luminol_filesystem::FileSystem,
+    command_db: &luminol_config::command_db::CommandDB,
+    target: Target,
+    id: usize,
+) -> Vec<Reference> {
+    let mut references = Vec::new();
+    visit_references(
+        data,
+        filesystem,
+        command_db,
+        target,
+        |location, description, found| {
+            if found == id {
+                references.push(Reference {
+                    location,
+                    description: description.to_string(),
+                });
+            }
+            Some(found)
+        },
+    );
+    references
+}
+
+/// Call `f` with every reference to any id of `target` in the project, and store the ids it returns.
+///
+/// Ids are only counted as references while they are in use, so a page condition switch is ignored while that condition is disabled.
+/// When `f` says an entry no longer exists, the reference is removed if it is part of a list, disabled if it is a condition,
+/// cleared if it is optional, and otherwise reset to the first entry.
+/// Event command parameters are left unchanged, since there's no valid id to give them.
+///
+/// This loads every map in the project, skipping the ones that fail to load.
+pub fn visit_references(
+    data: &crate::Data,
+    filesystem: &impl luminol_filesystem::FileSystem,
+    command_db: &luminol_config::command_db::CommandDB,
+    target: Target,
+    mut f: impl FnMut(Location, &str, usize) -> Option<usize>,
+) {
+    let mut walker = Walker {
+        target,
+        command_db,
+        f: &mut f,
+    };
+
+    let mut map_ids = data.map_infos().data.keys().copied().collect::<Vec<_>>();
+    map_ids.sort_unstable();
+    for map_id in map_ids {
+        // Maps that fail to load are reported by validation, there's nothing to visit in them
+        let Ok(mut map) = data.try_get_or_load_map(map_id, filesystem) else {
+            continue;
+        };
+        let mut changed = false;
+        let location = Location::Map { map_id };
+
+        if let Some(new) = walker.visit(Target::Tileset, location, "Tileset", map.tileset_id + 1) {
+            // Maps always have a tileset, so fall back to the first one
+            let new = new.map_or(0, |new| new - 1);
+            changed |= new != map.tileset_id;
+            map.tileset_id = new;
+        }
+
+        if target == Target::Troop {
+            map.encounter_list.retain_mut(|troop_id| {
+                match walker.visit(Target::Troop, location, "Encounter", *troop_id as usize) {
+                    Some(Some(new)) => {
+                        changed |= new as i32 != *troop_id;
+                        *troop_id = new as i32;
+                        true
+                    }
+                    Some(None) => {
+                        changed = true;
+                        false
+                    }
+                    None => true,
+                }
+            });
+        }
+
+        for (event_id, event) in map.events.iter_mut() {
+            for (page, event_page) in event.pages.iter_mut().enumerate() {
+                let location = Location::MapEvent {
+                    map_id,
+                    event_id,
+                    page,
+                };
+                let condition = &mut event_page.condition;
+                changed |= walker.condition(
+                    Target::Switch,
+                    location,
+                    &mut condition.switch1_valid,
+                    &mut condition.switch1_id,
+                    true,
+                );
+                changed |= walker.condition(
+                    Target::Switch,
+                    location,
+                    &mut condition.switch2_valid,
+                    &mut condition.switch2_id,
+                    true,
+                );
+                changed |= walker.condition(
+                    Target::Variable,
+                    location,
+                    &mut condition.variable_valid,
+                    &mut condition.variable_id,
+                    false,
+                );
+                changed |= walker.move_commands(
+                    location,
+                    "Move route",
+                    event_page.move_route.list.iter_mut(),
+                );
+                changed |= walker.commands(location, &mut event_page.list);
+            }
+        }
+
+        map.modified |= changed;
+    }
+
+    {
+        let mut common_events = data.common_events();
+        let mut changed = false;
+        for (index, common_event) in common_events.data.iter_mut().enumerate() {
+            let location = Location::CommonEvent { index };
+            if common_event.trigger != 0 {
+                match walker.visit(
+                    Target::Switch,
+                    location,
+                    "Trigger switch",
+                    common_event.switch_id,
+                ) {
+                    Some(Some(new)) => {
+                        changed |= new != common_event.switch_id;
+                        common_event.switch_id = new;
+                    }
+                    Some(None) => {
+                        common_event.trigger = 0;
+                        changed = true;
+                    }
+                    None => {}
+                }
+            }
+            changed |= walker.commands(location, &mut common_event.list);
+        }
+        common_events.modified |= changed;
+    }
+
+    {
+        let mut troops = data.troops();
+        let mut changed = false;
+        for (index, troop) in troops.data.iter_mut().enumerate() {
+            let location = Location::Database {
+                target: Target::Troop,
+                index,
+            };
+            troop.members.retain_mut(|member| {
+                let mut enemy_id = Some(member.enemy_id);
+                changed |= walker.optional(Target::Enemy, location, "Troop member", &mut enemy_id);
+                enemy_id.map(|id| member.enemy_id = id).is_some()
+            });
+
+            for (page, troop_page) in troop.pages.iter_mut().enumerate() {
+                let location = Location::Troop { index, page };
+                let condition = &mut troop_page.condition;
+                changed |= walker.optional_condition(
+                    Target::Switch,
+                    location,
+                    &mut condition.switch_valid,
+                    &mut condition.switch_id,
+                );
+                changed |= walker.optional_condition(
+                    Target::Actor,
+                    location,
+                    &mut condition.actor_valid,
+                    &mut condition.actor_id,
+                );
+                changed |= walker.commands(location, &mut troop_page.list);
+            }
+        }
+        troops.modified |= changed;
+    }
+
+    {
+        let mut actors = data.actors();
+        let mut changed = false;
+        for (index, actor) in actors.data.iter_mut().enumerate() {
+            let location = Location::Database {
+                target: Target::Actor,
+                index,
+            };
+            let mut class_id = Some(actor.class_id);
+            changed |= walker.optional(Target::Class, location, "Class", &mut class_id);
+            actor.class_id = class_id.unwrap_or(0);
+            changed |= walker.optional(Target::Weapon, location, "Weapon", &mut actor.weapon_id);
+            for (description, armor_id) in [
+                ("Shield", &mut actor.armor1_id),
+                ("Helmet", &mut actor.armor2_id),
+                ("Body armor", &mut actor.armor3_id),
+                ("Accessory", &mut actor.armor4_id),
+            ] {
+                changed |= walker.optional(Target::Armor, location, description, armor_id);
+            }
+        }
+        actors.modified |= changed;
+    }
+
+    {
+        let mut classes = data.classes();
+        let mut changed = false;
+        for (index, class) in classes.data.iter_mut().enumerate() {
+            let location = Location::Database {
+                target: Target::Class,
+                index,
+            };
+            changed |= walker.id_vec(
+                Target::Weapon,
+                location,
+                "Equippable weapons",
+                &mut class.weapon_set,
+            );
+            changed |= walker.id_vec(
+                Target::Armor,
+                location,
+                "Equippable armor",
+                &mut class.armor_set,
+            );
+            class.learnings.retain_mut(|learning| {
+                let mut skill_id = Some(learning.skill_id);
+                changed |= walker.optional(Target::Skill, location, "Learned skill", &mut skill_id);
+                skill_id.map(|id| learning.skill_id = id).is_some()
+            });
+        }
+        classes.modified |= changed;
+    }
+
+    macro_rules! visit_usable {
+        ($($getter:ident => $target:ident),* $(,)?) => {
+            $({
+                let mut container = data.$getter();
+                let mut changed = false;
+                for (index, entry) in container.data.iter_mut().enumerate() {
+                    let location = Location::Database {
+                        target: Target::$target,
+                        index,
+                    };
+                    changed |= walker.optional(Target::Animation, location, "User animation", &mut entry.animation1_id);
+                    changed |= walker.optional(Target::Animation, location, "Target animation", &mut entry.animation2_id);
+                    changed |= walker.id_vec(Target::State, location, "Adds state", &mut entry.plus_state_set);
+                    changed |= walker.id_vec(Target::State, location, "Removes state", &mut entry.minus_state_set);
+                }
+                container.modified |= changed;
+            })*
+        };
+    }
+
+    visit_usable! {
+        skills => Skill,
+        items => Item,
+        weapons => Weapon,
+    }
+
+    {
+        let mut skills = data.skills();
+        let mut changed = false;
+        for (index, skill) in skills.data.iter_mut().enumerate() {
+            let location = Location::Database {
+                target: Target::Skill,
+                index,
+            };
+            changed |= walker.optional(
+                Target::CommonEvent,
+                location,
+                "Common event",
+                &mut skill.common_event_id,
+            );
+        }
+        skills.modified |= changed;
+    }
+
+    {
+        let mut items = data.items();
+        let mut changed = false;
+        for (index, item) in items.data.iter_mut().enumerate() {
+            let location = Location::Database {
+                target: Target::Item,
+                index,
+            };
+            changed |= walker.optional(
+                Target::CommonEvent,
+                location,
+                "Common event",
+                &mut item.common_event_id,
+            );
+        }
+        items.modified |= changed;
+    }
+
+    {
+        let mut armors = data.armors();
+        let mut changed = false;
+        for (index, armor) in armors.data.iter_mut().enumerate() {
+            let location = Location::Database {
+                target: Target::Armor,
+                index,
+            };
+            changed |= walker.optional(
+                Target::State,
+                location,
+                "Auto state",
+                &mut armor.auto_state_id,
+            );
+            changed |= walker.id_vec(
+                Target::State,
+                location,
+                "Guards against state",
+                &mut armor.guard_state_set,
+            );
+        }
+        armors.modified |= changed;
+    }
+
+    {
+        let mut enemies = data.enemies();
+        let mut changed = false;
+        for (index, enemy) in enemies.data.iter_mut().enumerate() {
+            let location = Location::Database {
+                target: Target::Enemy,
+                index,
+            };
+            changed |= walker.optional(
+                Target::Animation,
+                location,
+                "Attacker animation",
+                &mut enemy.animation1_id,
+            );
+            changed |= walker.optional(
+                Target::Animation,
+                location,
+                "Target animation",
+                &mut enemy.animation2_id,
+            );
+            changed |= walker.optional(Target::Item, location, "Treasure", &mut enemy.item_id);
+            changed |= walker.optional(Target::Weapon, location, "Treasure", &mut enemy.weapon_id);
+            changed |= walker.optional(Target::Armor, location, "Treasure", &mut enemy.armor_id);
+            for (action_index, action) in enemy.actions.iter_mut().enumerate() {
+                let description = format!("Action {}", action_index + 1);
+                changed |=
+                    walker.optional(Target::Skill, location, &description, &mut action.skill_id);
+                changed |= walker.optional(
+                    Target::Switch,
+                    location,
+                    &description,
+                    &mut action.condition_switch_id,
+                );
+            }
+        }
+        enemies.modified |= changed;
+    }
+
+    {
+        let mut states = data.states();
+        let mut changed = false;
+        for (index, state) in states.data.iter_mut().enumerate() {
+            let location = Location::Database {
+                target: Target::State,
+                index,
+            };
+            changed |= walker.optional(
+                Target::Animation,
+                location,
+                "Animation",
+                &mut state.animation_id,
+            );
+            changed |= walker.id_vec(
+                Target::State,
+                location,
+                "Adds state",
+                &mut state.plus_state_set,
+            );
+            changed |= walker.id_vec(
+                Target::State,
+                location,
+                "Removes state",
+                &mut state.minus_state_set,
+            );
+        }
+        states.modified |= changed;
+    }
+
+    {
+        let mut system = data.system();
+        let mut changed = false;
+        changed |= walker.id_vec(
+            Target::Actor,
+            Location::System,
+            "Starting party",
+            &mut system.party_members,
+        );
+        changed |= walker.optional(
+            Target::Troop,
+            Location::System,
+            "Battle test troop",
+            &mut system.test_troop_id,
+        );
+        system.modified |= changed;
+    }
+}
+
+/// Applies a [`Visitor`] to the different ways ids are stored in the project.
+struct Walker<'a, 'b> {
+    target: Target,
+    command_db: &'a luminol_config::command_db::CommandDB,
+    f: &'a mut Visitor<'b>,
+}
+
+impl Walker<'_, '_> {
+    /// Visit a 1-based `id` of `kind`.
+    ///
+    /// Returns None if `kind` is not being searched for, otherwise what the id should become.
+    fn visit(
+        &mut self,
+        kind: Target,
+        location: Location,
+        description: &str,
+        id: usize,
+    ) -> Option<Option<usize>> {
+        (kind == self.target).then(|| (self.f)(location, description, id))
+    }
+
+    /// Visit an optional 0-based id. Returns true if it was changed.
+    fn optional(
+        &mut self,
+        kind: Target,
+        location: Location,
+        description: &str,
+        value: &mut Option<usize>,
+    ) -> bool {
+        let Some(id) = *value else {
+            return false;
+        };
+        let Some(new) = self.visit(kind, location, description, id + 1) else {
+            return false;
+        };
+        let new = new.map(|new| new - 1);
+        let changed = new != *value;
+        *value = new;
+        changed
+    }
+
+    /// Visit a list of 0-based ids. Returns true if it was changed.
+    fn id_vec(
+        &mut self,
+        kind: Target,
+        location: Location,
+        description: &str,
+        values: &mut Vec<usize>,
+    ) -> bool {
+        let mut changed = false;
+        values.retain_mut(|value| {
+            let mut id = Some(*value);
+            changed |= self.optional(kind, location, description, &mut id);
+            id.map(|id| *value = id).is_some()
+        });
+        changed
+    }
+
+    /// Visit an id used by a condition, which is disabled if the entry no longer exists.
+    fn condition(
+        &mut self,
+        kind: Target,
+        location: Location,
+        valid: &mut bool,
+        value: &mut usize,
+        zero_based: bool,
+    ) -> bool {
+        if !*valid {
+            return false;
+        }
+        let offset = zero_based as usize;
+        match self.visit(kind, location, "Page condition", *value + offset) {
+            Some(Some(new)) => {
+                let changed = new - offset != *value;
+                *value = new - offset;
+                changed
+            }
+            Some(None) => {
+                *valid = false;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Visit an optional 0-based id used by a condition.
+    fn optional_condition(
+        &mut self,
+        kind: Target,
+        location: Location,
+        valid: &mut bool,
+        value: &mut Option<usize>,
+    ) -> bool {
+        if !*valid {
+            return false;
+        }
+        let changed = self.optional(kind, location, "Page condition", value);
+        if value.is_none() {
+            *valid = false;
+        }
+        changed
+    }
+
+    /// Visit every command parameter the command database says holds an id.
+    fn commands(&mut self, location: Location, list: &mut [rpg::EventCommand]) -> bool {
+        let mut changed = false;
+        for (line, command) in list.iter_mut().enumerate() {
+            match command.code {
+                SET_MOVE_ROUTE => {
+                    if let Some(route) = command
+                        .parameters
+                        .get_mut(1)
+                        .and_then(|p| p.as_moveroute_mut())
+                    {
+                        let text = format!("Line {}: Set Move Route", line + 1);
+                        changed |= self.move_commands(location, &text, route.list.iter_mut());
+                    }
+                }
+                MOVE_ROUTE_CONTINUATION => {
+                    let move_command = command
+                        .parameters
+                        .first_mut()
+                        .and_then(|p| p.as_movecommand_mut());
+                    let text = format!("Line {}: Set Move Route", line + 1);
+                    changed |= self.move_commands(location, &text, move_command);
+                }
+                _ => {}
+            }
+
+            let Some(description) = self.command_db.get(command.code) else {
+                continue;
+            };
+
+            let mut indices = Vec::new();
+            description.walk_parameters(command, |index, kind, value| {
+                if self.target.matches_kind(kind) && value.as_integer().is_some_and(|&id| id > 0) {
+                    indices.push(index);
+                }
+            });
+
+            let text = format!("Line {}: {}", line + 1, description.name);
+            for index in indices {
+                let value = command.parameters[index].into_integer();
+                if let Some(new) = (self.f)(location, &text, *value as usize) {
+                    changed |= new as i32 != *value;
+                    *value = new as i32;
+                }
+            }
+        }
+        changed
+    }
+
+    /// Visit the switches turned on or off by move commands. Returns true if any were changed.
+    ///
+    /// Like command parameters, these are left unchanged if the switch no longer exists.
+    fn move_commands<'c>(
+        &mut self,
+        location: Location,
+        description: &str,
+        commands: impl IntoIterator<Item = &'c mut rpg::MoveCommand>,
+    ) -> bool {
+        if self.target != Target::Switch {
+            return false;
+        }
+
+        let mut changed = false;
+        for command in commands {
+            if !matches!(command.code, MOVE_SWITCH_ON | MOVE_SWITCH_OFF) {
+                continue;
+            }
+            let Some(value) = command
+                .parameters
+                .first_mut()
+                .and_then(|p| p.as_integer_mut())
+                .filter(|value| **value > 0)
+            else {
+                continue;
+            };
+            if let Some(new) = (self.f)(location, description, *value as usize) {
+                changed |= new as i32 != *value;
+                *value = new as i32;
+            }
+        }
+        changed
+    }
+}
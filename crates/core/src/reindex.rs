@@ -0,0 +1,429 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+use crate::references::{Location, Target};
+
+/// A change to the order of the entries of a database.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+    /// Insert a new entry so that it gets this id, shifting the following entries up.
+    Insert(usize),
+    /// Delete the entry with this id, shifting the following entries down.
+    Delete(usize),
+}
+
+impl Operation {
+    /// What a reference to `id` should become after this operation.
+    ///
+    /// Returns None if `id` is the entry being deleted.
+    pub fn remap(self, id: usize) -> Option<usize> {
+        match self {
+            Self::Insert(at) if id >= at => Some(id + 1),
+            Self::Delete(at) if id == at => None,
+            Self::Delete(at) if id > at => Some(id - 1),
+            _ => Some(id),
+        }
+    }
+}
+
+/// A reference that would be rewritten by an [`Operation`].
+#[derive(Debug, Clone)]
+pub struct Change {
+    pub location: Location,
+    /// A short human readable description of what refers to the id.
+    pub description: String,
+    pub old: usize,
+    /// The new id, or None if the referenced entry is being deleted.
+    pub new: Option<usize>,
+}
+
+/// How many entries the database of `target` has.
+pub fn entry_count(data: &crate::Data, target: Target) -> usize {
+    match target {
+        Target::Switch => data.system().switches.len(),
+        Target::Variable => data.system().variables.len(),
+        Target::CommonEvent => data.common_events().data.len(),
+        Target::Actor => data.actors().data.len(),
+        Target::Class => data.classes().data.len(),
+        Target::Skill => data.skills().data.len(),
+        Target::Item => data.items().data.len(),
+        Target::Weapon => data.weapons().data.len(),
+        Target::Armor => data.armors().data.len(),
+        Target::Enemy => data.enemies().data.len(),
+        Target::Troop => data.troops().data.len(),
+        Target::State => data.states().data.len(),
+        Target::Animation => data.animations().data.len(),
+        Target::Tileset => data.tilesets().data.len(),
+    }
+}
+
+/// List every reference that `operation` would rewrite, without changing anything.
+pub fn preview(
+    data: &crate::Data,
+    filesystem: &impl luminol_filesystem::FileSystem,
+    command_db: &luminol_config::command_db::CommandDB,
+    target: Target,
+    operation: Operation,
+) -> Vec<Change> {
+    let mut changes = Vec::new();
+    crate::references::visit_references(
+        data,
+        filesystem,
+        command_db,
+        target,
+        |location, description, id| {
+            let new = operation.remap(id);
+            if new != Some(id) {
+                changes.push(Change {
+                    location,
+                    description: description.to_string(),
+                    old: id,
+                    new,
+                });
+            }
+            Some(id)
+        },
+    );
+    changes
+}
+
+/// Insert or delete an entry of `target`, and rewrite every reference in the project to match.
+///
+/// References to a deleted entry are handled as described in [`crate::references::visit_references`].
+/// Returns how many references were rewritten.
+pub fn apply(
+    data: &crate::Data,
+    filesystem: &impl luminol_filesystem::FileSystem,
+    command_db: &luminol_config::command_db::CommandDB,
+    target: Target,
+    operation: Operation,
+) -> anyhow::Result<usize> {
+    let count = entry_count(data, target);
+    match operation {
+        Operation::Insert(at) if !(1..=count + 1).contains(&at) => {
+            anyhow::bail!("Cannot insert {target} {at:0>3}, there are only {count} entries")
+        }
+        Operation::Delete(at) if !(1..=count).contains(&at) => {
+            anyhow::bail!("{target} {at:0>3} does not exist")
+        }
+        _ => {}
+    }
+
+    let mut rewritten = 0;
+    crate::references::visit_references(data, filesystem, command_db, target, |_, _, id| {
+        let new = operation.remap(id);
+        if new != Some(id) {
+            rewritten += 1;
+        }
+        new
+    });
+
+    macro_rules! reindex {
+        ($getter:ident) => {{
+            let mut container = data.$getter();
+            match operation {
+                Operation::Insert(at) => container.data.insert(at - 1, Default::default()),
+                Operation::Delete(at) => {
+                    container.data.remove(at - 1);
+                }
+            }
+            for (index, entry) in container.data.iter_mut().enumerate() {
+                entry.id = index;
+            }
+            container.modified = true;
+        }};
+    }
+
+    match target {
+        Target::Switch | Target::Variable => {
+            let mut system = data.system();
+            let names = if target == Target::Switch {
+                &mut system.switches
+            } else {
+                &mut system.variables
+            };
+            match operation {
+                Operation::Insert(at) => names.insert(at - 1, String::new()),
+                Operation::Delete(at) => {
+                    names.remove(at - 1);
+                }
+            }
+            system.modified = true;
+        }
+        Target::CommonEvent => reindex!(common_events),
+        Target::Actor => reindex!(actors),
+        Target::Class => reindex!(classes),
+        Target::Skill => reindex!(skills),
+        Target::Item => reindex!(items),
+        Target::Weapon => reindex!(weapons),
+        Target::Armor => reindex!(armors),
+        Target::Enemy => reindex!(enemies),
+        Target::Troop => reindex!(troops),
+        Target::State => {
+            reindex!(states);
+
+            // State ranks are indexed by state id
+            let mut classes = data.classes();
+            for class in classes.data.iter_mut() {
+                shift_table(&mut class.state_ranks, operation);
+            }
+            classes.modified = true;
+            drop(classes);
+
+            let mut enemies = data.enemies();
+            for enemy in enemies.data.iter_mut() {
+                shift_table(&mut enemy.state_ranks, operation);
+            }
+            enemies.modified = true;
+        }
+        Target::Animation => reindex!(animations),
+        Target::Tileset => reindex!(tilesets),
+    }
+
+    Ok(rewritten)
+}
+
+/// Shift the entries of a table indexed by id to match `operation`.
+fn shift_table(table: &mut luminol_data::Table1, operation: Operation) {
+    // The default rank, C
+    const DEFAULT_RANK: i16 = 3;
+
+    let len = table.xsize();
+    match operation {
+        Operation::Insert(at) if at <= len => {
+            table.resize(len + 1);
+            let slice = table.as_mut_slice();
+            slice[at..].rotate_right(1);
+            slice[at] = DEFAULT_RANK;
+        }
+        Operation::Delete(at) if at < len => {
+            table.as_mut_slice()[at..].rotate_left(1);
+            table.resize(len - 1);
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use luminol_data::{rpg, ParameterType};
+
+    #[test]
+    fn remap() {
+        assert_eq!(Operation::Insert(2).remap(1), Some(1));
+        assert_eq!(Operation::Insert(2).remap(2), Some(3));
+        assert_eq!(Operation::Delete(2).remap(1), Some(1));
+        assert_eq!(Operation::Delete(2).remap(2), None);
+        assert_eq!(Operation::Delete(2).remap(3), Some(2));
+    }
+
+    #[test]
+    fn delete_rewrites_references() {
+        let (filesystem, _, data) = crate::project_manager::test_project();
+        let command_db = luminol_config::command_db::CommandDB::new(luminol_config::RMVer::XP);
+
+        data.items().data = ["A", "B", "C"]
+            .into_iter()
+            .map(|name| rpg::Item {
+                name: name.to_string(),
+                ..Default::default()
+            })
+            .collect();
+        // Change Items: item 3, increase by a constant 1
+        data.common_events().data[0].list = vec![rpg::EventCommand {
+            code: 126,
+            indent: 0,
+            parameters: vec![
+                ParameterType::Integer(3),
+                ParameterType::Integer(0),
+                ParameterType::Integer(0),
+                ParameterType::Integer(1),
+            ],
+            guid: 0,
+        }];
+        data.enemies().data[0].item_id = Some(1);
+
+        let changes = preview(
+            &data,
+            &filesystem,
+            &command_db,
+            Target::Item,
+            Operation::Delete(2),
+        );
+        assert_eq!(changes.len(), 2);
+        assert!(data.items().data.len() == 3);
+
+        let rewritten = apply(
+            &data,
+            &filesystem,
+            &command_db,
+            Target::Item,
+            Operation::Delete(2),
+        )
+        .unwrap();
+        assert_eq!(rewritten, 2);
+
+        let items = data.items();
+        assert_eq!(
+            items
+                .data
+                .iter()
+                .map(|i| i.name.as_str())
+                .collect::<Vec<_>>(),
+            ["A", "C"]
+        );
+        assert_eq!(items.data[1].id, 1);
+        assert_eq!(
+            data.common_events().data[0].list[0].parameters[0],
+            ParameterType::Integer(2)
+        );
+        assert_eq!(data.enemies().data[0].item_id, None);
+
+        assert!(apply(
+            &data,
+            &filesystem,
+            &command_db,
+            Target::Item,
+            Operation::Delete(3)
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn delete_leaves_commands_for_the_deleted_entry() {
+        let (filesystem, _, data) = crate::project_manager::test_project();
+        let command_db = luminol_config::command_db::CommandDB::new(luminol_config::RMVer::XP);
+
+        data.items().data = vec![rpg::Item::default(); 3];
+        // Change Items: item 2, increase by a constant 1
+        data.common_events().data[0].list = vec![rpg::EventCommand {
+            code: 126,
+            indent: 0,
+            parameters: vec![
+                ParameterType::Integer(2),
+                ParameterType::Integer(0),
+                ParameterType::Integer(0),
+                ParameterType::Integer(1),
+            ],
+            guid: 0,
+        }];
+
+        apply(
+            &data,
+            &filesystem,
+            &command_db,
+            Target::Item,
+            Operation::Delete(2),
+        )
+        .unwrap();
+
+        assert_eq!(
+            data.common_events().data[0].list[0].parameters[0],
+            ParameterType::Integer(2)
+        );
+    }
+
+    #[test]
+    fn insert_remaps_move_route_switches() {
+        use crate::command_codes::{MOVE_SWITCH_ON, SET_MOVE_ROUTE};
+
+        let (filesystem, _, data) = crate::project_manager::test_project();
+        let command_db = luminol_config::command_db::CommandDB::new(luminol_config::RMVer::XP);
+        let switch_on = |id| rpg::MoveCommand {
+            code: MOVE_SWITCH_ON,
+            parameters: vec![ParameterType::Integer(id)],
+            guid: 0,
+        };
+
+        let mut event = rpg::Event::new(0, 0, 1);
+        event.pages[0].move_route.list = vec![switch_on(1)];
+        event.pages[0].list = vec![rpg::EventCommand {
+            code: SET_MOVE_ROUTE,
+            indent: 0,
+            parameters: vec![
+                ParameterType::Integer(-1),
+                ParameterType::MoveRoute(rpg::MoveRoute {
+                    list: vec![switch_on(2)],
+                    ..Default::default()
+                }),
+            ],
+            guid: 0,
+        }];
+        data.get_or_load_map(1, &filesystem).events.insert(1, event);
+
+        apply(
+            &data,
+            &filesystem,
+            &command_db,
+            Target::Switch,
+            Operation::Insert(1),
+        )
+        .unwrap();
+
+        let map = data.get_or_load_map(1, &filesystem);
+        let page = &map.events[1].pages[0];
+        assert_eq!(
+            page.move_route.list[0].parameters[0],
+            ParameterType::Integer(2)
+        );
+        let route = page.list[0].parameters[1].as_moveroute().unwrap();
+        assert_eq!(route.list[0].parameters[0], ParameterType::Integer(3));
+    }
+
+    #[test]
+    fn insert_remaps_map_tilesets() {
+        let (filesystem, _, data) = crate::project_manager::test_project();
+        let command_db = luminol_config::command_db::CommandDB::new(luminol_config::RMVer::XP);
+        data.get_or_load_map(1, &filesystem).tileset_id = 0;
+
+        apply(
+            &data,
+            &filesystem,
+            &command_db,
+            Target::Tileset,
+            Operation::Insert(1),
+        )
+        .unwrap();
+
+        assert_eq!(data.tilesets().data.len(), 2);
+        assert_eq!(data.get_or_load_map(1, &filesystem).tileset_id, 1);
+    }
+
+    #[test]
+    fn shift_table() {
+        let mut table = luminol_data::Table1::new(4);
+        table.as_mut_slice().copy_from_slice(&[0, 1, 2, 3]);
+
+        super::shift_table(&mut table, Operation::Insert(2));
+        assert_eq!(table.as_slice(), [0, 1, 3, 2, 3]);
+
+        super::shift_table(&mut table, Operation::Delete(1));
+        assert_eq!(table.as_slice(), [0, 3, 2, 3]);
+
+        // Ids past the end of the table are left alone
+        super::shift_table(&mut table, Operation::Delete(9));
+        assert_eq!(table.len(), 4);
+    }
+}
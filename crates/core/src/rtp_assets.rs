@@ -0,0 +1,131 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+//! Copying the RTP assets a project uses into it, so it doesn't need the RTP anymore.
+
+use std::collections::BTreeSet;
+
+use anyhow::Context;
+use camino::Utf8PathBuf;
+use luminol_filesystem::project::Source;
+use luminol_filesystem::FileSystem as _;
+
+/// An asset the project's data refers to that is read from an RTP.
+#[derive(Debug, Clone)]
+pub struct Asset {
+    /// The path of the file in the RTP.
+    pub path: Utf8PathBuf,
+    /// The name of the RTP it's read from.
+    pub rtp: String,
+}
+
+/// Finds every asset the project's data refers to that isn't in the project itself, but in one of
+/// its RTPs. Each file is only listed once, sorted by path.
+///
+/// This loads every map in the project.
+pub fn find(
+    data: &crate::Data,
+    filesystem: &luminol_filesystem::project::FileSystem,
+) -> luminol_filesystem::Result<Vec<Asset>> {
+    let mut references = BTreeSet::new();
+    crate::portability::visit_asset_references(data, filesystem, |_, _, reference| {
+        references.insert(reference);
+    });
+
+    let mut assets = BTreeSet::new();
+    for reference in references {
+        let Some(path) = filesystem.desensitize(&reference)? else {
+            continue;
+        };
+        if let Some(Source::Rtp { name, .. }) = filesystem.source_of(&path)? {
+            assets.insert((path, name));
+        }
+    }
+
+    Ok(assets
+        .into_iter()
+        .map(|(path, rtp)| Asset { path, rtp })
+        .collect())
+}
+
+/// Copies `assets` from their RTPs into the project folder, at the same paths.
+/// Directories that already exist in the project are reused, even if they're named with a
+/// different case.
+pub fn copy(
+    filesystem: &luminol_filesystem::project::FileSystem,
+    assets: &[Asset],
+) -> anyhow::Result<()> {
+    let host = filesystem
+        .host()
+        .ok_or(luminol_filesystem::Error::NotLoaded)?;
+
+    let mut changes = Vec::with_capacity(assets.len());
+    for asset in assets {
+        let bytes = filesystem
+            .read(&asset.path)
+            .with_context(|| format!("while reading {} from the RTP {}", asset.path, asset.rtp))?;
+
+        let mut path = asset.path.clone();
+        if let (Some(parent), Some(file_name)) = (asset.path.parent(), asset.path.file_name()) {
+            // The project's directories come before the RTPs, so this is the project's casing if
+            // it already has the directory
+            let parent = filesystem
+                .desensitize(parent)?
+                .unwrap_or_else(|| parent.to_path_buf());
+            host.create_dir(&parent)?;
+            path = parent.join(file_name);
+        }
+        host.write(&path, bytes)
+            .with_context(|| format!("while copying {path} into the project"))?;
+        changes.push(luminol_filesystem::watch::Change::Created(path));
+    }
+
+    // The copies were written directly to the project folder, so the cache may still point at
+    // the RTPs' versions
+    filesystem.apply_changes(&changes)?;
+    Ok(())
+}
+
+/// Removes `RTP1` to `RTP3` from the project's `Game.ini` and saves it.
+/// The RTPs stay loaded until the project is reopened.
+pub fn clear_rtps(
+    filesystem: &impl luminol_filesystem::FileSystem,
+    config: &mut luminol_config::project::Config,
+) -> anyhow::Result<()> {
+    config
+        .game_ini
+        .with_section(Some("Game"))
+        .set("RTP1", "")
+        .set("RTP2", "")
+        .set("RTP3", "");
+
+    let mut game_ini = Vec::new();
+    config
+        .game_ini
+        .write_to_policy(&mut game_ini, ini::EscapePolicy::Nothing)?;
+    filesystem
+        .write("Game.ini", game_ini)
+        .context("while saving Game.ini")?;
+    Ok(())
+}
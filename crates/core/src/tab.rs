@@ -71,6 +71,10 @@ impl Tabs {
     }
 
     pub fn process_edit_tabs(&mut self, mut edit_tabs: EditTabs) {
+        if !edit_tabs.removed.is_empty() {
+            let removed = std::mem::take(&mut edit_tabs.removed);
+            self.clean_tabs(|t| !removed.contains(&t.id()));
+        }
         for tab in edit_tabs.added.drain(..) {
             self.add_boxed_tab(tab)
         }
@@ -247,7 +251,8 @@ impl<'a, 'res> egui_dock::TabViewer for TabViewer<'a, 'res> {
                 ui,
                 self.update_state,
                 self.focused_id.is_some_and(|focused_id| focused_id == id),
-            )
+            );
+            self.update_state.history.focus_on_click(ui, id);
         });
     }
 
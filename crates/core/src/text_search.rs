@@ -0,0 +1,403 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+use luminol_data::rpg;
+
+use crate::command_codes::{SHOW_CHOICES, SHOW_TEXT, SHOW_TEXT_CONTINUED, WHEN_CHOICE};
+use crate::references::{Location, Target};
+
+/// Which piece of text at a [`Location`] was found.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Field {
+    Name,
+    Description,
+    /// A line of a message shown with Show Text.
+    ///
+    /// `line` is where the command is in the command list. Messages are counted from the start of
+    /// the list instead, so `message` and `message_line` only change when messages are added or removed.
+    Text {
+        line: usize,
+        message: usize,
+        message_line: usize,
+    },
+    /// One of the choices of a Show Choices command, or the same choice in one of its When branches.
+    ///
+    /// `line` is where the command is in the command list, `choices` counts Show Choices commands
+    /// from the start of the list.
+    Choice {
+        line: usize,
+        choices: usize,
+        index: usize,
+    },
+    /// The body of a script.
+    Script,
+    /// One of the terms used in menus and battle, such as "gold".
+    Word(&'static str),
+}
+
+/// What to search for, and what to replace it with.
+pub struct Query {
+    regex: regex::Regex,
+    replacement: String,
+    literal: bool,
+}
+
+impl Query {
+    /// Build a query.
+    ///
+    /// If `use_regex` is false, both `pattern` and `replacement` are taken literally.
+    /// Otherwise `replacement` may refer to capture groups with `$1`, `$name`, etc.
+    pub fn new(
+        pattern: &str,
+        replacement: &str,
+        use_regex: bool,
+        case_sensitive: bool,
+    ) -> Result<Self, regex::Error> {
+        let pattern = if use_regex {
+            Cow::Borrowed(pattern)
+        } else {
+            Cow::Owned(regex::escape(pattern))
+        };
+        let regex = regex::RegexBuilder::new(&pattern)
+            .case_insensitive(!case_sensitive)
+            .build()?;
+
+        Ok(Self {
+            regex,
+            replacement: replacement.to_string(),
+            literal: !use_regex,
+        })
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.regex.is_match(text)
+    }
+
+    /// Apply the replacement to every match in `text`.
+    pub fn replace<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if self.literal {
+            self.regex
+                .replace_all(text, regex::NoExpand(&self.replacement))
+        } else {
+            self.regex.replace_all(text, self.replacement.as_str())
+        }
+    }
+}
+
+/// A piece of text that matched a [`Query`].
+#[derive(Debug, Clone)]
+pub struct TextMatch {
+    pub location: Location,
+    pub field: Field,
+    /// The text as it currently is.
+    pub text: String,
+    /// What the text would become if the replacement was applied.
+    pub replaced: String,
+}
+
+/// Find every piece of text in the project that matches `query`.
+///
+/// This loads every map in the project.
+pub fn search(
+    data: &crate::Data,
+    filesystem: &impl luminol_filesystem::FileSystem,
+    query: &Query,
+) -> Vec<TextMatch> {
+    let mut matches = Vec::new();
+    visit_text(data, filesystem, |location, field, text| {
+        if query.is_match(text) {
+            matches.push(TextMatch {
+                location,
+                field,
+                text: text.clone(),
+                replaced: query.replace(text).into_owned(),
+            });
+        }
+        false
+    });
+    matches
+}
+
+/// Apply the replacement of `query` to the text at every location in `selected`.
+///
+/// Returns how many pieces of text were changed.
+pub fn replace(
+    data: &crate::Data,
+    filesystem: &impl luminol_filesystem::FileSystem,
+    query: &Query,
+    selected: &HashSet<(Location, Field)>,
+) -> usize {
+    let mut count = 0;
+    visit_text(data, filesystem, |location, field, text| {
+        if !selected.contains(&(location, field)) {
+            return false;
+        }
+        match query.replace(text) {
+            Cow::Owned(replaced) if replaced != *text => {
+                *text = replaced;
+                count += 1;
+                true
+            }
+            _ => false,
+        }
+    });
+    count
+}
+
+/// Call `f` with every searchable piece of text in the project.
+///
+/// `f` returns true if it changed the text, which marks whatever holds the text as modified.
+/// Maps that fail to load are skipped.
+pub(crate) fn visit_text(
+    data: &crate::Data,
+    filesystem: &impl luminol_filesystem::FileSystem,
+    mut f: impl FnMut(Location, Field, &mut String) -> bool,
+) {
+    let mut map_ids = data.map_infos().data.keys().copied().collect::<Vec<_>>();
+    map_ids.sort_unstable();
+
+    {
+        let mut map_infos = data.map_infos();
+        let mut changed = false;
+        for &map_id in &map_ids {
+            if let Some(info) = map_infos.data.get_mut(&map_id) {
+                changed |= f(Location::Map { map_id }, Field::Name, &mut info.name);
+            }
+        }
+        map_infos.modified |= changed;
+    }
+
+    for map_id in map_ids {
+        let Ok(mut map) = data.try_get_or_load_map(map_id, filesystem) else {
+            continue;
+        };
+        let mut changed = false;
+        for (event_id, event) in map.events.iter_mut() {
+            for (page, event_page) in event.pages.iter_mut().enumerate() {
+                let location = Location::MapEvent {
+                    map_id,
+                    event_id,
+                    page,
+                };
+                changed |=
+                    visit_commands(&mut event_page.list, |field, text| f(location, field, text));
+            }
+        }
+        map.modified |= changed;
+    }
+
+    {
+        let mut common_events = data.common_events();
+        let mut changed = false;
+        for (index, common_event) in common_events.data.iter_mut().enumerate() {
+            changed |= visit_commands(&mut common_event.list, |field, text| {
+                f(Location::CommonEvent { index }, field, text)
+            });
+        }
+        common_events.modified |= changed;
+    }
+
+    {
+        let mut troops = data.troops();
+        let mut changed = false;
+        for (index, troop) in troops.data.iter_mut().enumerate() {
+            for (page, troop_page) in troop.pages.iter_mut().enumerate() {
+                changed |= visit_commands(&mut troop_page.list, |field, text| {
+                    f(Location::Troop { index, page }, field, text)
+                });
+            }
+        }
+        troops.modified |= changed;
+    }
+
+    {
+        let mut scripts = data.scripts();
+        let mut changed = false;
+        for (index, script) in scripts.data.iter_mut().enumerate() {
+            let location = Location::Script { index };
+            changed |= f(location, Field::Name, &mut script.name);
+            changed |= f(location, Field::Script, &mut script.script_text);
+        }
+        scripts.modified |= changed;
+    }
+
+    {
+        let mut system = data.system();
+        let system = &mut *system;
+        let mut changed = false;
+        // The first element is an unused placeholder, so the others' indices are their ids
+        for (id, element) in system.elements.iter_mut().enumerate().skip(1) {
+            changed |= f(Location::Element { id }, Field::Name, element);
+        }
+
+        let words = &mut system.words;
+        for (name, word) in [
+            ("gold", &mut words.gold),
+            ("hp", &mut words.hp),
+            ("sp", &mut words.sp),
+            ("str", &mut words.str),
+            ("dex", &mut words.dex),
+            ("agi", &mut words.agi),
+            ("int", &mut words.int),
+            ("atk", &mut words.atk),
+            ("pdef", &mut words.pdef),
+            ("mdef", &mut words.mdef),
+            ("weapon", &mut words.weapon),
+            ("armor1", &mut words.armor1),
+            ("armor2", &mut words.armor2),
+            ("armor3", &mut words.armor3),
+            ("armor4", &mut words.armor4),
+            ("attack", &mut words.attack),
+            ("skill", &mut words.skill),
+            ("guard", &mut words.guard),
+            ("item", &mut words.item),
+            ("equip", &mut words.equip),
+        ] {
+            changed |= f(Location::System, Field::Word(name), word);
+        }
+        system.modified |= changed;
+    }
+
+    macro_rules! visit_database {
+        ($($getter:ident => $target:ident $(, $description:ident)?;)*) => {
+            $({
+                let mut container = data.$getter();
+                let mut changed = false;
+                for (index, entry) in container.data.iter_mut().enumerate() {
+                    let location = Location::Database {
+                        target: Target::$target,
+                        index,
+                    };
+                    changed |= f(location, Field::Name, &mut entry.name);
+                    $(changed |= f(location, Field::Description, &mut entry.$description);)?
+                }
+                container.modified |= changed;
+            })*
+        };
+    }
+
+    visit_database! {
+        actors => Actor;
+        classes => Class;
+        skills => Skill, description;
+        items => Item, description;
+        weapons => Weapon, description;
+        armors => Armor, description;
+        enemies => Enemy;
+        troops => Troop;
+        states => State;
+        animations => Animation;
+        tilesets => Tileset;
+        common_events => CommonEvent;
+    }
+}
+
+/// Call `f` with the text of every Show Text and Show Choices command in `list`.
+fn visit_commands(
+    list: &mut [rpg::EventCommand],
+    mut f: impl FnMut(Field, &mut String) -> bool,
+) -> bool {
+    let mut changed = false;
+    let mut messages = 0;
+    let mut message_line = 0;
+    let mut choice_lists = 0;
+    // The Show Choices each indent level's When branches belong to
+    let mut open_choices = HashMap::new();
+    for (line, command) in list.iter_mut().enumerate() {
+        match command.code {
+            SHOW_TEXT | SHOW_TEXT_CONTINUED => {
+                // Continuation lines without a Show Text before them are counted as their own message
+                if command.code == SHOW_TEXT || messages == 0 {
+                    messages += 1;
+                    message_line = 0;
+                } else {
+                    message_line += 1;
+                }
+                let message = messages - 1;
+                if let Some(text) = command
+                    .parameters
+                    .get_mut(0)
+                    .and_then(|p| p.as_string_mut())
+                {
+                    changed |= f(
+                        Field::Text {
+                            line,
+                            message,
+                            message_line,
+                        },
+                        text,
+                    );
+                }
+            }
+            SHOW_CHOICES => {
+                let choices = choice_lists;
+                choice_lists += 1;
+                open_choices.insert(command.indent, choices);
+                if let Some(list) = command.parameters.get_mut(0).and_then(|p| p.as_array_mut()) {
+                    for (index, choice) in list.iter_mut().enumerate() {
+                        if let Some(text) = choice.as_string_mut() {
+                            changed |= f(
+                                Field::Choice {
+                                    line,
+                                    choices,
+                                    index,
+                                },
+                                text,
+                            );
+                        }
+                    }
+                }
+            }
+            WHEN_CHOICE => {
+                let Some(&choices) = open_choices.get(&command.indent) else {
+                    continue;
+                };
+                let index = command
+                    .parameters
+                    .first()
+                    .and_then(|p| p.as_integer())
+                    .map_or(0, |&i| i as usize);
+                if let Some(text) = command
+                    .parameters
+                    .get_mut(1)
+                    .and_then(|p| p.as_string_mut())
+                {
+                    changed |= f(
+                        Field::Choice {
+                            line,
+                            choices,
+                            index,
+                        },
+                        text,
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+    changed
+}
@@ -0,0 +1,302 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+use std::collections::HashMap;
+
+use luminol_data::commands::CommandKind;
+use luminol_data::rpg;
+use strum::IntoEnumIterator;
+
+use crate::command_codes::TRANSFER_PLAYER;
+use crate::references::{Location, Target};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, strum::Display)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// A problem found by [`validate`].
+#[derive(Debug, Clone)]
+pub struct Problem {
+    pub severity: Severity,
+    pub location: Location,
+    pub message: String,
+}
+
+/// Check the project for problems.
+///
+/// This loads every map in the project that exists.
+pub fn validate(
+    data: &crate::Data,
+    filesystem: &impl luminol_filesystem::FileSystem,
+    command_db: &luminol_config::command_db::CommandDB,
+) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let mut push = |severity: Severity, location: Location, message: String| {
+        problems.push(Problem {
+            severity,
+            location,
+            message,
+        })
+    };
+
+    let mut map_ids = data.map_infos().data.keys().copied().collect::<Vec<_>>();
+    map_ids.sort_unstable();
+
+    // Map tree
+    {
+        let map_infos = data.map_infos();
+        for &map_id in &map_ids {
+            let parent_id = map_infos.data[&map_id].parent_id;
+            if parent_id != 0 && !map_infos.data.contains_key(&parent_id) {
+                push(
+                    Severity::Warning,
+                    Location::Map { map_id },
+                    format!("Parent map {parent_id:0>3} does not exist"),
+                );
+            }
+        }
+    }
+
+    // Maps that can't be loaded are skipped from here on
+    let map_ids = map_ids
+        .into_iter()
+        .filter(|&map_id| {
+            let Err(error) = data.try_get_or_load_map(map_id, filesystem) else {
+                return true;
+            };
+            let exists = filesystem
+                .exists(format!("Data/Map{map_id:0>3}.rxdata"))
+                .unwrap_or(false);
+            push(
+                Severity::Error,
+                Location::Map { map_id },
+                if exists {
+                    format!("Map{map_id:0>3}.rxdata could not be read: {error:#}")
+                } else {
+                    format!("Map{map_id:0>3}.rxdata is missing")
+                },
+            );
+            false
+        })
+        .collect::<Vec<_>>();
+
+    let map_sizes = map_ids
+        .iter()
+        .map(|&map_id| {
+            let map = data.get_or_load_map(map_id, filesystem);
+            (map_id, (map.width, map.height))
+        })
+        .collect::<HashMap<_, _>>();
+
+    // Maps and their events
+    let tileset_count = data.tilesets().data.len();
+    for &map_id in &map_ids {
+        let map = data.get_or_load_map(map_id, filesystem);
+
+        if map.tileset_id >= tileset_count {
+            push(
+                Severity::Error,
+                Location::Map { map_id },
+                format!("Tileset {:0>3} does not exist", map.tileset_id + 1),
+            );
+        }
+
+        let mut seen_ids = HashMap::new();
+        for (event_id, event) in map.events.iter() {
+            let location = Location::MapEvent {
+                map_id,
+                event_id,
+                page: 0,
+            };
+
+            if event.x < 0
+                || event.y < 0
+                || event.x as usize >= map.width
+                || event.y as usize >= map.height
+            {
+                push(
+                    Severity::Error,
+                    location,
+                    format!("Event is outside the map at ({}, {})", event.x, event.y),
+                );
+            }
+
+            if event.id != event_id {
+                push(
+                    Severity::Error,
+                    location,
+                    format!(
+                        "Event has id {:0>3} but is stored as {event_id:0>3}",
+                        event.id
+                    ),
+                );
+            }
+            if let Some(other) = seen_ids.insert(event.id, event_id) {
+                push(
+                    Severity::Error,
+                    location,
+                    format!(
+                        "Event id {:0>3} is also used by event {other:0>3}",
+                        event.id
+                    ),
+                );
+            }
+
+            for (page, event_page) in event.pages.iter().enumerate() {
+                let location = Location::MapEvent {
+                    map_id,
+                    event_id,
+                    page,
+                };
+                check_commands(
+                    &event_page.list,
+                    location,
+                    command_db,
+                    &map_sizes,
+                    &mut push,
+                );
+            }
+        }
+    }
+
+    for (index, common_event) in data.common_events().data.iter().enumerate() {
+        check_commands(
+            &common_event.list,
+            Location::CommonEvent { index },
+            command_db,
+            &map_sizes,
+            &mut push,
+        );
+    }
+
+    for (index, troop) in data.troops().data.iter().enumerate() {
+        for (page, troop_page) in troop.pages.iter().enumerate() {
+            check_commands(
+                &troop_page.list,
+                Location::Troop { index, page },
+                command_db,
+                &map_sizes,
+                &mut push,
+            );
+        }
+    }
+
+    // References to database entries that don't exist
+    for target in Target::iter() {
+        let count = crate::reindex::entry_count(data, target);
+        crate::references::visit_references(
+            data,
+            filesystem,
+            command_db,
+            target,
+            |location, description, id| {
+                if id > count {
+                    push(
+                        Severity::Error,
+                        location,
+                        format!("{description}: {target} {id:0>3} does not exist"),
+                    );
+                }
+                Some(id)
+            },
+        );
+    }
+
+    problems.sort_by(|a, b| b.severity.cmp(&a.severity));
+    problems
+}
+
+fn check_commands(
+    list: &[rpg::EventCommand],
+    location: Location,
+    command_db: &luminol_config::command_db::CommandDB,
+    map_sizes: &HashMap<usize, (usize, usize)>,
+    push: &mut impl FnMut(Severity, Location, String),
+) {
+    for (line, command) in list.iter().enumerate() {
+        if let Some(description) = command_db.get(command.code) {
+            if let CommandKind::Branch { end_code, .. } = description.kind {
+                let has_end = list[line + 1..]
+                    .iter()
+                    .take_while(|c| c.indent >= command.indent)
+                    .any(|c| c.indent == command.indent && c.code == end_code);
+                if !has_end {
+                    push(
+                        Severity::Error,
+                        location,
+                        format!(
+                            "Line {}: {} is missing its end command",
+                            line + 1,
+                            description.name
+                        ),
+                    );
+                }
+            }
+        }
+
+        if command.code == TRANSFER_PLAYER {
+            let parameter = |index: usize| {
+                command
+                    .parameters
+                    .get(index)
+                    .and_then(|p| p.as_integer())
+                    .copied()
+            };
+            // The destination is only known here if it's not taken from variables
+            if parameter(0) != Some(0) {
+                continue;
+            }
+            let (Some(map_id), Some(x), Some(y)) = (parameter(1), parameter(2), parameter(3))
+            else {
+                continue;
+            };
+
+            match map_sizes.get(&(map_id as usize)) {
+                None => push(
+                    Severity::Error,
+                    location,
+                    format!(
+                        "Line {}: Transfer Player to map {map_id:0>3}, which does not exist",
+                        line + 1
+                    ),
+                ),
+                Some(&(width, height))
+                    if x < 0 || y < 0 || x as usize >= width || y as usize >= height =>
+                {
+                    push(
+                        Severity::Error,
+                        location,
+                        format!(
+                            "Line {}: Transfer Player to ({x}, {y}), which is outside map {map_id:0>3}",
+                            line + 1
+                        ),
+                    )
+                }
+                Some(_) => {}
+            }
+        }
+    }
+}
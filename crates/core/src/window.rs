@@ -61,6 +61,10 @@ impl Windows {
     }
 
     pub fn process_edit_windows(&mut self, mut edit_windows: EditWindows) {
+        if !edit_windows.removed.is_empty() {
+            self.windows
+                .retain(|w| !edit_windows.removed.contains(&w.id()));
+        }
         for window in edit_windows.added.drain(..) {
             self.add_boxed_window(window)
         }
@@ -100,15 +104,7 @@ impl Windows {
             open
         });
 
-        for window in edit_windows.added {
-            if self.windows.iter().any(|w| w.id() == window.id()) {
-                return;
-            }
-            self.windows.push(window);
-        }
-        if let Some(f) = edit_windows.clean_fn {
-            self.clean_windows(f)
-        }
+        self.process_edit_windows(edit_windows);
     }
 }
 
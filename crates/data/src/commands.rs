@@ -49,6 +49,27 @@ impl CommandDescription {
     }
 }
 
+impl CommandDescription {
+    /// Walk over every parameter of `command` that this description knows about.
+    ///
+    /// Selections are resolved using the value `command` actually has at the selection's index,
+    /// so only the parameters that apply to this particular command are visited.
+    pub fn walk_parameters<'a>(
+        &'a self,
+        command: &'a crate::rpg::EventCommand,
+        mut f: impl FnMut(usize, &'a ParameterKind, &'a crate::ParameterType),
+    ) {
+        match self.kind {
+            CommandKind::Branch { ref parameters, .. } | CommandKind::Single(ref parameters) => {
+                for parameter in parameters {
+                    parameter.walk(command, &mut f);
+                }
+            }
+            CommandKind::Multi { .. } => {}
+        }
+    }
+}
+
 impl Default for CommandDescription {
     fn default() -> Self {
         CommandDescription {
@@ -185,6 +206,40 @@ impl PartialEq for Parameter {
 }
 
 impl Parameter {
+    fn walk<'a>(
+        &'a self,
+        command: &'a crate::rpg::EventCommand,
+        f: &mut impl FnMut(usize, &'a ParameterKind, &'a crate::ParameterType),
+    ) {
+        match self {
+            Self::Selection {
+                index, parameters, ..
+            } => {
+                let Some(value) = command
+                    .parameters
+                    .get(index.as_usize())
+                    .and_then(|p| p.as_integer())
+                else {
+                    return;
+                };
+                for (_, parameter) in parameters.iter().filter(|(v, _)| *v as i32 == *value) {
+                    parameter.walk(command, f);
+                }
+            }
+            Self::Group { parameters, .. } => {
+                for parameter in parameters {
+                    parameter.walk(command, f);
+                }
+            }
+            Self::Single { index, kind, .. } => {
+                if let Some(value) = command.parameters.get(index.as_usize()) {
+                    f(index.as_usize(), kind, value);
+                }
+            }
+            Self::Dummy | Self::Label(_) => {}
+        }
+    }
+
     pub fn parameter_count(&self) -> u8 {
         match self {
             Self::Group { ref parameters, .. } => {
@@ -229,6 +284,29 @@ pub enum ParameterKind {
     ///
     /// The variants are a Vec of (String, i8) with the String being the variant, and the i8 being the value
     Enum { variants: Vec<(String, i8)> },
+
+    /// Parameter is an actor id
+    Actor,
+    /// Parameter is a class id
+    Class,
+    /// Parameter is a skill id
+    Skill,
+    /// Parameter is an item id
+    Item,
+    /// Parameter is a weapon id
+    Weapon,
+    /// Parameter is an armor id
+    Armor,
+    /// Parameter is an enemy id
+    Enemy,
+    /// Parameter is a troop id
+    Troop,
+    /// Parameter is a state id
+    State,
+    /// Parameter is an animation id
+    Animation,
+    /// Parameter is a common event id
+    CommonEvent,
 }
 
 impl PartialEq for ParameterKind {
@@ -15,6 +15,8 @@ pub mod helpers;
 
 pub mod commands;
 
+pub mod tilemap;
+
 pub use helpers::*;
 pub use option_vec::OptionVec;
 pub use rgss_structs::{Color, Table1, Table2, Table3, Tone};
@@ -0,0 +1,587 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+//! Tilemap logic that doesn't depend on the GUI or the renderer: autotile resolution, passage
+//! calculation and walkability analysis. Everything in here operates directly on the map's tile data.
+
+use crate::{rpg, OptionVec, Table1, Table2, Table3};
+
+/// Returns the position offset by `(x, y)`, or `None` if that would be out of the bounds of
+/// `tiles`.
+fn offset_position(
+    tiles: &Table3,
+    position: (usize, usize, usize),
+    (x, y): (i8, i8),
+) -> Option<(usize, usize, usize)> {
+    let new_x = position.0.checked_add_signed(x as isize)?;
+    let new_y = position.1.checked_add_signed(y as isize)?;
+    (new_x < tiles.xsize() && new_y < tiles.ysize()).then_some((new_x, new_y, position.2))
+}
+
+/// Determines which of the 48 variants of an autotile the tile at `position` should be, based on
+/// its 8 neighbors, and returns the resulting tile ID.
+///
+/// Tiles that aren't autotiles are returned unchanged, and blank autotiles are returned as 0.
+pub fn recompute_autotile(tiles: &Table3, position: (usize, usize, usize)) -> i16 {
+    if tiles[position] >= 384 {
+        return tiles[position];
+    }
+
+    let autotile = tiles[position] / 48;
+    if autotile == 0 {
+        return 0;
+    }
+
+    let x_array: [i8; 8] = [-1, 0, 1, 1, 1, 0, -1, -1];
+    let y_array: [i8; 8] = [-1, -1, -1, 0, 1, 1, 1, 0];
+
+    /*
+     * 765
+     * 0 4
+     * 123
+     */
+    let mut bitfield = 0u8;
+
+    // Loop through the 8 neighbors of this position
+    for (x, y) in x_array.into_iter().zip(y_array.into_iter()) {
+        bitfield <<= 1;
+        // Out-of-bounds tiles always count as valid neighbors, otherwise we only consider
+        // neighbors that are autotiles of the same type
+        if offset_position(tiles, position, (x, y))
+            .map_or(true, |neighbor| tiles[neighbor] / 48 == autotile)
+        {
+            bitfield |= 1
+        }
+    }
+
+    // Check how many edges have valid neighbors
+    autotile * 48
+        + match (bitfield & 0b01010101).count_ones() {
+            4 => {
+                // If the autotile is surrounded on all 4 edges,
+                // then the autotile variant is one of the first 16,
+                // depending on which corners are surrounded
+                let tl = (bitfield & 0b10000000 == 0) as u8;
+                let tr = (bitfield & 0b00100000 == 0) as u8;
+                let br = (bitfield & 0b00001000 == 0) as u8;
+                let bl = (bitfield & 0b00000010 == 0) as u8;
+                tl | (tr << 1) | (br << 2) | (bl << 3)
+            }
+
+            3 => {
+                // Rotate the bitfield 90 degrees counterclockwise until
+                // the one edge that is not surrounded is at the left
+                let mut bitfield = bitfield;
+                let mut i = 16u8;
+                while bitfield & 0b00000001 != 0 {
+                    bitfield = bitfield.rotate_left(2);
+                    i += 4;
+                }
+                // Now, the variant is one of the next 16
+                let tr = (bitfield & 0b00100000 == 0) as u8;
+                let br = (bitfield & 0b00001000 == 0) as u8;
+                i + (tr | (br << 1))
+            }
+
+            // Top and bottom edges
+            2 if bitfield & 0b01000100 == 0b01000100 => 32,
+
+            // Left and right edges
+            2 if bitfield & 0b00010001 == 0b00010001 => 33,
+
+            2 => {
+                // Rotate the bitfield 90 degrees counterclockwise until
+                // the two edges that are surrounded are at the right and bottom
+                let mut bitfield = bitfield;
+                let mut i = 34u8;
+                while bitfield & 0b00010100 != 0b00010100 {
+                    bitfield = bitfield.rotate_left(2);
+                    i += 2;
+                }
+                let br = (bitfield & 0b00001000 == 0) as u8;
+                i + br
+            }
+
+            1 => {
+                // Rotate the bitfield 90 degrees clockwise until
+                // the edge is at the bottom
+                let mut bitfield = bitfield;
+                let mut i = 42u8;
+                while bitfield & 0b00000100 == 0 {
+                    bitfield = bitfield.rotate_right(2);
+                    i += 1;
+                }
+                i
+            }
+
+            0 => 46,
+
+            _ => unreachable!(),
+        } as i16
+}
+
+/// Sets the tile at `position` to `tile_id` and recomputes the autotile variants of it and its 8
+/// neighbors.
+pub fn set_tile(tiles: &mut Table3, tile_id: i16, position: (usize, usize, usize)) {
+    tiles[position] = tile_id;
+
+    for y in -1i8..=1i8 {
+        for x in -1i8..=1i8 {
+            // Don't check tiles that are out of bounds
+            let Some(position) = offset_position(tiles, position, (x, y)) else {
+                continue;
+            };
+            let tile_id = recompute_autotile(tiles, position);
+            tiles[position] = tile_id;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollisionType {
+    /// An event
+    Event,
+    /// A tile whose ID is less than 48 (i.e. a blank autotile)
+    BlankTile,
+    /// A tile whose ID is greater than or equal to 48
+    Tile,
+}
+
+/// Determines the passage values for every position on the map, running `f(x, y, passage)` for
+/// every position.
+///
+/// `layers` should be an iterator over the enabled layer numbers of the map from top to bottom.
+pub fn calculate_passages(
+    passages: &Table1,
+    priorities: &Table1,
+    tiles: &Table3,
+    events: Option<&OptionVec<rpg::Event>>,
+    layers: impl Iterator<Item = usize> + Clone,
+    mut f: impl FnMut(usize, usize, i16),
+) {
+    let tileset_size = passages.len().min(priorities.len());
+
+    let mut event_map = if let Some(events) = events {
+        events
+            .iter()
+            .filter_map(|(_, event)| {
+                let Some(page) = event.pages.first() else {
+                    return None;
+                };
+                if page.through {
+                    return None;
+                }
+                let tile_event = page
+                    .graphic
+                    .tile_id
+                    .map_or((15, 1, CollisionType::Event), |id| {
+                        let tile_id = id + 1;
+                        if tile_id >= tileset_size {
+                            (0, 0, CollisionType::Event)
+                        } else {
+                            (passages[tile_id], priorities[tile_id], CollisionType::Event)
+                        }
+                    });
+                Some(((event.x as usize, event.y as usize), tile_event))
+            })
+            .collect()
+    } else {
+        std::collections::HashMap::new()
+    };
+
+    for y in 0..tiles.ysize() {
+        for x in 0..tiles.xsize() {
+            let tile_event = event_map.remove(&(x, y));
+
+            f(
+                x,
+                y,
+                calculate_passage(tile_event.into_iter().chain(layers.clone().map(|z| {
+                    let tile_id = tiles[(x, y, z)].try_into().unwrap_or_default();
+                    let collision_type = if tile_id < 48 {
+                        CollisionType::BlankTile
+                    } else {
+                        CollisionType::Tile
+                    };
+                    if tile_id >= tileset_size {
+                        (0, 0, collision_type)
+                    } else {
+                        (passages[tile_id], priorities[tile_id], collision_type)
+                    }
+                }))),
+            );
+        }
+    }
+}
+
+/// Determines the passage value for a position on the map given an iterator over the
+/// `(passage, priority, collision_type)` values for the tiles in each layer on that position.
+/// The iterator should iterate over the layers from top to bottom.
+pub fn calculate_passage(layers: impl Iterator<Item = (i16, i16, CollisionType)> + Clone) -> i16 {
+    let mut computed_passage = 0;
+
+    for direction in [1, 2, 4, 8] {
+        let mut at_least_one_layer_not_blank = false;
+        let mut layers = layers.clone().peekable();
+        while let Some((passage, priority, collision_type)) = layers.next() {
+            if matches!(
+                collision_type,
+                CollisionType::Tile | CollisionType::BlankTile
+            ) {
+                if matches!(collision_type, CollisionType::BlankTile)
+                    && (at_least_one_layer_not_blank || layers.peek().is_some())
+                {
+                    continue;
+                } else {
+                    at_least_one_layer_not_blank = true;
+                }
+            }
+            if passage & direction != 0 {
+                computed_passage |= direction;
+                break;
+            } else if priority == 0 {
+                break;
+            }
+        }
+    }
+
+    computed_passage
+}
+
+/// The four directions the player can move in as `(x offset, y offset, passage bit)`, ordered so
+/// that the opposite of the direction at index `i` is at index `3 - i`.
+const DIRECTIONS: [(i8, i8, i16); 4] = [(0, 1, 1), (-1, 0, 2), (1, 0, 4), (0, -1, 8)];
+
+/// Returns the positions the player can move to in a single step from `position`, given the
+/// passage values computed by [`calculate_passages`].
+///
+/// Like in RPG Maker XP, a move is only possible if the current tile isn't blocked in the direction
+/// of movement and the destination tile isn't blocked in the opposite direction.
+pub fn walkable_neighbors(
+    passages: &Table2,
+    (x, y): (usize, usize),
+) -> impl Iterator<Item = (usize, usize)> + '_ {
+    DIRECTIONS
+        .iter()
+        .enumerate()
+        .filter_map(move |(index, &(offset_x, offset_y, direction))| {
+            let new_x = x.checked_add_signed(offset_x as isize)?;
+            let new_y = y.checked_add_signed(offset_y as isize)?;
+            if new_x >= passages.xsize() || new_y >= passages.ysize() {
+                return None;
+            }
+            let opposite = DIRECTIONS[3 - index].2;
+            (passages[(x, y)] & direction == 0 && passages[(new_x, new_y)] & opposite == 0)
+                .then_some((new_x, new_y))
+        })
+}
+
+/// Runs a breadth-first search over the tiles the player can walk to from `start`, stopping early
+/// if `goal` is reached.
+///
+/// Returns the position every tile was first reached from, indexed by `x + y * xsize`. The start
+/// tile is reached from itself and tiles that weren't reached are `None`.
+fn walk_search(
+    passages: &Table2,
+    start: (usize, usize),
+    goal: Option<(usize, usize)>,
+) -> Vec<Option<(usize, usize)>> {
+    let xsize = passages.xsize();
+    let mut predecessors = vec![None; passages.len()];
+    if start.0 >= xsize || start.1 >= passages.ysize() {
+        return predecessors;
+    }
+
+    let mut queue = std::collections::VecDeque::from([start]);
+    predecessors[start.0 + start.1 * xsize] = Some(start);
+
+    while let Some(position) = queue.pop_front() {
+        if goal == Some(position) {
+            break;
+        }
+        for neighbor in walkable_neighbors(passages, position) {
+            let predecessor = &mut predecessors[neighbor.0 + neighbor.1 * xsize];
+            if predecessor.is_none() {
+                *predecessor = Some(position);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+
+    predecessors
+}
+
+/// Determines which tiles the player can walk to from `start`, given the passage values computed
+/// by [`calculate_passages`]. The result is indexed by `x + y * xsize`.
+pub fn reachable_tiles(passages: &Table2, start: (usize, usize)) -> Vec<bool> {
+    walk_search(passages, start, None)
+        .into_iter()
+        .map(|predecessor| predecessor.is_some())
+        .collect()
+}
+
+/// Finds one of the shortest paths the player can walk from `start` to `goal`, given the passage
+/// values computed by [`calculate_passages`]. The path includes both `start` and `goal`.
+///
+/// Returns `None` if `goal` can't be reached.
+pub fn find_path(
+    passages: &Table2,
+    start: (usize, usize),
+    goal: (usize, usize),
+) -> Option<Vec<(usize, usize)>> {
+    if goal.0 >= passages.xsize() || goal.1 >= passages.ysize() {
+        return None;
+    }
+
+    let predecessors = walk_search(passages, start, Some(goal));
+    let mut path = vec![goal];
+    let mut position = goal;
+    while position != start {
+        position = predecessors[position.0 + position.1 * passages.xsize()]?;
+        path.push(position);
+    }
+    path.reverse();
+    Some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The ID of the first variant of the first non-blank autotile.
+    const AUTOTILE: i16 = 48;
+    /// The ID of the first variant of the second non-blank autotile.
+    const OTHER_AUTOTILE: i16 = 96;
+
+    /// Builds a single-layer map from rows of tile IDs.
+    fn single_layer(rows: &[&[i16]]) -> Table3 {
+        Table3::new_data(
+            rows[0].len(),
+            rows.len(),
+            1,
+            rows.iter().flat_map(|row| row.iter().copied()).collect(),
+        )
+    }
+
+    /// Recomputes every tile in the first layer and returns the resulting variants (0-47).
+    fn variants(tiles: &Table3) -> Vec<Vec<i16>> {
+        (0..tiles.ysize())
+            .map(|y| {
+                (0..tiles.xsize())
+                    .map(|x| recompute_autotile(tiles, (x, y, 0)) % 48)
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn isolated_autotile() {
+        let tiles = single_layer(&[&[0, 0, 0], &[0, AUTOTILE, 0], &[0, 0, 0]]);
+        assert_eq!(recompute_autotile(&tiles, (1, 1, 0)), AUTOTILE + 46);
+    }
+
+    #[test]
+    fn out_of_bounds_neighbors_connect() {
+        let tiles = single_layer(&[&[AUTOTILE]]);
+        assert_eq!(recompute_autotile(&tiles, (0, 0, 0)), AUTOTILE);
+    }
+
+    #[test]
+    fn square_of_autotiles() {
+        let a = AUTOTILE;
+        let tiles = single_layer(&[
+            &[0, 0, 0, 0, 0],
+            &[0, a, a, a, 0],
+            &[0, a, a, a, 0],
+            &[0, a, a, a, 0],
+            &[0, 0, 0, 0, 0],
+        ]);
+        let variants = variants(&tiles);
+        assert_eq!(variants[1][1..4], [34, 20, 36]);
+        assert_eq!(variants[2][1..4], [16, 0, 24]);
+        assert_eq!(variants[3][1..4], [40, 28, 38]);
+    }
+
+    #[test]
+    fn lines_of_autotiles() {
+        let a = AUTOTILE;
+        let horizontal = single_layer(&[&[0, 0, 0, 0, 0], &[0, a, a, a, 0], &[0, 0, 0, 0, 0]]);
+        assert_eq!(variants(&horizontal)[1][1..4], [43, 33, 45]);
+
+        let vertical = single_layer(&[&[0, 0, 0], &[0, a, 0], &[0, a, 0], &[0, a, 0], &[0, 0, 0]]);
+        let variants = variants(&vertical);
+        assert_eq!(
+            [variants[1][1], variants[2][1], variants[3][1]],
+            [42, 32, 44]
+        );
+    }
+
+    #[test]
+    fn inner_corners() {
+        let a = AUTOTILE;
+        // Fully surrounded on the edges, but missing the top left corner
+        let tiles = single_layer(&[&[0, a, a], &[a, a, a], &[a, a, a]]);
+        assert_eq!(recompute_autotile(&tiles, (1, 1, 0)), a + 1);
+
+        // Missing every corner
+        let tiles = single_layer(&[&[0, a, 0], &[a, a, a], &[0, a, 0]]);
+        assert_eq!(recompute_autotile(&tiles, (1, 1, 0)), a + 15);
+    }
+
+    #[test]
+    fn different_autotiles_do_not_connect() {
+        let tiles = single_layer(&[
+            &[OTHER_AUTOTILE, OTHER_AUTOTILE, OTHER_AUTOTILE],
+            &[OTHER_AUTOTILE, AUTOTILE, OTHER_AUTOTILE],
+            &[OTHER_AUTOTILE, OTHER_AUTOTILE, OTHER_AUTOTILE],
+        ]);
+        assert_eq!(recompute_autotile(&tiles, (1, 1, 0)), AUTOTILE + 46);
+    }
+
+    #[test]
+    fn non_autotiles_are_unchanged() {
+        let tiles = single_layer(&[&[0, 30, 384]]);
+        assert_eq!(recompute_autotile(&tiles, (0, 0, 0)), 0);
+        assert_eq!(recompute_autotile(&tiles, (1, 0, 0)), 0);
+        assert_eq!(recompute_autotile(&tiles, (2, 0, 0)), 384);
+    }
+
+    #[test]
+    fn set_tile_updates_neighbors() {
+        let mut tiles = single_layer(&[&[0, 0, 0, 0], &[0, AUTOTILE + 46, 0, 0], &[0, 0, 0, 0]]);
+        set_tile(&mut tiles, AUTOTILE, (2, 1, 0));
+        assert_eq!(tiles[(1, 1, 0)], AUTOTILE + 43);
+        assert_eq!(tiles[(2, 1, 0)], AUTOTILE + 45);
+    }
+
+    #[test]
+    fn passage_of_single_tile() {
+        assert_eq!(
+            calculate_passage([(15, 0, CollisionType::Tile)].into_iter()),
+            15
+        );
+        assert_eq!(
+            calculate_passage([(0, 0, CollisionType::Tile)].into_iter()),
+            0
+        );
+    }
+
+    #[test]
+    fn passage_ignores_blank_upper_layers() {
+        let layers = [
+            (0, 0, CollisionType::BlankTile),
+            (0, 0, CollisionType::BlankTile),
+            (15, 0, CollisionType::Tile),
+        ];
+        assert_eq!(calculate_passage(layers.into_iter()), 15);
+    }
+
+    #[test]
+    fn passage_with_priority() {
+        // A passable tile with a priority doesn't make an impassable tile below it passable
+        let layers = [(0, 1, CollisionType::Tile), (15, 0, CollisionType::Tile)];
+        assert_eq!(calculate_passage(layers.into_iter()), 15);
+
+        // A passable tile without a priority does
+        let layers = [(0, 0, CollisionType::Tile), (15, 0, CollisionType::Tile)];
+        assert_eq!(calculate_passage(layers.into_iter()), 0);
+
+        // Directions are handled separately
+        let layers = [(1, 1, CollisionType::Tile), (8, 0, CollisionType::Tile)];
+        assert_eq!(calculate_passage(layers.into_iter()), 9);
+    }
+
+    #[test]
+    fn passages_with_events() {
+        // Tile 48 is passable and tile 96 is impassable
+        let mut passages = Table1::new(144);
+        passages[96] = 15;
+        let priorities = Table1::new(144);
+
+        let tiles = single_layer(&[&[48, 96, 48]]);
+
+        let mut events = OptionVec::new();
+        events.insert(1, rpg::Event::new(0, 0, 1));
+        let mut through = rpg::Event::new(2, 0, 2);
+        through.pages[0].through = true;
+        events.insert(2, through);
+
+        let mut result = vec![];
+        calculate_passages(
+            &passages,
+            &priorities,
+            &tiles,
+            Some(&events),
+            0..1,
+            |x, _, passage| result.push((x, passage)),
+        );
+        assert_eq!(result, [(0, 15), (1, 15), (2, 0)]);
+
+        result.clear();
+        calculate_passages(
+            &passages,
+            &priorities,
+            &tiles,
+            None,
+            0..1,
+            |x, _, passage| result.push((x, passage)),
+        );
+        assert_eq!(result, [(0, 0), (1, 15), (2, 0)]);
+    }
+
+    #[test]
+    fn walking_around_walls() {
+        // 15 blocks every direction, 4 only blocks moving right out of (or left into) the tile
+        let mut passages = Table2::new(4, 3);
+        #[rustfmt::skip]
+        passages.as_mut_slice().copy_from_slice(&[
+            0, 15, 0, 0,
+            0, 15, 4, 0,
+            0, 0, 0, 15,
+        ]);
+
+        let reachable = reachable_tiles(&passages, (0, 0));
+        assert_eq!(
+            reachable,
+            [true, false, true, true, true, false, true, true, true, true, true, false]
+        );
+
+        assert_eq!(
+            find_path(&passages, (0, 0), (3, 1)),
+            Some(vec![
+                (0, 0),
+                (0, 1),
+                (0, 2),
+                (1, 2),
+                (2, 2),
+                (2, 1),
+                (2, 0),
+                (3, 0),
+                (3, 1)
+            ])
+        );
+        assert_eq!(find_path(&passages, (0, 0), (1, 0)), None);
+        assert_eq!(find_path(&passages, (0, 0), (0, 0)), Some(vec![(0, 0)]));
+    }
+}
@@ -0,0 +1,137 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+//! Benchmarks for listing and caching the files of a synthetic project with tens of thousands of
+//! assets, split between the project itself and an RTP with a lot of the same filenames.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use luminol_filesystem::{list, memory, path_cache, FileSystem};
+
+const ASSET_DIRS: &[&str] = &[
+    "Audio/BGM",
+    "Audio/BGS",
+    "Audio/ME",
+    "Audio/SE",
+    "Graphics/Animations",
+    "Graphics/Autotiles",
+    "Graphics/Battlers",
+    "Graphics/Characters",
+    "Graphics/Fogs",
+    "Graphics/Icons",
+    "Graphics/Panoramas",
+    "Graphics/Pictures",
+    "Graphics/Tilesets",
+];
+const FILES_PER_DIR: usize = 2500;
+
+/// Creates a project and an RTP that each have `FILES_PER_DIR` files in every asset directory,
+/// with half of the project's filenames also being in the RTP.
+fn synthetic_project() -> (memory::FileSystem, memory::FileSystem) {
+    let project = memory::FileSystem::new();
+    let rtp = memory::FileSystem::new();
+
+    project.create_dir("Data").unwrap();
+    for id in 1..=999 {
+        project
+            .write(format!("Data/Map{id:0>3}.rxdata"), [0; 64])
+            .unwrap();
+    }
+
+    for dir in ASSET_DIRS {
+        project.create_dir(dir).unwrap();
+        rtp.create_dir(dir).unwrap();
+        for index in 0..FILES_PER_DIR {
+            project
+                .write(format!("{dir}/Asset{index:0>5}.png"), [0; 64])
+                .unwrap();
+            rtp.write(
+                format!("{dir}/Asset{:0>5}.png", index + FILES_PER_DIR / 2),
+                [0; 32],
+            )
+            .unwrap();
+        }
+    }
+
+    (project, rtp)
+}
+
+fn stack(project: &memory::FileSystem, rtp: &memory::FileSystem) -> list::FileSystem {
+    let mut list = list::FileSystem::new();
+    list.push(project.clone());
+    list.push(rtp.clone());
+    list
+}
+
+fn benchmarks(c: &mut Criterion) {
+    let (project, rtp) = synthetic_project();
+
+    c.bench_function("list read_dir merge", |b| {
+        let list = stack(&project, &rtp);
+        b.iter(|| list.read_dir("Graphics/Characters").unwrap())
+    });
+
+    c.bench_function("path cache build", |b| {
+        b.iter_batched(
+            || stack(&project, &rtp),
+            |list| path_cache::FileSystem::new(list).unwrap(),
+            BatchSize::LargeInput,
+        )
+    });
+
+    let path_cache = path_cache::FileSystem::new(stack(&project, &rtp)).unwrap();
+
+    c.bench_function("path cache create and remove file", |b| {
+        b.iter(|| {
+            path_cache
+                .write("Graphics/Pictures/new_picture.png", [0; 64])
+                .unwrap();
+            path_cache
+                .remove_file("graphics/pictures/new_picture")
+                .unwrap();
+        })
+    });
+
+    c.bench_function("path cache remove shadowing file", |b| {
+        b.iter(|| {
+            // The RTP has a file with the same name, so it has to be found again
+            path_cache
+                .remove_file("Graphics/Icons/Asset02000.png")
+                .unwrap();
+            assert!(path_cache.exists("graphics/icons/asset02000").unwrap());
+            project
+                .write("Graphics/Icons/Asset02000.png", [0; 64])
+                .unwrap();
+        })
+    });
+
+    c.bench_function("path cache rename directory", |b| {
+        b.iter(|| {
+            path_cache.rename("Graphics/Fogs", "Graphics/Mist").unwrap();
+            path_cache.rename("Graphics/Mist", "Graphics/Fogs").unwrap();
+        })
+    });
+}
+
+criterion_group!(benches, benchmarks);
+criterion_main!(benches);
@@ -516,11 +516,13 @@ where
             Ok(Metadata {
                 is_file: true,
                 size: entry.size,
+                modified: None,
             })
         } else if let Some(size) = trie.get_dir_size(path) {
             Ok(Metadata {
                 is_file: false,
                 size: size as u64,
+                modified: None,
             })
         } else {
             Err(Error::NotExist)
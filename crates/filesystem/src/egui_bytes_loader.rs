@@ -67,6 +67,24 @@ impl Loader {
         }
         self.unloaded_files.clear();
     }
+
+    /// Makes egui reload every image loaded from `path`, after it was changed outside of Luminol.
+    pub fn forget_changed(&self, ctx: &egui::Context, path: impl AsRef<camino::Utf8Path>) {
+        let changed = crate::path_cache::to_lowercase(path.as_ref().with_extension(""));
+        let uris: Vec<_> = self
+            .loaded_files
+            .iter()
+            .map(|entry| entry.key().clone())
+            .chain(self.errored_files.iter().map(|entry| entry.key().clone()))
+            .filter(|path| crate::path_cache::to_lowercase(path.with_extension("")) == changed)
+            .map(|path| format!("{PROTOCOL}{path}"))
+            .collect();
+
+        // This also forgets the decoded image, unlike calling `forget` on ourselves
+        for uri in uris {
+            ctx.forget_image(&uri);
+        }
+    }
 }
 
 impl egui::load::BytesLoader for Loader {
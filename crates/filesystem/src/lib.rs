@@ -19,8 +19,11 @@ pub mod archiver;
 pub mod egui_bytes_loader;
 pub mod erased;
 pub mod list;
+pub mod memory;
 pub mod path_cache;
 pub mod project;
+pub mod watch;
+pub mod zip;
 
 mod trie;
 pub use trie::*;
@@ -53,6 +56,8 @@ pub enum Error {
     NotSupported,
     #[error("Archive header is incorrect")]
     InvalidHeader,
+    #[error("Zip error {0}")]
+    ZipError(#[from] ::zip::result::ZipError),
     #[error("No filesystems are loaded to perform this operation")]
     NoFilesystems,
     #[error("Unable to detect the project's RPG Maker version (perhaps you did not open an RPG Maker project?")]
@@ -73,6 +78,8 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub struct Metadata {
     pub is_file: bool,
     pub size: u64,
+    /// When the file was last modified, if the filesystem keeps track of it.
+    pub modified: Option<std::time::SystemTime>,
 }
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
@@ -183,6 +190,12 @@ pub trait FileSystem: Send + Sync {
 
     fn read_dir(&self, path: impl AsRef<camino::Utf8Path>) -> Result<Vec<DirEntry>>;
 
+    /// Starts watching this filesystem for changes made outside of Luminol.
+    /// Filesystems that can't be notified of changes fall back to [`watch::Watcher::polling`].
+    fn watch(&self) -> Result<watch::Watcher> {
+        Ok(watch::Watcher::polling())
+    }
+
     /// Corresponds to [`std::fs::read()`].
     /// Will open a file at the path and read the entire file into a buffer.
     fn read(&self, path: impl AsRef<camino::Utf8Path>) -> Result<Vec<u8>> {
@@ -217,3 +230,25 @@ pub trait FileSystem: Send + Sync {
         Ok(())
     }
 }
+
+/// Copy the directory at `path` in `from`, along with everything inside it, to the same path in `to`.
+pub fn copy_dir(
+    from: &impl FileSystem,
+    to: &impl FileSystem,
+    path: impl AsRef<camino::Utf8Path>,
+) -> Result<()> {
+    let path = path.as_ref();
+    if !to.exists(path)? {
+        to.create_dir(path)?;
+    }
+
+    for entry in from.read_dir(path)? {
+        if entry.metadata.is_file {
+            to.write(&entry.path, from.read(&entry.path)?)?;
+        } else {
+            copy_dir(from, to, &entry.path)?;
+        }
+    }
+
+    Ok(())
+}
@@ -16,7 +16,6 @@
 // along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
 
 use crate::{erased::ErasedFilesystem, DirEntry, Error, File, Metadata, OpenFlags};
-use itertools::Itertools;
 
 #[derive(Default)]
 pub struct FileSystem {
@@ -31,6 +30,55 @@ impl FileSystem {
     pub fn push(&mut self, fs: impl crate::FileSystem + 'static) {
         self.filesystems.push(Box::new(fs))
     }
+
+    /// Adds a filesystem at `index`, so it takes priority over the ones after it.
+    pub fn insert(&mut self, index: usize, fs: impl crate::FileSystem + 'static) {
+        self.filesystems.insert(index, Box::new(fs))
+    }
+
+    pub fn len(&self) -> usize {
+        self.filesystems.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filesystems.is_empty()
+    }
+
+    /// The index of the filesystem `path` is read from, if any of them have it.
+    pub fn find(&self, path: impl AsRef<camino::Utf8Path>) -> Result<Option<usize>, Error> {
+        let path = path.as_ref();
+        for (index, fs) in self.filesystems.iter().enumerate() {
+            if fs.exists(path)? {
+                return Ok(Some(index));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Merges the listings of the same directory in several filesystems. Each path is only listed
+/// once, using the entry from the first listing it appears in.
+pub(crate) fn merge_dir_entries(
+    listings: impl IntoIterator<Item = Vec<DirEntry>>,
+) -> Vec<DirEntry> {
+    let mut listings = listings.into_iter();
+    let Some(mut entries) = listings.next() else {
+        return Vec::new();
+    };
+
+    // Only start hashing paths once there's actually something to merge
+    let mut seen: Option<std::collections::HashSet<camino::Utf8PathBuf>> = None;
+    for listing in listings {
+        let seen =
+            seen.get_or_insert_with(|| entries.iter().map(|entry| entry.path.clone()).collect());
+        entries.extend(
+            listing
+                .into_iter()
+                .filter(|entry| seen.insert(entry.path.clone())),
+        );
+    }
+
+    entries
 }
 
 impl crate::FileSystem for FileSystem {
@@ -108,16 +156,14 @@ impl crate::FileSystem for FileSystem {
     fn read_dir(&self, path: impl AsRef<camino::Utf8Path>) -> Result<Vec<DirEntry>, Error> {
         let path = path.as_ref();
 
-        let mut entries = Vec::new();
+        let mut listings = Vec::with_capacity(self.filesystems.len());
         for fs in self.filesystems.iter() {
             if fs.exists(path)? {
-                entries.extend(fs.read_dir(path)?)
+                listings.push(fs.read_dir(path)?)
             }
         }
-        // FIXME: remove duplicates in a more efficient manner
-        let entries = entries.into_iter().unique().collect_vec();
 
-        Ok(entries)
+        Ok(merge_dir_entries(listings))
     }
 
     fn read(&self, path: impl AsRef<camino::Utf8Path>) -> Result<Vec<u8>, Error> {
@@ -0,0 +1,454 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+//! A filesystem that only exists in memory, for tests and for projects that haven't been given a
+//! location yet.
+
+use std::io::{
+    ErrorKind::{InvalidInput, PermissionDenied},
+    SeekFrom,
+};
+use std::sync::Arc;
+use std::{pin::Pin, task::Poll};
+
+use crate::{DirEntry, Error, Metadata, OpenFlags, Result};
+
+pub(crate) type Contents = Arc<parking_lot::RwLock<Vec<u8>>>;
+
+/// Cloning this gives another handle to the same files.
+#[derive(Debug, Default, Clone)]
+pub struct FileSystem {
+    trie: Arc<parking_lot::RwLock<crate::FileSystemTrie<Contents>>>,
+}
+
+/// An open file. Writes are visible to every other handle to the same file immediately, and the
+/// file stays readable after it's removed from the filesystem, like on Unix.
+#[derive(Debug)]
+pub struct File {
+    contents: Contents,
+    position: u64,
+    read_allowed: bool,
+    write_allowed: bool,
+}
+
+impl FileSystem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl File {
+    /// Opens a file with the given contents, with the read and write permissions in `flags`.
+    pub(crate) fn new(contents: Contents, flags: OpenFlags) -> Self {
+        Self {
+            contents,
+            position: 0,
+            read_allowed: flags.contains(OpenFlags::Read),
+            write_allowed: flags.contains(OpenFlags::Write),
+        }
+    }
+}
+
+impl crate::FileSystem for FileSystem {
+    type File = File;
+
+    fn open_file(&self, path: impl AsRef<camino::Utf8Path>, flags: OpenFlags) -> Result<File> {
+        let path = path.as_ref();
+        let mut trie = self.trie.write();
+
+        let contents = match trie.get_file(path) {
+            Some(contents) => contents.clone(),
+            None if flags.contains(OpenFlags::Create) => {
+                if trie.contains_dir(path) {
+                    return Err(Error::IoError(InvalidInput.into()));
+                }
+                if !trie.contains_dir(path.parent().unwrap_or(camino::Utf8Path::new(""))) {
+                    return Err(Error::NotExist);
+                }
+                let contents = Contents::default();
+                trie.create_file(path, contents.clone());
+                contents
+            }
+            None => return Err(Error::NotExist),
+        };
+
+        if flags.contains(OpenFlags::Truncate) {
+            if !flags.contains(OpenFlags::Write) {
+                return Err(Error::IoError(InvalidInput.into()));
+            }
+            contents.write().clear();
+        }
+
+        Ok(File::new(contents, flags))
+    }
+
+    fn metadata(&self, path: impl AsRef<camino::Utf8Path>) -> Result<Metadata> {
+        let path = path.as_ref();
+        let trie = self.trie.read();
+        if let Some(contents) = trie.get_file(path) {
+            Ok(Metadata {
+                is_file: true,
+                size: contents.read().len() as u64,
+                modified: None,
+            })
+        } else if let Some(size) = trie.get_dir_size(path) {
+            Ok(Metadata {
+                is_file: false,
+                size: size as u64,
+                modified: None,
+            })
+        } else {
+            Err(Error::NotExist)
+        }
+    }
+
+    fn rename(
+        &self,
+        from: impl AsRef<camino::Utf8Path>,
+        to: impl AsRef<camino::Utf8Path>,
+    ) -> Result<()> {
+        let from = from.as_ref();
+        let to = to.as_ref();
+        let mut trie = self.trie.write();
+
+        if trie.contains(to) {
+            return Err(Error::IoError(std::io::ErrorKind::AlreadyExists.into()));
+        }
+        if !trie.contains_dir(to.parent().unwrap_or(camino::Utf8Path::new(""))) {
+            return Err(Error::NotExist);
+        }
+
+        if !trie.rename(from, to) {
+            return Err(Error::NotExist);
+        }
+
+        Ok(())
+    }
+
+    fn exists(&self, path: impl AsRef<camino::Utf8Path>) -> Result<bool> {
+        Ok(self.trie.read().contains(path))
+    }
+
+    fn create_dir(&self, path: impl AsRef<camino::Utf8Path>) -> Result<()> {
+        let path = path.as_ref();
+        let mut trie = self.trie.write();
+        if trie.contains_file(path) {
+            return Err(Error::IoError(std::io::ErrorKind::AlreadyExists.into()));
+        }
+        trie.create_dir(path);
+        Ok(())
+    }
+
+    fn remove_dir(&self, path: impl AsRef<camino::Utf8Path>) -> Result<()> {
+        let path = path.as_ref();
+        let mut trie = self.trie.write();
+        if !trie.contains_dir(path) {
+            return Err(Error::NotExist);
+        }
+        trie.remove_dir(path);
+        Ok(())
+    }
+
+    fn remove_file(&self, path: impl AsRef<camino::Utf8Path>) -> Result<()> {
+        self.trie
+            .write()
+            .remove_file(path)
+            .map(|_| ())
+            .ok_or(Error::NotExist)
+    }
+
+    fn read_dir(&self, path: impl AsRef<camino::Utf8Path>) -> Result<Vec<DirEntry>> {
+        let path = path.as_ref();
+        let trie = self.trie.read();
+        let entries = trie
+            .iter_dir(path)
+            .ok_or(Error::NotExist)?
+            .map(|(name, contents)| {
+                let path = path.join(name);
+                let metadata = match contents {
+                    Some(contents) => Metadata {
+                        is_file: true,
+                        size: contents.read().len() as u64,
+                        modified: None,
+                    },
+                    None => Metadata {
+                        is_file: false,
+                        size: trie.get_dir_size(&path).unwrap_or_default() as u64,
+                        modified: None,
+                    },
+                };
+                DirEntry::new(path, metadata)
+            })
+            .collect();
+        Ok(entries)
+    }
+}
+
+impl crate::File for File {
+    fn metadata(&self) -> std::io::Result<Metadata> {
+        Ok(Metadata {
+            is_file: true,
+            size: self.contents.read().len() as u64,
+            modified: None,
+        })
+    }
+
+    fn set_len(&self, new_size: u64) -> std::io::Result<()> {
+        if !self.write_allowed {
+            return Err(PermissionDenied.into());
+        }
+        let new_size = usize::try_from(new_size).map_err(|_| InvalidInput)?;
+        self.contents.write().resize(new_size, 0);
+        Ok(())
+    }
+}
+
+impl std::io::Read for File {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if !self.read_allowed {
+            return Err(PermissionDenied.into());
+        }
+        let contents = self.contents.read();
+        let start = usize::try_from(self.position)
+            .unwrap_or(usize::MAX)
+            .min(contents.len());
+        let count = buf.len().min(contents.len() - start);
+        buf[..count].copy_from_slice(&contents[start..start + count]);
+        self.position += count as u64;
+        Ok(count)
+    }
+}
+
+impl std::io::Write for File {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if !self.write_allowed {
+            return Err(PermissionDenied.into());
+        }
+        let mut contents = self.contents.write();
+        let start = usize::try_from(self.position).map_err(|_| InvalidInput)?;
+        let end = start.checked_add(buf.len()).ok_or(InvalidInput)?;
+        // Writing past the end of the file fills the gap with zeroes
+        if contents.len() < end {
+            contents.resize(end, 0);
+        }
+        contents[start..end].copy_from_slice(buf);
+        self.position = end as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl std::io::Seek for File {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let (base, offset) = match pos {
+            SeekFrom::Start(position) => {
+                self.position = position;
+                return Ok(position);
+            }
+            SeekFrom::End(offset) => (self.contents.read().len() as u64, offset),
+            SeekFrom::Current(offset) => (self.position, offset),
+        };
+        self.position = base.checked_add_signed(offset).ok_or(InvalidInput)?;
+        Ok(self.position)
+    }
+}
+
+impl futures_lite::AsyncRead for File {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Poll::Ready(std::io::Read::read(self.get_mut(), buf))
+    }
+}
+
+impl futures_lite::AsyncWrite for File {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Poll::Ready(std::io::Write::write(self.get_mut(), buf))
+    }
+
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(
+        self: Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl futures_lite::AsyncSeek for File {
+    fn poll_seek(
+        self: Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+        pos: SeekFrom,
+    ) -> Poll<std::io::Result<u64>> {
+        Poll::Ready(std::io::Seek::seek(self.get_mut(), pos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{File as _, FileSystem as _};
+    use std::io::{Read, Seek, Write};
+
+    #[test]
+    fn write_and_read_back() {
+        let fs = FileSystem::new();
+        fs.create_dir("Data/Maps").unwrap();
+        fs.write("Data/Actors.rxdata", b"actors").unwrap();
+
+        assert_eq!(fs.read("Data/Actors.rxdata").unwrap(), b"actors");
+        assert!(fs.exists("Data/Maps").unwrap());
+        assert!(matches!(
+            fs.write("Missing/Actors.rxdata", b""),
+            Err(Error::NotExist)
+        ));
+
+        let mut entries: Vec<_> = fs
+            .read_dir("Data")
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.path, entry.metadata.is_file))
+            .collect();
+        entries.sort();
+        assert_eq!(
+            entries,
+            [
+                ("Data/Actors.rxdata".into(), true),
+                ("Data/Maps".into(), false)
+            ]
+        );
+    }
+
+    #[test]
+    fn seek_and_set_len() {
+        let fs = FileSystem::new();
+        let mut file = fs
+            .open_file(
+                "file",
+                OpenFlags::Create | OpenFlags::Read | OpenFlags::Write,
+            )
+            .unwrap();
+
+        file.seek(SeekFrom::Start(2)).unwrap();
+        file.write_all(b"ab").unwrap();
+        assert_eq!(fs.read("file").unwrap(), b"\0\0ab");
+
+        // The cursor stays where it is when truncating
+        file.set_len(1).unwrap();
+        assert_eq!(file.stream_position().unwrap(), 4);
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf).unwrap();
+        assert!(buf.is_empty());
+
+        assert_eq!(file.seek(SeekFrom::End(-1)).unwrap(), 0);
+        assert!(file.seek(SeekFrom::Current(-1)).is_err());
+    }
+
+    #[test]
+    fn permissions() {
+        let fs = FileSystem::new();
+        fs.write("file", b"contents").unwrap();
+
+        let mut file = fs.open_file("file", OpenFlags::Read).unwrap();
+        assert!(file.write_all(b"x").is_err());
+        assert!(file.set_len(0).is_err());
+
+        let mut file = fs.open_file("file", OpenFlags::Write).unwrap();
+        assert!(file.read(&mut [0; 4]).is_err());
+    }
+
+    #[test]
+    fn rename_and_remove_directories() {
+        let fs = FileSystem::new();
+        fs.create_dir("Graphics/Characters/Empty").unwrap();
+        fs.write("Graphics/Characters/hero.png", b"png").unwrap();
+
+        fs.rename("Graphics", "Art").unwrap();
+        assert!(!fs.exists("Graphics").unwrap());
+        assert!(fs.exists("Art/Characters/Empty").unwrap());
+        assert_eq!(fs.read("Art/Characters/hero.png").unwrap(), b"png");
+
+        let mut file = fs
+            .open_file("Art/Characters/hero.png", OpenFlags::Read)
+            .unwrap();
+        fs.remove_dir("Art").unwrap();
+        assert!(!fs.exists("Art/Characters/hero.png").unwrap());
+
+        // Open files can still be read after they're removed
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, b"png");
+    }
+
+    #[test]
+    fn create_archive() {
+        let fs = FileSystem::new();
+        fs.create_dir("Data").unwrap();
+        fs.write("Data/Actors.rxdata", b"actors").unwrap();
+        fs.write("Data/System.rxdata", b"system").unwrap();
+
+        let paths = ["Data/Actors.rxdata", "Data/System.rxdata"];
+        let buffer = fs
+            .open_file(
+                "Game.rgssad",
+                OpenFlags::Create | OpenFlags::Read | OpenFlags::Write,
+            )
+            .unwrap();
+        let archive =
+            futures_lite::future::block_on(crate::archiver::FileSystem::from_buffer_and_files(
+                buffer,
+                1,
+                paths.iter().map(|path| {
+                    let file = fs.open_file(path, OpenFlags::Read)?;
+                    Ok((path, file.metadata()?.size as u32, file))
+                }),
+            ))
+            .unwrap();
+
+        assert_eq!(archive.read("Data/System.rxdata").unwrap(), b"system");
+
+        // Opening the archive again from what was written to the in-memory file gives the same contents
+        let buffer = fs
+            .open_file("Game.rgssad", OpenFlags::Read | OpenFlags::Write)
+            .unwrap();
+        let archive = crate::archiver::FileSystem::new(buffer).unwrap();
+        assert_eq!(archive.read("Data/Actors.rxdata").unwrap(), b"actors");
+    }
+}
@@ -113,6 +113,7 @@ impl crate::FileSystem for FileSystem {
         Ok(Metadata {
             is_file: metadata.is_file(),
             size: metadata.len(),
+            modified: metadata.modified().ok(),
         })
     }
 
@@ -166,6 +167,10 @@ impl crate::FileSystem for FileSystem {
             .flatten()
             .try_collect()
     }
+
+    fn watch(&self) -> Result<crate::watch::Watcher> {
+        crate::watch::Watcher::notify(&self.root_path)
+    }
 }
 
 impl File {
@@ -259,6 +264,7 @@ impl crate::File for File {
         Ok(Metadata {
             is_file: metdata.is_file(),
             size: metdata.len(),
+            modified: metdata.modified().ok(),
         })
     }
 
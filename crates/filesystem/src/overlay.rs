@@ -15,7 +15,6 @@
 // You should have received a copy of the GNU General Public License
 // along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
 use super::{DirEntry, Error, FileSystem, Metadata, OpenFlags};
-use itertools::Itertools;
 use std::io::prelude::*;
 
 #[derive(Debug, Clone)]
@@ -194,16 +193,14 @@ where
     fn read_dir(&self, path: impl AsRef<camino::Utf8Path>) -> Result<Vec<DirEntry>, Error> {
         let path = path.as_ref();
 
-        let mut entries = vec![]; // FIXME: inefficient
+        let mut listings = Vec::with_capacity(2);
         if self.primary.exists(path)? {
-            entries.extend(self.primary.read_dir(path)?);
+            listings.push(self.primary.read_dir(path)?);
         }
         if self.secondary.exists(path)? {
-            entries.extend(self.secondary.read_dir(path)?);
+            listings.push(self.secondary.read_dir(path)?);
         }
-        // FIXME: remove duplicates in a more efficient manner
-        let entries = entries.into_iter().unique().collect_vec();
 
-        Ok(entries)
+        Ok(crate::list::merge_dir_entries(listings))
     }
 }
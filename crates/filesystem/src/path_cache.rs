@@ -40,43 +40,103 @@ where
         &self.fs
     }
 
+    /// The cache isn't updated for changes made through this, so call [`Self::regen_cache`]
+    /// afterwards.
+    pub fn fs_mut(&mut self) -> &mut F {
+        &mut self.fs
+    }
+
+    /// Rebuilds the whole cache by walking every directory in the filesystem. The cache is kept
+    /// up to date for changes made through this filesystem, so this is only needed if the
+    /// filesystem was changed some other way.
     pub fn regen_cache(&self) -> Result<()> {
-        fn read_dir_recursive(
-            fs: &(impl crate::FileSystem + ?Sized),
-            path: impl AsRef<camino::Utf8Path>,
-            mut f: impl FnMut(&camino::Utf8Path),
-        ) -> Result<()> {
-            fn internal(
-                fs: &(impl crate::FileSystem + ?Sized),
-                path: impl AsRef<camino::Utf8Path>,
-                f: &mut impl FnMut(&camino::Utf8Path),
-            ) -> Result<()> {
-                // In web builds, RTPs are currently to be placed in the "RTP" subdirectory of
-                // the project root directory, so this is to avoid loading the contents of
-                // those directories twice
-                let skip = matches!(path.as_ref().iter().next_back(), Some("RTP"));
-
-                for entry in fs.read_dir(path)? {
-                    f(entry.path());
-                    if !skip && !entry.metadata().is_file {
-                        internal(fs, entry.path(), f)?;
-                    }
-                }
-                Ok(())
+        self.cache.clear();
+        self.cache_dir(camino::Utf8Path::new(""))
+    }
+
+    /// Adds everything inside the directory at `path` to the cache.
+    fn cache_dir(&self, path: &camino::Utf8Path) -> Result<()> {
+        // In web builds, RTPs are currently to be placed in the "RTP" subdirectory of
+        // the project root directory, so this is to avoid loading the contents of
+        // those directories twice
+        let skip = matches!(path.iter().next_back(), Some("RTP"));
+
+        for entry in self.fs.read_dir(path)? {
+            // Filesystems earlier in the list take priority when several have the same path
+            self.cache
+                .entry(cache_key(entry.path()))
+                .or_insert_with(|| entry.path().to_path_buf());
+            if !skip && !entry.metadata().is_file {
+                self.cache_dir(entry.path())?;
             }
-            internal(fs, path, &mut f)
         }
+        Ok(())
+    }
+
+    /// Adds the file or directory at `path` to the cache, along with everything inside it if it's
+    /// a directory.
+    fn cache_path(&self, path: &camino::Utf8Path, is_file: bool) -> Result<()> {
+        self.cache.insert(cache_key(path), path.to_path_buf());
+        if !is_file {
+            self.cache_dir(path)?;
+        }
+        Ok(())
+    }
 
-        self.cache.clear();
-        read_dir_recursive(&self.fs, "", |path| {
-            let mut lowercase = to_lowercase(path);
-            lowercase.set_extension("");
+    /// Removes the given paths from the cache, and everything inside them too if
+    /// `include_descendants` is set. Anything further down the list of filesystems that was being
+    /// shadowed by a removed path is cached in its place.
+    fn uncache_paths(
+        &self,
+        paths: &[camino::Utf8PathBuf],
+        include_descendants: bool,
+    ) -> Result<()> {
+        if paths.is_empty() {
+            return Ok(());
+        }
 
-            self.cache.insert(lowercase, path.to_path_buf());
-        })?;
+        for path in paths {
+            self.cache.remove(&cache_key(path));
+        }
+        if include_descendants {
+            let removed: std::collections::HashSet<_> = paths.iter().map(to_lowercase).collect();
+            self.cache.retain(|key, _| {
+                !key.ancestors()
+                    .skip(1)
+                    .any(|ancestor| removed.contains(ancestor))
+            });
+        }
+
+        for path in paths {
+            if let Some(entry) = self.find_shadowed(path)? {
+                self.cache_path(entry.path(), entry.metadata().is_file)?;
+            }
+        }
         Ok(())
     }
 
+    /// Finds an entry in the filesystem that has the same cache key as `path`, by listing only
+    /// the directory `path` is in.
+    fn find_shadowed(&self, path: &camino::Utf8Path) -> Result<Option<DirEntry>> {
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_str().is_empty() => match self.desensitize(parent) {
+                Some(parent) => parent,
+                None => return Ok(None),
+            },
+            _ => camino::Utf8PathBuf::new(),
+        };
+        if !self.fs.exists(&parent)? {
+            return Ok(None);
+        }
+
+        let key = cache_key(path);
+        Ok(self
+            .fs
+            .read_dir(&parent)?
+            .into_iter()
+            .find(|entry| cache_key(entry.path()) == key))
+    }
+
     pub fn debug_ui(&self, ui: &mut egui::Ui) {
         egui::ScrollArea::vertical()
             .id_source("luminol_path_cache_debug_ui")
@@ -101,10 +161,28 @@ where
             );
     }
 
+    /// Updates the cache for files that were created or removed outside of Luminol.
+    pub fn apply_changes(&self, changes: &[crate::watch::Change]) -> Result<()> {
+        let mut removed = Vec::new();
+        for change in changes {
+            match change {
+                crate::watch::Change::Created(path) => match self.fs.metadata(path) {
+                    Ok(metadata) => self.cache_path(path, metadata.is_file)?,
+                    // It was removed again before we got to it
+                    Err(Error::NotExist) => {}
+                    Err(e) => return Err(e),
+                },
+                crate::watch::Change::Removed(path) => removed.push(path.clone()),
+                crate::watch::Change::Modified(_) => {}
+            }
+        }
+
+        // We can't tell whether a removed path was a file or a directory anymore
+        self.uncache_paths(&removed, true)
+    }
+
     pub fn desensitize(&self, path: impl AsRef<camino::Utf8Path>) -> Option<camino::Utf8PathBuf> {
-        let mut path = to_lowercase(path);
-        path.set_extension("");
-        self.cache.get(&path).as_deref().cloned()
+        self.cache.get(&cache_key(path)).as_deref().cloned()
     }
 }
 
@@ -112,6 +190,13 @@ pub fn to_lowercase(p: impl AsRef<camino::Utf8Path>) -> camino::Utf8PathBuf {
     p.as_ref().as_str().to_lowercase().into()
 }
 
+/// The key a path is stored under in the cache: lowercase, without a file extension.
+fn cache_key(path: impl AsRef<camino::Utf8Path>) -> camino::Utf8PathBuf {
+    let mut key = to_lowercase(path);
+    key.set_extension("");
+    key
+}
+
 impl<F> crate::FileSystem for FileSystem<F>
 where
     F: crate::FileSystem,
@@ -124,13 +209,30 @@ where
         flags: OpenFlags,
     ) -> Result<Self::File> {
         let path = path.as_ref();
-        if flags.contains(OpenFlags::Create) {
-            let mut lower_path = to_lowercase(path);
-            lower_path.set_extension("");
-            self.cache.insert(lower_path, path.to_path_buf());
+        let existing = self.desensitize(path);
+        if !flags.contains(OpenFlags::Create) {
+            return self.fs.open_file(existing.ok_or(Error::NotExist)?, flags);
         }
-        let path = self.desensitize(path).ok_or(Error::NotExist)?;
-        self.fs.open_file(path, flags)
+
+        // The cache ignores extensions, so only reuse the cached path if it's for this exact file,
+        // and not e.g. `Hero.bmp` when creating `Hero.png`
+        let file_name = path.file_name().map(str::to_lowercase);
+        if let Some(existing) =
+            existing.filter(|existing| existing.file_name().map(str::to_lowercase) == file_name)
+        {
+            return self.fs.open_file(existing, flags);
+        }
+
+        // Create the file next to whatever the parent directory's path matches
+        let path = match (path.parent(), path.file_name()) {
+            (Some(parent), Some(file_name)) if !parent.as_str().is_empty() => self
+                .desensitize(parent)
+                .map_or_else(|| path.to_path_buf(), |parent| parent.join(file_name)),
+            _ => path.to_path_buf(),
+        };
+        let file = self.fs.open_file(&path, flags)?;
+        self.cache.insert(cache_key(&path), path);
+        Ok(file)
     }
 
     fn metadata(&self, path: impl AsRef<camino::Utf8Path>) -> Result<Metadata> {
@@ -144,12 +246,13 @@ where
         to: impl AsRef<camino::Utf8Path>,
     ) -> Result<()> {
         let from = self.desensitize(from).ok_or(Error::NotExist)?;
-        let to = to.as_ref().to_path_buf();
+        let to = to.as_ref();
+        let is_file = self.fs.metadata(&from)?.is_file;
 
-        self.fs.rename(&from, &to)?;
+        self.fs.rename(&from, to)?;
 
-        self.cache.remove(&from);
-        self.cache.insert(to_lowercase(&to), to);
+        self.uncache_paths(&[from], !is_file)?;
+        self.cache_path(to, is_file)?;
 
         Ok(())
     }
@@ -159,11 +262,18 @@ where
     }
 
     fn create_dir(&self, path: impl AsRef<camino::Utf8Path>) -> Result<()> {
-        let path = path.as_ref().to_path_buf();
+        let path = path.as_ref();
 
-        self.fs.create_dir(&path)?;
+        self.fs.create_dir(path)?;
 
-        self.cache.insert(to_lowercase(&path), path);
+        // Parent directories are created too if they don't exist
+        for ancestor in path.ancestors() {
+            if ancestor.as_str().is_empty() || self.desensitize(ancestor).is_some() {
+                break;
+            }
+            self.cache
+                .insert(cache_key(ancestor), ancestor.to_path_buf());
+        }
 
         Ok(())
     }
@@ -173,9 +283,7 @@ where
 
         self.fs.remove_dir(&path)?;
 
-        self.cache.remove(&to_lowercase(path));
-
-        Ok(())
+        self.uncache_paths(&[path], true)
     }
 
     fn remove_file(&self, path: impl AsRef<camino::Utf8Path>) -> Result<()> {
@@ -183,13 +291,116 @@ where
 
         self.fs.remove_file(&path)?;
 
-        self.cache.remove(&to_lowercase(path));
-
-        Ok(())
+        self.uncache_paths(&[path], false)
     }
 
     fn read_dir(&self, path: impl AsRef<camino::Utf8Path>) -> Result<Vec<DirEntry>> {
         let path = self.desensitize(path).ok_or(Error::NotExist)?;
         self.fs.read_dir(path)
     }
+
+    fn watch(&self) -> Result<crate::watch::Watcher> {
+        self.fs.watch()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{memory, FileSystem as _};
+
+    #[test]
+    fn incremental_updates() {
+        let project = memory::FileSystem::new();
+        let rtp = memory::FileSystem::new();
+        project.create_dir("Graphics/Characters").unwrap();
+        project.write("Graphics/Characters/Hero.png", b"").unwrap();
+        rtp.create_dir("Graphics/Characters").unwrap();
+        rtp.write("Graphics/Characters/hero.png", b"").unwrap();
+
+        let mut list = crate::list::FileSystem::new();
+        list.push(project.clone());
+        list.push(rtp);
+        let path_cache = super::FileSystem::new(list).unwrap();
+
+        path_cache.create_dir("Graphics/Fogs/Night").unwrap();
+        path_cache
+            .write("graphics/fogs/night/Stars.png", b"")
+            .unwrap();
+        assert_eq!(
+            path_cache.desensitize("GRAPHICS/FOGS/NIGHT/STARS"),
+            Some("Graphics/Fogs/Night/Stars.png".into())
+        );
+
+        path_cache.rename("graphics/fogs", "Graphics/Mist").unwrap();
+        assert!(!path_cache.exists("graphics/fogs/night/stars").unwrap());
+        assert!(path_cache.exists("graphics/mist/night/stars").unwrap());
+
+        path_cache.remove_dir("graphics/mist").unwrap();
+        assert!(!path_cache.exists("graphics/mist/night").unwrap());
+
+        // Removing the project's file uncovers the RTP's one with the same name
+        path_cache.remove_file("graphics/characters/hero").unwrap();
+        assert_eq!(
+            path_cache.desensitize("graphics/characters/hero"),
+            Some("Graphics/Characters/hero.png".into())
+        );
+    }
+
+    #[test]
+    fn inserting_filesystems() {
+        let project = memory::FileSystem::new();
+        let archive = memory::FileSystem::new();
+        let rtp = memory::FileSystem::new();
+        project.create_dir("Graphics/Titles").unwrap();
+        archive.create_dir("Graphics/Titles").unwrap();
+        archive.write("Graphics/Titles/Title.png", b"").unwrap();
+        rtp.create_dir("Graphics/Titles").unwrap();
+        rtp.write("Graphics/Titles/title.png", b"").unwrap();
+        rtp.write("Graphics/Titles/Intro.png", b"").unwrap();
+
+        let mut list = crate::list::FileSystem::new();
+        list.push(project);
+        list.push(archive);
+        let mut path_cache = super::FileSystem::new(list).unwrap();
+        assert_eq!(path_cache.desensitize("graphics/titles/intro"), None);
+
+        path_cache.fs_mut().insert(1, rtp);
+        path_cache.regen_cache().unwrap();
+        let title = path_cache.desensitize("graphics/titles/title").unwrap();
+        assert_eq!(title, "Graphics/Titles/title.png");
+        assert_eq!(path_cache.fs().find(title).unwrap(), Some(1));
+        let intro = path_cache.desensitize("graphics/titles/intro").unwrap();
+        assert_eq!(path_cache.fs().find(intro).unwrap(), Some(1));
+    }
+
+    #[test]
+    fn creating_a_file_with_another_extension() {
+        let project = memory::FileSystem::new();
+        project.create_dir("Graphics/Characters").unwrap();
+        project
+            .write("Graphics/Characters/Hero.bmp", b"bitmap")
+            .unwrap();
+        let path_cache = super::FileSystem::new(project.clone()).unwrap();
+
+        path_cache
+            .write("graphics/characters/Hero.png", b"png")
+            .unwrap();
+        assert_eq!(
+            project.read("Graphics/Characters/Hero.bmp").unwrap(),
+            b"bitmap"
+        );
+        assert_eq!(
+            project.read("Graphics/Characters/Hero.png").unwrap(),
+            b"png"
+        );
+
+        // Writing the same file again, in different case, reuses it
+        path_cache
+            .write("GRAPHICS/CHARACTERS/HERO.PNG", b"again")
+            .unwrap();
+        assert_eq!(
+            project.read("Graphics/Characters/Hero.png").unwrap(),
+            b"again"
+        );
+    }
 }
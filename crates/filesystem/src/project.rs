@@ -15,11 +15,8 @@
 // You should have received a copy of the GNU General Public License
 // along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
 
-#[cfg(target_arch = "wasm32")]
-use itertools::Itertools;
-
 use crate::FileSystem as _;
-use crate::{archiver, host, list, path_cache};
+use crate::{archiver, host, list, path_cache, zip};
 use crate::{DirEntry, Error, Metadata, OpenFlags, Result};
 
 #[derive(Default)]
@@ -31,9 +28,35 @@ pub enum FileSystem {
         filesystem: path_cache::FileSystem<list::FileSystem>,
         host_filesystem: host::FileSystem,
         project_path: camino::Utf8PathBuf,
+        /// Where each filesystem in the list comes from, in the same order.
+        sources: Vec<Source>,
     },
 }
 
+/// Where the files in one of the filesystems of a loaded project come from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// The project's own folder.
+    Project,
+    /// An RTP, either a folder or a zip archive.
+    Rtp {
+        name: String,
+        path: camino::Utf8PathBuf,
+    },
+    /// The project's encrypted archive.
+    Archive(camino::Utf8PathBuf),
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Source::Project => write!(f, "Project"),
+            Source::Rtp { name, .. } => write!(f, "RTP {name}"),
+            Source::Archive(path) => write!(f, "Archive {path}"),
+        }
+    }
+}
+
 pub enum File {
     Host(<host::FileSystem as crate::FileSystem>::File),
     Loaded(<path_cache::FileSystem<list::FileSystem> as crate::FileSystem>::File),
@@ -64,6 +87,87 @@ impl FileSystem {
     pub fn unload_project(&mut self) {
         *self = FileSystem::Unloaded;
     }
+
+    /// Where the files of the loaded project come from, in the order they are looked up in.
+    pub fn sources(&self) -> &[Source] {
+        match self {
+            FileSystem::Loaded { sources, .. } => sources,
+            _ => &[],
+        }
+    }
+
+    /// The actual path of the file or directory at `path`, found ignoring case and file
+    /// extensions like when opening it.
+    pub fn desensitize(
+        &self,
+        path: impl AsRef<camino::Utf8Path>,
+    ) -> Result<Option<camino::Utf8PathBuf>> {
+        let path = path.as_ref();
+        match self {
+            FileSystem::Unloaded => Err(Error::NotLoaded),
+            FileSystem::HostLoaded(host) => Ok(host.exists(path)?.then(|| path.to_path_buf())),
+            FileSystem::Loaded { filesystem, .. } => Ok(filesystem.desensitize(path)),
+        }
+    }
+
+    /// Rebuilds the path cache, after files were changed without going through this filesystem.
+    pub fn regen_cache(&self) -> Result<()> {
+        match self {
+            FileSystem::Unloaded => Err(Error::NotLoaded),
+            FileSystem::HostLoaded(_) => Ok(()),
+            FileSystem::Loaded { filesystem, .. } => filesystem.regen_cache(),
+        }
+    }
+
+    /// Updates the path cache for files that were created or removed without going through this
+    /// filesystem. Cheaper than [`Self::regen_cache`] when the changed paths are known.
+    pub fn apply_changes(&self, changes: &[crate::watch::Change]) -> Result<()> {
+        match self {
+            FileSystem::Unloaded => Err(Error::NotLoaded),
+            FileSystem::HostLoaded(_) => Ok(()),
+            FileSystem::Loaded { filesystem, .. } => filesystem.apply_changes(changes),
+        }
+    }
+
+    /// Which filesystem the file or directory at `path` is read from. This is the first one in
+    /// [`Self::sources`] that has it; the same path in later ones is shadowed.
+    pub fn source_of(&self, path: impl AsRef<camino::Utf8Path>) -> Result<Option<Source>> {
+        match self {
+            FileSystem::Unloaded => Err(Error::NotLoaded),
+            FileSystem::HostLoaded(host) => Ok(host.exists(path)?.then_some(Source::Project)),
+            FileSystem::Loaded {
+                filesystem,
+                sources,
+                ..
+            } => {
+                let Some(path) = filesystem.desensitize(path) else {
+                    return Ok(None);
+                };
+                Ok(filesystem
+                    .fs()
+                    .find(path)?
+                    .and_then(|index| sources.get(index))
+                    .cloned())
+            }
+        }
+    }
+}
+
+/// The names of the RTPs the project uses, from `RTP1` to `RTP3` in its `Game.ini`.
+pub fn rtp_names(config: &luminol_config::project::Config) -> Vec<String> {
+    let Some(section) = config.game_ini.section(Some("Game")) else {
+        return vec![];
+    };
+    let mut names: Vec<String> = vec![];
+    // FIXME: handle vx ace?
+    for rtp in ["RTP1", "RTP2", "RTP3"] {
+        if let Some(rtp) = section.get(rtp) {
+            if !rtp.is_empty() && !names.iter().any(|name| name == rtp) {
+                names.push(rtp.to_string());
+            }
+        }
+    }
+    names
 }
 
 // Not platform specific
@@ -124,9 +228,12 @@ impl FileSystem {
         let command_db = match self
             .read_to_string(".luminol/commands")
             .ok()
-            .and_then(|s| ron::from_str(&s).ok())
+            .and_then(|s| ron::from_str::<luminol_config::command_db::CommandDB>(&s).ok())
         {
-            Some(c) => c,
+            Some(mut c) => {
+                c.load_defaults(project.editor_ver);
+                c
+            }
             None => {
                 let command_db = luminol_config::command_db::CommandDB::new(project.editor_ver);
                 self.write(".luminol/commands", ron::to_string(&command_db).unwrap())?;
@@ -200,6 +307,80 @@ impl FileSystem {
         Ok(result)
     }
 
+    /// Collects the changes made to the project directory since the last call and updates the
+    /// path cache for them. `watcher` should have been created by [`crate::FileSystem::watch`].
+    pub fn collect_changes(
+        &self,
+        watcher: &mut crate::watch::Watcher,
+    ) -> Result<Vec<crate::watch::Change>> {
+        match self {
+            FileSystem::Unloaded => Err(Error::NotLoaded),
+            FileSystem::HostLoaded(host) => watcher.changes(host),
+            FileSystem::Loaded {
+                filesystem,
+                host_filesystem,
+                ..
+            } => {
+                let changes = watcher.changes(host_filesystem)?;
+                filesystem.apply_changes(&changes)?;
+                Ok(changes)
+            }
+        }
+    }
+
+    /// Finds and mounts the project's RTPs that weren't found when it was loaded, e.g. after
+    /// their paths were set in the global config.
+    #[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+    pub fn mount_missing_rtps(
+        &mut self,
+        project_config: &luminol_config::project::Config,
+        global_config: &luminol_config::global::Config,
+    ) -> Result<LoadResult> {
+        let FileSystem::Loaded {
+            filesystem,
+            #[cfg(target_arch = "wasm32")]
+            host_filesystem,
+            sources,
+            ..
+        } = self
+        else {
+            return Err(Error::NotLoaded);
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let (found_rtps, missing_rtps) = Self::find_rtp_paths(project_config, global_config);
+        #[cfg(target_arch = "wasm32")]
+        let (found_rtps, missing_rtps) = Self::find_rtp_paths(host_filesystem, project_config);
+
+        // RTPs go after the project and before its archive
+        let mut index = sources
+            .iter()
+            .position(|source| matches!(source, Source::Archive(_)))
+            .unwrap_or(sources.len());
+        let mut mounted_any = false;
+        for (name, path) in found_rtps {
+            let mounted = sources
+                .iter()
+                .any(|source| matches!(source, Source::Rtp { name: n, .. } if *n == name));
+            if mounted {
+                continue;
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::insert_rtp(filesystem.fs_mut(), index, &path)?;
+            #[cfg(target_arch = "wasm32")]
+            Self::insert_rtp(filesystem.fs_mut(), index, host_filesystem, &path)?;
+            sources.insert(index, Source::Rtp { name, path });
+            index += 1;
+            mounted_any = true;
+        }
+
+        if mounted_any {
+            filesystem.regen_cache()?;
+        }
+        Ok(LoadResult { missing_rtps })
+    }
+
     pub fn host(&self) -> Option<host::FileSystem> {
         match self {
             FileSystem::Unloaded => None,
@@ -217,67 +398,56 @@ impl FileSystem {
     fn find_rtp_paths(
         config: &luminol_config::project::Config,
         global_config: &luminol_config::global::Config,
-    ) -> (Vec<camino::Utf8PathBuf>, Vec<String>) {
-        let Some(section) = config.game_ini.section(Some("Game")) else {
-            return (vec![], vec![]);
-        };
+    ) -> (Vec<(String, camino::Utf8PathBuf)>, Vec<String>) {
         let mut paths = vec![];
-        let mut seen_rtps = vec![];
         let mut missing_rtps = vec![];
-        // FIXME: handle vx ace?
-        for rtp in ["RTP1", "RTP2", "RTP3"] {
-            if let Some(rtp) = section.get(rtp) {
-                if seen_rtps.contains(&rtp) || rtp.is_empty() {
+        for rtp in rtp_names(config) {
+            // RTPs set up in Luminol take priority over installed ones
+            if let Some(path) = global_config.rtp_paths.get(&rtp) {
+                let path = camino::Utf8PathBuf::from(path);
+                if path.exists() {
+                    paths.push((rtp, path));
                     continue;
                 }
-                seen_rtps.push(rtp);
-
-                let hklm = winreg::RegKey::predef(winreg::enums::HKEY_LOCAL_MACHINE);
-                if let Ok(value) = hklm
-                    .open_subkey("SOFTWARE\\WOW6432Node\\Enterbrain\\RGSS\\RTP")
-                    .and_then(|key| key.get_value::<String, _>(rtp))
-                {
-                    let path = camino::Utf8PathBuf::from(value);
-                    if path.exists() {
-                        paths.push(path);
-                        continue;
-                    }
-                }
+            }
 
-                if let Ok(value) = hklm
-                    .open_subkey("SOFTWARE\\WOW6432Node\\Enterbrain\\RPGXP")
-                    .and_then(|key| key.get_value::<String, _>("ApplicationPath"))
-                {
-                    let path = camino::Utf8PathBuf::from(value).join("rtp");
-                    if path.exists() {
-                        paths.push(path);
-                        continue;
-                    }
+            let hklm = winreg::RegKey::predef(winreg::enums::HKEY_LOCAL_MACHINE);
+            if let Ok(value) = hklm
+                .open_subkey("SOFTWARE\\WOW6432Node\\Enterbrain\\RGSS\\RTP")
+                .and_then(|key| key.get_value::<String, _>(&rtp))
+            {
+                let path = camino::Utf8PathBuf::from(value);
+                if path.exists() {
+                    paths.push((rtp, path));
+                    continue;
                 }
+            }
 
-                if let Ok(value) = hklm
-                    .open_subkey(
-                        "SOFTWARE\\Microsoft\\Windows\\CurrentVersion\\Uninstall\\Steam App 235900",
-                    )
-                    .and_then(|key| key.get_value::<String, _>("InstallLocation"))
-                {
-                    let path = camino::Utf8PathBuf::from(value).join("rtp");
-                    if path.exists() {
-                        paths.push(path);
-                        continue;
-                    }
+            if let Ok(value) = hklm
+                .open_subkey("SOFTWARE\\WOW6432Node\\Enterbrain\\RPGXP")
+                .and_then(|key| key.get_value::<String, _>("ApplicationPath"))
+            {
+                let path = camino::Utf8PathBuf::from(value).join("rtp");
+                if path.exists() {
+                    paths.push((rtp, path));
+                    continue;
                 }
+            }
 
-                if let Some(path) = global_config.rtp_paths.get(rtp) {
-                    let path = camino::Utf8PathBuf::from(path);
-                    if path.exists() {
-                        paths.push(path);
-                        continue;
-                    }
+            if let Ok(value) = hklm
+                .open_subkey(
+                    "SOFTWARE\\Microsoft\\Windows\\CurrentVersion\\Uninstall\\Steam App 235900",
+                )
+                .and_then(|key| key.get_value::<String, _>("InstallLocation"))
+            {
+                let path = camino::Utf8PathBuf::from(value).join("rtp");
+                if path.exists() {
+                    paths.push((rtp, path));
+                    continue;
                 }
-
-                missing_rtps.push(rtp.to_string());
             }
+
+            missing_rtps.push(rtp);
         }
         (paths, missing_rtps)
     }
@@ -289,31 +459,19 @@ impl FileSystem {
     fn find_rtp_paths(
         config: &luminol_config::project::Config,
         global_config: &luminol_config::global::Config,
-    ) -> (Vec<camino::Utf8PathBuf>, Vec<String>) {
-        let Some(section) = config.game_ini.section(Some("Game")) else {
-            return (vec![], vec![]);
-        };
+    ) -> (Vec<(String, camino::Utf8PathBuf)>, Vec<String>) {
         let mut paths = vec![];
-        let mut seen_rtps = vec![];
         let mut missing_rtps = vec![];
-        // FIXME: handle vx ace?
-        for rtp in ["RTP1", "RTP2", "RTP3"] {
-            if let Some(rtp) = section.get(rtp) {
-                if seen_rtps.contains(&rtp) || rtp.is_empty() {
+        for rtp in rtp_names(config) {
+            if let Some(path) = global_config.rtp_paths.get(&rtp) {
+                let path = camino::Utf8PathBuf::from(path);
+                if path.exists() {
+                    paths.push((rtp, path));
                     continue;
                 }
-                seen_rtps.push(rtp);
-
-                if let Some(path) = global_config.rtp_paths.get(rtp) {
-                    let path = camino::Utf8PathBuf::from(path);
-                    if path.exists() {
-                        paths.push(path);
-                        continue;
-                    }
-                }
-
-                missing_rtps.push(rtp.to_string());
             }
+
+            missing_rtps.push(rtp);
         }
         (paths, missing_rtps)
     }
@@ -350,19 +508,22 @@ impl FileSystem {
                 entry.metadata.is_file
                     && matches!(entry.path.extension(), Some("rgssad" | "rgss2a" | "rgss3a"))
             })
-            .map(|entry| host.open_file(entry.path, OpenFlags::Read | OpenFlags::Write))
-            .transpose()?
-            .map(archiver::FileSystem::new)
+            .map(|entry| {
+                let file = host.open_file(&entry.path, OpenFlags::Read | OpenFlags::Write)?;
+                Ok::<_, Error>((entry.path, archiver::FileSystem::new(file)?))
+            })
             .transpose()?;
 
         list.push(host);
-        // FIXME: handle missing rtps
+        let mut sources = vec![Source::Project];
         let (found_rtps, missing_rtps) = Self::find_rtp_paths(project_config, global_config);
-        for path in found_rtps {
-            list.push(host::FileSystem::new(path))
+        for (name, path) in found_rtps {
+            Self::insert_rtp(&mut list, list.len(), &path)?;
+            sources.push(Source::Rtp { name, path });
         }
-        if let Some(archive) = archive {
+        if let Some((path, archive)) = archive {
             list.push(archive);
+            sources.push(Source::Archive(path));
         }
 
         let path_cache = path_cache::FileSystem::new(list)?;
@@ -371,6 +532,7 @@ impl FileSystem {
             filesystem: path_cache,
             host_filesystem: host_clone,
             project_path: project_path.to_path_buf(),
+            sources,
         };
 
         // FIXME: handle
@@ -390,6 +552,22 @@ impl FileSystem {
 
         Ok(LoadResult { missing_rtps })
     }
+
+    /// Adds the RTP at `path`, which is either a folder or a zip archive, to `list` at `index`.
+    fn insert_rtp(
+        list: &mut list::FileSystem,
+        index: usize,
+        path: &camino::Utf8Path,
+    ) -> Result<()> {
+        if path.is_file() && path.extension() == Some("zip") {
+            let parent = host::FileSystem::new(path.parent().unwrap_or(camino::Utf8Path::new("")));
+            let file = parent.open_file(path.file_name().unwrap_or_default(), OpenFlags::Read)?;
+            list.insert(index, zip::FileSystem::new(file)?);
+        } else {
+            list.insert(index, host::FileSystem::new(path));
+        }
+        Ok(())
+    }
 }
 
 // Specific to web
@@ -398,35 +576,50 @@ impl FileSystem {
     fn find_rtp_paths(
         filesystem: &host::FileSystem,
         config: &luminol_config::project::Config,
-    ) -> (Vec<camino::Utf8PathBuf>, Vec<String>) {
-        let Some(section) = config.game_ini.section(Some("Game")) else {
-            return (vec![], vec![]);
-        };
+    ) -> (Vec<(String, camino::Utf8PathBuf)>, Vec<String>) {
         let mut paths = vec![];
-        let mut seen_rtps = vec![];
         let mut missing_rtps = vec![];
-        // FIXME: handle vx ace?
-        for rtp in ["RTP1", "RTP2", "RTP3"] {
-            if let Some(rtp) = section.get(rtp) {
-                if seen_rtps.contains(&rtp) || rtp.is_empty() {
+        for rtp in rtp_names(config) {
+            let path = camino::Utf8PathBuf::from("RTP").join(&rtp);
+            if let Ok(exists) = filesystem.exists(&path) {
+                if exists {
+                    paths.push((rtp, path));
                     continue;
                 }
-                seen_rtps.push(rtp);
-
-                let path = camino::Utf8PathBuf::from("RTP").join(rtp);
-                if let Ok(exists) = filesystem.exists(&path) {
-                    if exists {
-                        paths.push(path);
-                        continue;
-                    }
-                }
+            }
 
-                missing_rtps.push(rtp.to_string());
+            let path = camino::Utf8PathBuf::from("RTP").join(format!("{rtp}.zip"));
+            if let Ok(exists) = filesystem.exists(&path) {
+                if exists {
+                    paths.push((rtp, path));
+                    continue;
+                }
             }
+
+            missing_rtps.push(rtp);
         }
         (paths, missing_rtps)
     }
 
+    /// Adds the RTP at `path` in the project folder, which is either a folder or a zip archive,
+    /// to `list` at `index`.
+    fn insert_rtp(
+        list: &mut list::FileSystem,
+        index: usize,
+        host: &host::FileSystem,
+        path: &camino::Utf8Path,
+    ) -> Result<()> {
+        if path.extension() == Some("zip") {
+            list.insert(
+                index,
+                zip::FileSystem::new(host.open_file(path, OpenFlags::Read)?)?,
+            );
+        } else {
+            list.insert(index, host.subdir(path)?);
+        }
+        Ok(())
+    }
+
     #[cfg(target_arch = "wasm32")]
     pub fn load_partially_loaded_project(
         &mut self,
@@ -458,10 +651,6 @@ impl FileSystem {
         let mut list = list::FileSystem::new();
 
         let (found_rtps, missing_rtps) = Self::find_rtp_paths(&host, project_config);
-        let rtp_filesystems: Vec<_> = found_rtps
-            .into_iter()
-            .map(|rtp| host.subdir(rtp))
-            .try_collect()?;
 
         let archive = host
             .read_dir("")?
@@ -470,17 +659,21 @@ impl FileSystem {
                 entry.metadata.is_file
                     && matches!(entry.path.extension(), Some("rgssad" | "rgss2a" | "rgss3a"))
             })
-            .map(|entry| host.open_file(entry.path, OpenFlags::Read | OpenFlags::Write))
-            .transpose()?
-            .map(archiver::FileSystem::new)
+            .map(|entry| {
+                let file = host.open_file(&entry.path, OpenFlags::Read | OpenFlags::Write)?;
+                Ok::<_, Error>((entry.path, archiver::FileSystem::new(file)?))
+            })
             .transpose()?;
 
         list.push(host.clone());
-        for filesystem in rtp_filesystems {
-            list.push(filesystem)
+        let mut sources = vec![Source::Project];
+        for (name, path) in found_rtps {
+            Self::insert_rtp(&mut list, list.len(), &host, &path)?;
+            sources.push(Source::Rtp { name, path });
         }
-        if let Some(archive) = archive {
+        if let Some((path, archive)) = archive {
             list.push(archive);
+            sources.push(Source::Archive(path));
         }
 
         let path_cache = path_cache::FileSystem::new(list)?;
@@ -489,6 +682,7 @@ impl FileSystem {
             filesystem: path_cache,
             host_filesystem: host.clone(),
             project_path: root_path.clone(),
+            sources,
         };
 
         if let Ok(idb_key) = host.save_to_idb() {
@@ -658,4 +852,15 @@ impl crate::FileSystem for FileSystem {
             FileSystem::Loaded { filesystem, .. } => filesystem.read_dir(path),
         }
     }
+
+    /// Only the project directory is watched, not the RTPs or the project's archive.
+    fn watch(&self) -> Result<crate::watch::Watcher> {
+        match self {
+            FileSystem::Unloaded => Err(Error::NotLoaded),
+            FileSystem::HostLoaded(f) => f.watch(),
+            FileSystem::Loaded {
+                host_filesystem, ..
+            } => host_filesystem.watch(),
+        }
+    }
 }
@@ -345,6 +345,69 @@ impl<T> FileSystemTrie<T> {
         }
     }
 
+    /// Moves the file or directory at `from`, along with everything inside it, to `to`. If parent
+    /// directories of `to` do not exist, they will be created. Returns whether or not `from`
+    /// existed.
+    pub fn rename(
+        &mut self,
+        from: impl AsRef<camino::Utf8Path>,
+        to: impl AsRef<camino::Utf8Path>,
+    ) -> bool {
+        let from = from.as_ref();
+        let to = to.as_ref();
+
+        if let Some(value) = self.remove_file(from) {
+            self.create_file(to, value);
+            return true;
+        }
+        if !self.contains_dir(from) {
+            return false;
+        }
+
+        let mut dirs = Vec::new();
+        let mut file_paths = Vec::new();
+        self.collect_descendants(from, &mut dirs, &mut file_paths);
+        let files: Vec<_> = file_paths
+            .into_iter()
+            .filter_map(|path| self.remove_file(&path).map(|value| (path, value)))
+            .collect();
+
+        self.remove_dir(from);
+        self.create_dir(to);
+        for dir in dirs {
+            self.create_dir(to.join(dir.strip_prefix(from).unwrap()));
+        }
+        for (path, value) in files {
+            self.create_file(to.join(path.strip_prefix(from).unwrap()), value);
+        }
+        true
+    }
+
+    /// Collects the paths of every directory and file inside the directory at `path`, at any
+    /// depth.
+    fn collect_descendants(
+        &self,
+        path: &camino::Utf8Path,
+        dirs: &mut Vec<camino::Utf8PathBuf>,
+        files: &mut Vec<camino::Utf8PathBuf>,
+    ) {
+        let Some(iter) = self.iter_dir(path) else {
+            return;
+        };
+        let mut subdirs = Vec::new();
+        for (name, value) in iter {
+            if value.is_some() {
+                files.push(path.join(name));
+            } else {
+                subdirs.push(path.join(name));
+            }
+        }
+        for subdir in subdirs {
+            self.collect_descendants(&subdir, dirs, files);
+            dirs.push(subdir);
+        }
+    }
+
     /// Given the path to a directory, returns an iterator over its children if it exists.
     /// The iterator's items are of the form `(key, value)` where `key` is the name of the child as
     /// `&str` and `value` is the data of the child if it's a file, as `Option<&T>`.
@@ -0,0 +1,204 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+//! Noticing changes made to a filesystem from outside of Luminol.
+
+use itertools::Itertools;
+use std::collections::HashMap;
+
+use crate::{Metadata, Result};
+
+/// A change to a file or directory, relative to the root of the watched filesystem.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Change {
+    Created(camino::Utf8PathBuf),
+    Modified(camino::Utf8PathBuf),
+    Removed(camino::Utf8PathBuf),
+}
+
+impl Change {
+    pub fn path(&self) -> &camino::Utf8Path {
+        match self {
+            Change::Created(path) | Change::Modified(path) | Change::Removed(path) => path,
+        }
+    }
+}
+
+/// Collects the changes made to a filesystem, created by [`crate::FileSystem::watch`].
+pub struct Watcher {
+    kind: Kind,
+}
+
+enum Kind {
+    /// Notified by the operating system as soon as something changes.
+    #[cfg(not(target_arch = "wasm32"))]
+    Notify {
+        receiver: std::sync::mpsc::Receiver<Change>,
+        _watcher: notify::RecommendedWatcher,
+    },
+    /// Rescans the whole filesystem every time it's asked for changes.
+    Polling {
+        snapshot: Option<HashMap<camino::Utf8PathBuf, Metadata>>,
+    },
+}
+
+impl Watcher {
+    /// Creates a watcher that finds changes by listing the filesystem and comparing it to the
+    /// previous listing. Files whose size or modification time changed are reported as modified,
+    /// so on filesystems that don't keep modification times, only size changes are noticed.
+    /// Directories starting with a `.` are skipped.
+    pub fn polling() -> Self {
+        Self {
+            kind: Kind::Polling { snapshot: None },
+        }
+    }
+
+    /// Creates a watcher that uses the operating system's file change notifications for the
+    /// directory at `root_path` and everything inside it.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn notify(root_path: impl AsRef<camino::Utf8Path>) -> Result<Self> {
+        use notify::event::{EventKind, ModifyKind, RenameMode};
+        use notify::Watcher as _;
+
+        let root_path = root_path.as_ref().to_path_buf();
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let Ok(event) = event else {
+                    return;
+                };
+
+                for (index, path) in event.paths.iter().enumerate() {
+                    let Some(path) = camino::Utf8Path::from_path(path)
+                        .and_then(|path| path.strip_prefix(&root_path).ok())
+                    else {
+                        continue;
+                    };
+                    let path = path.to_path_buf();
+                    #[cfg(windows)]
+                    let path = path.into_string().replace('\\', "/").into();
+
+                    let change = match event.kind {
+                        EventKind::Create(_) => Change::Created(path),
+                        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+                            Change::Removed(path)
+                        }
+                        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+                            Change::Created(path)
+                        }
+                        // The first path is where the file was moved from, the second where it was moved to
+                        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if index == 0 => {
+                            Change::Removed(path)
+                        }
+                        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+                            Change::Created(path)
+                        }
+                        EventKind::Modify(_) => Change::Modified(path),
+                        EventKind::Remove(_) => Change::Removed(path),
+                        _ => continue,
+                    };
+                    // The receiver is only dropped along with the watcher
+                    let _ = sender.send(change);
+                }
+            })
+            .map_err(std::io::Error::other)?;
+
+        watcher
+            .watch(root_path.as_std_path(), notify::RecursiveMode::Recursive)
+            .map_err(std::io::Error::other)?;
+
+        Ok(Self {
+            kind: Kind::Notify {
+                receiver,
+                _watcher: watcher,
+            },
+        })
+    }
+
+    /// Whether this watcher has to rescan the filesystem to find changes.
+    pub fn is_polling(&self) -> bool {
+        matches!(self.kind, Kind::Polling { .. })
+    }
+
+    /// Returns the changes made since the last call, without duplicates.
+    ///
+    /// `filesystem` must be the filesystem this watcher was created for. It's only read by
+    /// polling watchers, and the first call to a polling watcher never returns any changes.
+    pub fn changes(&mut self, filesystem: &impl crate::FileSystem) -> Result<Vec<Change>> {
+        match &mut self.kind {
+            #[cfg(not(target_arch = "wasm32"))]
+            Kind::Notify { receiver, .. } => Ok(receiver.try_iter().unique().collect()),
+            Kind::Polling { snapshot } => {
+                let mut current = HashMap::with_capacity(
+                    snapshot.as_ref().map_or(256, |snapshot| snapshot.len()),
+                );
+                list_recursive(filesystem, camino::Utf8Path::new(""), &mut current)?;
+
+                let Some(previous) = snapshot.replace(current) else {
+                    return Ok(vec![]);
+                };
+                let current = snapshot.as_ref().expect("snapshot was just set");
+
+                let mut changes: Vec<_> = current
+                    .iter()
+                    .filter_map(|(path, metadata)| match previous.get(path) {
+                        None => Some(Change::Created(path.clone())),
+                        // Directories change whenever something inside them does, which is
+                        // already reported for that path
+                        Some(previous) if metadata.is_file && previous != metadata => {
+                            Some(Change::Modified(path.clone()))
+                        }
+                        Some(_) => None,
+                    })
+                    .collect();
+                changes.extend(
+                    previous
+                        .into_keys()
+                        .filter(|path| !current.contains_key(path))
+                        .map(Change::Removed),
+                );
+
+                Ok(changes)
+            }
+        }
+    }
+}
+
+fn list_recursive(
+    filesystem: &impl crate::FileSystem,
+    path: &camino::Utf8Path,
+    listing: &mut HashMap<camino::Utf8PathBuf, Metadata>,
+) -> Result<()> {
+    for entry in filesystem.read_dir(path)? {
+        if !entry.metadata.is_file {
+            if entry.file_name().starts_with('.') {
+                continue;
+            }
+            list_recursive(filesystem, &entry.path, listing)?;
+        }
+        listing.insert(entry.path, entry.metadata);
+    }
+    Ok(())
+}
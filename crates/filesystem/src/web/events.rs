@@ -16,7 +16,8 @@
 // along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
 
 use super::util::{
-    generate_key, get_subdir, get_subdir_create, get_tmp_dir, handle_event, idb, to_future,
+    generate_key, get_subdir, get_subdir_create, get_tmp_dir, handle_event, idb, last_modified,
+    to_future,
 };
 use super::FileSystemCommand;
 use crate::{DirEntry, Error, Metadata, OpenFlags};
@@ -64,6 +65,7 @@ pub fn setup_main_thread_hooks(main_channels: super::MainChannels) {
                             return Ok(Metadata {
                                 is_file: false,
                                 size: 0,
+                                modified: None,
                             });
                         };
                         let subdir = get_subdir(dirs.get(key).unwrap(), &mut iter)
@@ -80,6 +82,7 @@ pub fn setup_main_thread_hooks(main_channels: super::MainChannels) {
                                 .map(|blob| Metadata {
                                     is_file: true,
                                     size: blob.size() as u64,
+                                    modified: Some(last_modified(&blob)),
                                 })
                                 .map_err(|_| Error::IoError(PermissionDenied.into()))
                         } else if to_future::<web_sys::FileSystemDirectoryHandle>(
@@ -92,6 +95,7 @@ pub fn setup_main_thread_hooks(main_channels: super::MainChannels) {
                             Ok(Metadata {
                                 is_file: false,
                                 size: 0,
+                                modified: None,
                             })
                         } else {
                             // If the path is neither a file nor a directory
@@ -393,6 +397,7 @@ pub fn setup_main_thread_hooks(main_channels: super::MainChannels) {
                                             Metadata {
                                                 is_file: true,
                                                 size: blob.size() as u64,
+                                                modified: Some(last_modified(&blob)),
                                             },
                                         ));
                                     }
@@ -403,6 +408,7 @@ pub fn setup_main_thread_hooks(main_channels: super::MainChannels) {
                                         Metadata {
                                             is_file: false,
                                             size: 0,
+                                            modified: None,
                                         },
                                     ));
                                 }
@@ -374,6 +374,7 @@ impl crate::File for File {
         Ok(Metadata {
             is_file: true,
             size,
+            modified: None,
         })
     }
 
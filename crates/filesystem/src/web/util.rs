@@ -95,6 +95,11 @@ pub(super) fn generate_key() -> String {
         .collect()
 }
 
+/// Returns when a file was last modified. Browsers report it in milliseconds since the Unix epoch.
+pub(super) fn last_modified(file: &web_sys::File) -> std::time::SystemTime {
+    std::time::UNIX_EPOCH + std::time::Duration::from_millis(file.last_modified() as u64)
+}
+
 /// Helper function for performing IndexedDB operations on an `IdbObjectStore` with a given
 /// `IdbTransactionMode`.
 pub(super) async fn idb<R>(
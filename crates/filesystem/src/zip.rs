@@ -0,0 +1,457 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+//! Read-write access to zip archives, so zipped RTPs can be mounted and projects exported without
+//! extracting anything.
+//!
+//! Opening a zipped project for editing isn't supported yet, since the project filesystem still
+//! needs a host folder for its config, backups and autosaves.
+
+use std::io::{prelude::*, ErrorKind::InvalidInput, SeekFrom};
+use std::sync::Arc;
+
+use crate::memory::Contents;
+use crate::{DirEntry, Error, Metadata, OpenFlags, Result};
+
+#[derive(Debug, Clone)]
+enum Entry {
+    /// A file that hasn't changed since the archive was read, stored at this index in the archive.
+    Archived { index: usize, size: u64 },
+    /// A file that was created or changed since the archive was read.
+    Memory(Contents),
+}
+
+impl Entry {
+    fn size(&self) -> u64 {
+        match self {
+            Entry::Archived { size, .. } => *size,
+            Entry::Memory(contents) => contents.read().len() as u64,
+        }
+    }
+}
+
+enum Archive<T> {
+    Zip(::zip::ZipArchive<T>),
+    /// A file that hasn't had an archive written to it yet.
+    Empty(T),
+}
+
+/// A filesystem inside of a zip archive.
+///
+/// Reading a file decompresses all of it into memory. Files that are written to are kept in
+/// memory until [`FileSystem::save`] writes them back to the archive, so changes made to a mounted
+/// archive that is never saved are lost when it's dropped.
+pub struct FileSystem<T> {
+    trie: Arc<parking_lot::RwLock<crate::FileSystemTrie<Entry>>>,
+    /// This is only `None` while [`FileSystem::save`] is replacing the archive's contents.
+    archive: Arc<parking_lot::Mutex<Option<Archive<T>>>>,
+}
+
+impl<T> Clone for FileSystem<T> {
+    fn clone(&self) -> Self {
+        Self {
+            trie: self.trie.clone(),
+            archive: self.archive.clone(),
+        }
+    }
+}
+
+impl<T> FileSystem<T>
+where
+    T: crate::File,
+{
+    /// Creates a new zip filesystem from a file containing an existing zip archive.
+    pub fn new(file: T) -> Result<Self> {
+        let mut archive = ::zip::ZipArchive::new(file)?;
+        let trie = read_entries(&mut archive)?;
+        Ok(Self {
+            trie: Arc::new(parking_lot::RwLock::new(trie)),
+            archive: Arc::new(parking_lot::Mutex::new(Some(Archive::Zip(archive)))),
+        })
+    }
+
+    /// Creates a new empty zip filesystem. Anything already in `file` is overwritten on the first
+    /// call to [`FileSystem::save`].
+    pub fn create(file: T) -> Self {
+        Self {
+            trie: Default::default(),
+            archive: Arc::new(parking_lot::Mutex::new(Some(Archive::Empty(file)))),
+        }
+    }
+
+    /// Writes the archive, including every change made since it was read, back to its file.
+    /// Files that haven't changed are copied over as they are, without recompressing them.
+    pub fn save(&self) -> Result<()> {
+        let mut trie = self.trie.write();
+        let mut archive = self.archive.lock();
+
+        let mut zip = match archive.as_mut() {
+            Some(Archive::Zip(zip)) => Some(zip),
+            Some(Archive::Empty(_)) => None,
+            None => return Err(archive_lost()),
+        };
+
+        // Write the new archive somewhere else first, since unchanged files are copied out of the
+        // old one
+        let mut tmp = crate::host::File::new()?;
+        let mut writer = ::zip::ZipWriter::new(&mut tmp);
+        let mut archived = Vec::new();
+        write_dir(
+            &trie,
+            camino::Utf8Path::new(""),
+            &mut writer,
+            &mut zip,
+            &mut 0,
+            &mut archived,
+        )?;
+        writer.finish()?;
+        drop(writer);
+
+        let mut file = match archive.take() {
+            Some(Archive::Zip(zip)) => zip.into_inner(),
+            Some(Archive::Empty(file)) => file,
+            None => unreachable!(),
+        };
+        let result = replace_contents(&mut file, &mut tmp);
+        // Whether or not that worked, the archive has to be put back. If the file can't be read
+        // as an archive anymore it's kept as an empty one, so that it can still be saved again
+        *archive = Some(if ::zip::ZipArchive::new(&mut file).is_ok() {
+            Archive::Zip(::zip::ZipArchive::new(file)?)
+        } else {
+            Archive::Empty(file)
+        });
+        result?;
+
+        // The unchanged files have moved to different indices in the new archive
+        for (path, new_index) in archived {
+            if let Some(Entry::Archived { index, .. }) = trie.get_file_mut(path) {
+                *index = new_index;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn read_archived(&self, index: usize) -> Result<Vec<u8>> {
+        let mut archive = self.archive.lock();
+        let Some(Archive::Zip(zip)) = archive.as_mut() else {
+            return Err(archive_lost());
+        };
+        let mut file = zip.by_index(index)?;
+        let mut buf = Vec::with_capacity(file.size() as usize);
+        file.read_to_end(&mut buf)?;
+        Ok(buf)
+    }
+}
+
+/// Replaces everything in `file` with the contents of `tmp`.
+fn replace_contents<T: crate::File>(file: &mut T, tmp: &mut crate::host::File) -> Result<()> {
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+    tmp.seek(SeekFrom::Start(0))?;
+    std::io::copy(tmp, file)?;
+    file.flush()?;
+    Ok(())
+}
+
+fn archive_lost() -> Error {
+    Error::IoError(std::io::Error::new(
+        std::io::ErrorKind::Other,
+        "the zip archive could not be read after it was last saved",
+    ))
+}
+
+fn read_entries<T: crate::File>(
+    archive: &mut ::zip::ZipArchive<T>,
+) -> Result<crate::FileSystemTrie<Entry>> {
+    let mut trie = crate::FileSystemTrie::new();
+    for index in 0..archive.len() {
+        let file = archive.by_index_raw(index)?;
+        // Skip anything that would end up outside of the archive's root if it was extracted
+        if file.enclosed_name().is_none() {
+            continue;
+        }
+        let path = file.name().replace('\\', "/");
+        let path = camino::Utf8Path::new(path.trim_end_matches('/'));
+        if path.as_str().is_empty() {
+            continue;
+        }
+
+        if file.is_dir() {
+            trie.create_dir(path);
+        } else {
+            trie.create_file(
+                path,
+                Entry::Archived {
+                    index,
+                    size: file.size(),
+                },
+            );
+        }
+    }
+    Ok(trie)
+}
+
+/// Writes everything inside the directory at `path` to `writer`. `index` is the index of the next
+/// entry in the archive being written, and the new index of every file copied from `zip` is added
+/// to `archived`.
+fn write_dir<T, W>(
+    trie: &crate::FileSystemTrie<Entry>,
+    path: &camino::Utf8Path,
+    writer: &mut ::zip::ZipWriter<W>,
+    zip: &mut Option<&mut ::zip::ZipArchive<T>>,
+    index: &mut usize,
+    archived: &mut Vec<(camino::Utf8PathBuf, usize)>,
+) -> Result<()>
+where
+    T: crate::File,
+    W: Write + Seek,
+{
+    let children: Vec<_> = trie
+        .iter_dir(path)
+        .into_iter()
+        .flatten()
+        .map(|(name, entry)| (path.join(name), entry.cloned()))
+        .collect();
+
+    for (child, entry) in children {
+        match entry {
+            None => {
+                writer.add_directory(child.as_str(), Default::default())?;
+                *index += 1;
+                write_dir(trie, &child, writer, zip, index, archived)?;
+                continue;
+            }
+            Some(Entry::Archived {
+                index: old_index, ..
+            }) => {
+                let zip = zip.as_mut().ok_or_else(archive_lost)?;
+                writer.raw_copy_file_rename(zip.by_index_raw(old_index)?, child.as_str())?;
+                archived.push((child, *index));
+            }
+            Some(Entry::Memory(contents)) => {
+                let contents = contents.read();
+                let options = ::zip::write::FileOptions::default()
+                    .large_file(contents.len() as u64 > u32::MAX as u64);
+                writer.start_file(child.as_str(), options)?;
+                writer.write_all(&contents)?;
+            }
+        }
+        *index += 1;
+    }
+
+    Ok(())
+}
+
+impl<T> crate::FileSystem for FileSystem<T>
+where
+    T: crate::File,
+{
+    type File = crate::memory::File;
+
+    fn open_file(
+        &self,
+        path: impl AsRef<camino::Utf8Path>,
+        flags: OpenFlags,
+    ) -> Result<Self::File> {
+        let path = path.as_ref();
+        if flags.contains(OpenFlags::Truncate) && !flags.contains(OpenFlags::Write) {
+            return Err(Error::IoError(InvalidInput.into()));
+        }
+        let mut trie = self.trie.write();
+
+        let contents = match trie.get_file(path).cloned() {
+            Some(Entry::Memory(contents)) => {
+                if flags.contains(OpenFlags::Truncate) {
+                    contents.write().clear();
+                }
+                contents
+            }
+            Some(Entry::Archived { index, .. }) => {
+                let contents = if flags.contains(OpenFlags::Truncate) {
+                    Contents::default()
+                } else {
+                    Arc::new(parking_lot::RwLock::new(self.read_archived(index)?))
+                };
+                // Anything written to the file has to be kept around until the archive is saved
+                if flags.contains(OpenFlags::Write) {
+                    trie.create_file(path, Entry::Memory(contents.clone()));
+                }
+                contents
+            }
+            None if flags.contains(OpenFlags::Create) => {
+                if trie.contains_dir(path) {
+                    return Err(Error::IoError(InvalidInput.into()));
+                }
+                if !trie.contains_dir(path.parent().unwrap_or(camino::Utf8Path::new(""))) {
+                    return Err(Error::NotExist);
+                }
+                let contents = Contents::default();
+                trie.create_file(path, Entry::Memory(contents.clone()));
+                contents
+            }
+            None => return Err(Error::NotExist),
+        };
+
+        Ok(crate::memory::File::new(contents, flags))
+    }
+
+    fn metadata(&self, path: impl AsRef<camino::Utf8Path>) -> Result<Metadata> {
+        let path = path.as_ref();
+        let trie = self.trie.read();
+        if let Some(entry) = trie.get_file(path) {
+            Ok(Metadata {
+                is_file: true,
+                size: entry.size(),
+                modified: None,
+            })
+        } else if let Some(size) = trie.get_dir_size(path) {
+            Ok(Metadata {
+                is_file: false,
+                size: size as u64,
+                modified: None,
+            })
+        } else {
+            Err(Error::NotExist)
+        }
+    }
+
+    fn rename(
+        &self,
+        from: impl AsRef<camino::Utf8Path>,
+        to: impl AsRef<camino::Utf8Path>,
+    ) -> Result<()> {
+        let to = to.as_ref();
+        let mut trie = self.trie.write();
+
+        if trie.contains(to) {
+            return Err(Error::IoError(std::io::ErrorKind::AlreadyExists.into()));
+        }
+        if !trie.contains_dir(to.parent().unwrap_or(camino::Utf8Path::new(""))) {
+            return Err(Error::NotExist);
+        }
+        if !trie.rename(from, to) {
+            return Err(Error::NotExist);
+        }
+
+        Ok(())
+    }
+
+    fn exists(&self, path: impl AsRef<camino::Utf8Path>) -> Result<bool> {
+        Ok(self.trie.read().contains(path))
+    }
+
+    fn create_dir(&self, path: impl AsRef<camino::Utf8Path>) -> Result<()> {
+        let path = path.as_ref();
+        let mut trie = self.trie.write();
+        if trie.contains_file(path) {
+            return Err(Error::IoError(std::io::ErrorKind::AlreadyExists.into()));
+        }
+        trie.create_dir(path);
+        Ok(())
+    }
+
+    fn remove_dir(&self, path: impl AsRef<camino::Utf8Path>) -> Result<()> {
+        let path = path.as_ref();
+        let mut trie = self.trie.write();
+        if !trie.contains_dir(path) {
+            return Err(Error::NotExist);
+        }
+        trie.remove_dir(path);
+        Ok(())
+    }
+
+    fn remove_file(&self, path: impl AsRef<camino::Utf8Path>) -> Result<()> {
+        self.trie
+            .write()
+            .remove_file(path)
+            .map(|_| ())
+            .ok_or(Error::NotExist)
+    }
+
+    fn read_dir(&self, path: impl AsRef<camino::Utf8Path>) -> Result<Vec<DirEntry>> {
+        let path = path.as_ref();
+        let trie = self.trie.read();
+        let entries = trie
+            .iter_dir(path)
+            .ok_or(Error::NotExist)?
+            .map(|(name, entry)| {
+                let path = path.join(name);
+                let metadata = match entry {
+                    Some(entry) => Metadata {
+                        is_file: true,
+                        size: entry.size(),
+                        modified: None,
+                    },
+                    None => Metadata {
+                        is_file: false,
+                        size: trie.get_dir_size(&path).unwrap_or_default() as u64,
+                        modified: None,
+                    },
+                };
+                DirEntry::new(path, metadata)
+            })
+            .collect();
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FileSystem as _;
+
+    #[test]
+    fn save_and_reopen() {
+        let memory = crate::memory::FileSystem::new();
+        let file = memory
+            .open_file(
+                "Project.zip",
+                crate::OpenFlags::Create | crate::OpenFlags::Read | crate::OpenFlags::Write,
+            )
+            .unwrap();
+
+        let zip = super::FileSystem::create(file);
+        zip.create_dir("Audio/BGM").unwrap();
+        zip.create_dir("Data").unwrap();
+        zip.write("Data/Actors.rxdata", b"actors").unwrap();
+        zip.write("Data/System.rxdata", b"system").unwrap();
+        zip.save().unwrap();
+
+        // Unchanged files are copied from the old archive, so they have to survive a second save
+        zip.rename("Data/System.rxdata", "Data/Scripts.rxdata")
+            .unwrap();
+        zip.write("Data/Actors.rxdata", b"new actors").unwrap();
+        zip.save().unwrap();
+        assert_eq!(zip.read("Data/Scripts.rxdata").unwrap(), b"system");
+
+        let file = memory
+            .open_file("Project.zip", crate::OpenFlags::Read)
+            .unwrap();
+        let zip = super::FileSystem::new(file).unwrap();
+        assert_eq!(zip.read("Data/Actors.rxdata").unwrap(), b"new actors");
+        assert_eq!(zip.read("Data/Scripts.rxdata").unwrap(), b"system");
+        assert!(!zip.exists("Data/System.rxdata").unwrap());
+        assert!(zip.metadata("Audio/BGM").is_ok_and(|m| !m.is_file));
+    }
+}
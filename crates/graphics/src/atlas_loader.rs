@@ -48,6 +48,47 @@ impl Loader {
             .clone())
     }
 
+    /// Rebuilds the atlas of a tileset after its graphics were changed outside of Luminol.
+    ///
+    /// If the atlas has the same layout as before, its texture is overwritten in place so maps
+    /// that are already open update immediately. Otherwise only maps opened afterwards use it.
+    pub fn reload_changed(
+        &self,
+        graphics_state: &GraphicsState,
+        filesystem: &impl luminol_filesystem::FileSystem,
+        tileset: &luminol_data::rpg::Tileset,
+    ) -> anyhow::Result<()> {
+        let Some(old) = self.atlases.get(&tileset.id).map(|atlas| atlas.clone()) else {
+            return Ok(());
+        };
+        let new = Atlas::new(graphics_state, filesystem, tileset)?;
+
+        if old.autotile_width != new.autotile_width
+            || old.tileset_height != new.tileset_height
+            || old.autotile_frames != new.autotile_frames
+        {
+            self.atlases.insert(tileset.id, new);
+            return Ok(());
+        }
+
+        let mut encoder = graphics_state.render_state.device.create_command_encoder(
+            &wgpu::CommandEncoderDescriptor {
+                label: Some("atlas reload"),
+            },
+        );
+        encoder.copy_texture_to_texture(
+            new.atlas_texture.texture.as_image_copy(),
+            old.atlas_texture.texture.as_image_copy(),
+            old.atlas_texture.size(),
+        );
+        graphics_state
+            .render_state
+            .queue
+            .submit(std::iter::once(encoder.finish()));
+
+        Ok(())
+    }
+
     pub fn clear(&self) {
         self.atlases.clear()
     }
@@ -0,0 +1,510 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+//! Renders maps into images on the CPU, so that maps can be exported without a GPU.
+
+use image::{GenericImageView, Rgba, RgbaImage};
+
+use crate::tiles::AtlasImage;
+
+/// What to draw when rendering a map into an image.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MapImageOptions {
+    /// Draw the graphics of the events' first pages.
+    pub events: bool,
+    /// Draw the tileset's panorama behind the tiles.
+    pub panorama: bool,
+    /// Draw the tileset's fog over the map.
+    pub fog: bool,
+    /// Draw the passage overlay the same way the collision view in the map editor does.
+    pub passages: bool,
+    /// The size of the image relative to the size of the map in pixels.
+    pub scale: f32,
+}
+
+impl Default for MapImageOptions {
+    fn default() -> Self {
+        Self {
+            events: true,
+            panorama: false,
+            fog: false,
+            passages: false,
+            scale: 1.,
+        }
+    }
+}
+
+/// A map rendered by [`render_map`].
+pub struct MapImage {
+    pub image: RgbaImage,
+    /// Describes the graphics that failed to load, which were left out of the image.
+    pub warnings: Vec<String>,
+}
+
+/// Renders every layer of a map into an image. Graphics that fail to load are left out and
+/// reported in [`MapImage::warnings`], rather than failing the whole render.
+pub fn render_map(
+    filesystem: &impl luminol_filesystem::FileSystem,
+    map: &luminol_data::rpg::Map,
+    tileset: &luminol_data::rpg::Tileset,
+    options: &MapImageOptions,
+) -> MapImage {
+    let mut warnings = Vec::new();
+    let atlas = AtlasImage::new(filesystem, tileset)
+        .map_err(|e| warnings.push(format!("Skipped the tiles: {e:#}")))
+        .ok();
+
+    let mut canvas = RgbaImage::new(map.data.xsize() as u32 * 32, map.data.ysize() as u32 * 32);
+
+    if options.panorama {
+        if let Some(panorama_name) = &tileset.panorama_name {
+            match load_image(filesystem, "Graphics/Panoramas", panorama_name) {
+                Ok(panorama) => {
+                    let panorama = image::imageops::huerotate(&panorama, tileset.panorama_hue);
+                    draw_tiled(&mut canvas, &panorama, 255, luminol_data::BlendMode::Normal);
+                }
+                Err(e) => warnings.push(format!("Skipped the panorama: {e:#}")),
+            }
+        }
+    }
+
+    // Tiles with a priority are sorted together with the events, like in RPG Maker XP, so that
+    // they can cover the events below them
+    let mut sprites = Vec::new();
+    if let Some(atlas) = &atlas {
+        for z in 0..map.data.zsize() {
+            for y in 0..map.data.ysize() {
+                for x in 0..map.data.xsize() {
+                    let tile_id = map.data[(x, y, z)];
+                    // Blank tiles aren't in the atlas either
+                    let Some(tile) = atlas.tile(tile_id) else {
+                        continue;
+                    };
+                    let position = (x as i64 * 32, y as i64 * 32);
+                    match priority(tileset, tile_id) {
+                        0 => image::imageops::overlay(&mut canvas, &*tile, position.0, position.1),
+                        priority => sprites.push(Sprite {
+                            z: (y as i64 + priority) * 32 + 32,
+                            image: tile.to_image(),
+                            position,
+                            opacity: 255,
+                            blend_mode: luminol_data::BlendMode::Normal,
+                        }),
+                    }
+                }
+            }
+        }
+    }
+
+    if options.events {
+        event_sprites(
+            filesystem,
+            &mut sprites,
+            &mut warnings,
+            map,
+            tileset,
+            atlas.as_ref(),
+        );
+    }
+
+    // The sort is stable, so events are drawn over tiles with the same z
+    sprites.sort_by_key(|sprite| sprite.z);
+    for sprite in &sprites {
+        draw(
+            &mut canvas,
+            &sprite.image,
+            sprite.position,
+            sprite.opacity,
+            sprite.blend_mode,
+        );
+    }
+
+    if options.fog {
+        if let Some(fog_name) = &tileset.fog_name {
+            match load_image(filesystem, "Graphics/Fogs", fog_name) {
+                Ok(fog) => {
+                    let fog = image::imageops::huerotate(&fog, tileset.fog_hue);
+                    let fog = if tileset.fog_zoom != 100 && tileset.fog_zoom > 0 {
+                        image::imageops::resize(
+                            &fog,
+                            (fog.width() * tileset.fog_zoom as u32 / 100).max(1),
+                            (fog.height() * tileset.fog_zoom as u32 / 100).max(1),
+                            image::imageops::FilterType::Nearest,
+                        )
+                    } else {
+                        fog
+                    };
+                    draw_tiled(
+                        &mut canvas,
+                        &fog,
+                        tileset.fog_opacity.clamp(0, 255) as u8,
+                        tileset.fog_blend_type,
+                    );
+                }
+                Err(e) => warnings.push(format!("Skipped the fog: {e:#}")),
+            }
+        }
+    }
+
+    if options.passages {
+        luminol_data::tilemap::calculate_passages(
+            &tileset.passages,
+            &tileset.priorities,
+            &map.data,
+            options.events.then_some(&map.events),
+            (0..map.data.zsize()).rev(),
+            |x, y, passage| draw_passage(&mut canvas, (x as u32 * 32, y as u32 * 32), passage),
+        );
+    }
+
+    let image = if options.scale != 1. && options.scale > 0. {
+        image::imageops::resize(
+            &canvas,
+            ((canvas.width() as f32 * options.scale).round() as u32).max(1),
+            ((canvas.height() as f32 * options.scale).round() as u32).max(1),
+            if options.scale > 1. {
+                image::imageops::FilterType::Nearest
+            } else {
+                image::imageops::FilterType::Triangle
+            },
+        )
+    } else {
+        canvas
+    };
+
+    MapImage { image, warnings }
+}
+
+fn load_image(
+    filesystem: &impl luminol_filesystem::FileSystem,
+    directory: &str,
+    file: impl AsRef<camino::Utf8Path>,
+) -> anyhow::Result<RgbaImage> {
+    let path = camino::Utf8Path::new(directory).join(file);
+    let data = filesystem
+        .read(&path)
+        .map_err(|e| anyhow::anyhow!("while loading {path}: {e}"))?;
+    Ok(image::load_from_memory(&data)
+        .map_err(|e| anyhow::anyhow!("while loading {path}: {e}"))?
+        .to_rgba8())
+}
+
+/// An image drawn between the tiles without a priority and the fog, ordered by `z`.
+struct Sprite {
+    z: i64,
+    image: RgbaImage,
+    position: (i64, i64),
+    opacity: u8,
+    blend_mode: luminol_data::BlendMode,
+}
+
+/// The priority of a tile in a tileset, which is 0 for tiles that are always below events.
+fn priority(tileset: &luminol_data::rpg::Tileset, tile_id: i16) -> i64 {
+    let tile_id = tile_id as usize;
+    if tile_id < tileset.priorities.len() {
+        tileset.priorities[tile_id] as i64
+    } else {
+        0
+    }
+}
+
+/// Adds the graphic of the first page of every event, in the same place as the map editor and
+/// with the same z as `Game_Character#screen_z`. Events whose graphic fails to load are skipped.
+fn event_sprites(
+    filesystem: &impl luminol_filesystem::FileSystem,
+    sprites: &mut Vec<Sprite>,
+    warnings: &mut Vec<String>,
+    map: &luminol_data::rpg::Map,
+    tileset: &luminol_data::rpg::Tileset,
+    atlas: Option<&AtlasImage>,
+) {
+    let mut characters = std::collections::HashMap::new();
+
+    for (_, event) in map.events.iter() {
+        let Some(page) = event.pages.first() else {
+            continue;
+        };
+        let graphic = &page.graphic;
+
+        let mut z = event.y as i64 * 32 + 32;
+        let sprite = if let Some(id) = graphic.tile_id {
+            // Like in `Event::new`, tile graphics are offset by one
+            let tile_id = (id + 1) as i16;
+            z += priority(tileset, tile_id) * 32;
+            match atlas.and_then(|atlas| atlas.tile(tile_id)) {
+                Some(tile) => tile.to_image(),
+                None => continue,
+            }
+        } else if let Some(character_name) = &graphic.character_name {
+            // Failures are cached too, so each missing graphic is only reported once
+            let character = characters.entry(character_name.clone()).or_insert_with(|| {
+                load_image(filesystem, "Graphics/Characters", character_name)
+                    .map_err(|e| {
+                        warnings.push(format!("Skipped the events using {character_name}: {e:#}"))
+                    })
+                    .ok()
+            });
+            let Some(character) = character else {
+                continue;
+            };
+
+            let cw = character.width() / 4;
+            let ch = character.height() / 4;
+            if ch > 32 {
+                z += 31;
+            }
+            let x = graphic.pattern.clamp(0, 3) as u32 * cw;
+            let y = ((graphic.direction - 2) / 2).clamp(0, 3) as u32 * ch;
+            character.view(x, y, cw, ch).to_image()
+        } else {
+            continue;
+        };
+        if page.always_on_top {
+            z = i64::MAX;
+        }
+
+        let image = if graphic.character_hue != 0 {
+            image::imageops::huerotate(&sprite, graphic.character_hue)
+        } else {
+            sprite
+        };
+
+        let x = event.x as i64 * 32 + (32 - image.width() as i64) / 2;
+        let y = event.y as i64 * 32 + 32 - image.height() as i64;
+        sprites.push(Sprite {
+            z,
+            image,
+            position: (x, y),
+            opacity: graphic.opacity.clamp(0, 255) as u8,
+            blend_mode: graphic.blend_type,
+        });
+    }
+}
+
+/// Draws an image repeatedly until it covers the whole canvas, like panoramas and fogs.
+fn draw_tiled(
+    canvas: &mut RgbaImage,
+    image: &RgbaImage,
+    opacity: u8,
+    blend_mode: luminol_data::BlendMode,
+) {
+    if image.width() == 0 || image.height() == 0 {
+        return;
+    }
+
+    for y in (0..canvas.height()).step_by(image.height() as usize) {
+        for x in (0..canvas.width()).step_by(image.width() as usize) {
+            draw(canvas, image, (x as i64, y as i64), opacity, blend_mode);
+        }
+    }
+}
+
+/// Draws an image onto the canvas with the given opacity and blend mode. Parts of the image that
+/// are outside of the canvas are ignored.
+fn draw(
+    canvas: &mut RgbaImage,
+    image: &RgbaImage,
+    (x, y): (i64, i64),
+    opacity: u8,
+    blend_mode: luminol_data::BlendMode,
+) {
+    for (image_x, image_y, &Rgba(source)) in image.enumerate_pixels() {
+        let (Ok(canvas_x), Ok(canvas_y)) = (
+            u32::try_from(x + image_x as i64),
+            u32::try_from(y + image_y as i64),
+        ) else {
+            continue;
+        };
+        if canvas_x >= canvas.width() || canvas_y >= canvas.height() {
+            continue;
+        }
+
+        let alpha = source[3] as f32 / 255. * opacity as f32 / 255.;
+        if alpha <= 0. {
+            continue;
+        }
+
+        let Rgba(dest) = canvas.get_pixel_mut(canvas_x, canvas_y);
+        for (dest_channel, &source_channel) in dest.iter_mut().zip(source.iter()).take(3) {
+            let source_channel = source_channel as f32;
+            let dest_value = *dest_channel as f32;
+            *dest_channel = match blend_mode {
+                luminol_data::BlendMode::Normal => {
+                    source_channel * alpha + dest_value * (1. - alpha)
+                }
+                luminol_data::BlendMode::Add => dest_value + source_channel * alpha,
+                luminol_data::BlendMode::Subtract => dest_value - source_channel * alpha,
+            }
+            .round()
+            .clamp(0., 255.) as u8;
+        }
+        dest[3] = (alpha * 255. + dest[3] as f32 * (1. - alpha))
+            .round()
+            .clamp(0., 255.) as u8;
+    }
+}
+
+/// Draws a red triangle pointing from the center of the tile towards each edge the passage value
+/// blocks, like the collision shader does.
+fn draw_passage(canvas: &mut RgbaImage, (tile_x, tile_y): (u32, u32), passage: i16) {
+    if passage == 0 {
+        return;
+    }
+
+    for y in 0..32 {
+        for x in 0..32 {
+            // Offset from the center of the tile, doubled so that it's never 0
+            let dx = x as i32 * 2 - 31;
+            let dy = y as i32 * 2 - 31;
+            let direction = if dy.abs() >= dx.abs() {
+                if dy > 0 {
+                    1 // Down
+                } else {
+                    8 // Up
+                }
+            } else if dx < 0 {
+                2 // Left
+            } else {
+                4 // Right
+            };
+            if passage & direction == 0 {
+                continue;
+            }
+
+            let Rgba(dest) = canvas.get_pixel_mut(tile_x + x, tile_y + y);
+            for (dest_channel, source_channel) in dest.iter_mut().zip([255., 0., 0.]) {
+                *dest_channel = (source_channel * 0.4 + *dest_channel as f32 * 0.6).round() as u8;
+            }
+            dest[3] = (0.4 * 255. + dest[3] as f32 * 0.6).round() as u8;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgba, RgbaImage};
+    use luminol_filesystem::FileSystem;
+
+    const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+    const GREEN: Rgba<u8> = Rgba([0, 255, 0, 255]);
+    const BLUE: Rgba<u8> = Rgba([0, 0, 255, 255]);
+
+    fn write_png(
+        filesystem: &luminol_filesystem::memory::FileSystem,
+        path: &str,
+        image: &RgbaImage,
+    ) {
+        let mut bytes = Vec::new();
+        image
+            .write_to(
+                &mut std::io::Cursor::new(&mut bytes),
+                image::ImageOutputFormat::Png,
+            )
+            .unwrap();
+        filesystem.write(path, bytes).unwrap();
+    }
+
+    #[test]
+    fn renders_tiles_and_events_by_priority() {
+        let filesystem = luminol_filesystem::memory::FileSystem::new();
+        filesystem.create_dir("Graphics/Tilesets").unwrap();
+        filesystem.create_dir("Graphics/Characters").unwrap();
+
+        // The first tile of the tileset is red and the second one is blue
+        let tileset_image = RgbaImage::from_fn(256, 32, |x, _| match x / 32 {
+            0 => RED,
+            1 => BLUE,
+            _ => Rgba([0; 4]),
+        });
+        write_png(&filesystem, "Graphics/Tilesets/Tiles", &tileset_image);
+        write_png(
+            &filesystem,
+            "Graphics/Characters/Hero",
+            &RgbaImage::from_pixel(128, 128, GREEN),
+        );
+
+        let mut tileset = luminol_data::rpg::Tileset {
+            tileset_name: Some("Tiles".into()),
+            autotile_names: vec![String::new(); 7],
+            passages: luminol_data::Table1::new(392),
+            priorities: luminol_data::Table1::new(392),
+            ..Default::default()
+        };
+        tileset.priorities[385] = 1;
+
+        let mut map = luminol_data::rpg::Map {
+            data: luminol_data::Table3::new(2, 2, 3),
+            ..Default::default()
+        };
+        map.data[(0, 0, 0)] = 384;
+        // A tile with a priority in the same row as an event covers it
+        map.data[(0, 1, 1)] = 385;
+        for (id, x) in [(1, 0), (2, 1)] {
+            let mut event = luminol_data::rpg::Event::new(x, 1, id);
+            event.pages[0].graphic.character_name = Some("Hero".into());
+            map.events.insert(id, event);
+        }
+
+        let super::MapImage { image, warnings } = super::render_map(
+            &filesystem,
+            &map,
+            &tileset,
+            &super::MapImageOptions::default(),
+        );
+        assert!(warnings.is_empty());
+
+        assert_eq!(image.dimensions(), (64, 64));
+        assert_eq!(*image.get_pixel(16, 16), RED);
+        assert_eq!(image.get_pixel(48, 16)[3], 0);
+        assert_eq!(*image.get_pixel(16, 48), BLUE);
+        assert_eq!(*image.get_pixel(48, 48), GREEN);
+    }
+
+    #[test]
+    fn skips_missing_graphics() {
+        let filesystem = luminol_filesystem::memory::FileSystem::new();
+        let tileset = luminol_data::rpg::Tileset {
+            tileset_name: Some("Missing".into()),
+            autotile_names: vec![String::new(); 7],
+            ..Default::default()
+        };
+        let mut map = luminol_data::rpg::Map {
+            data: luminol_data::Table3::new(1, 1, 3),
+            ..Default::default()
+        };
+        map.data[(0, 0, 0)] = 384;
+        let mut event = luminol_data::rpg::Event::new(0, 0, 1);
+        event.pages[0].graphic.character_name = Some("Missing".into());
+        map.events.insert(1, event);
+
+        let super::MapImage { image, warnings } = super::render_map(
+            &filesystem,
+            &map,
+            &tileset,
+            &super::MapImageOptions::default(),
+        );
+        assert_eq!(image.dimensions(), (32, 32));
+        assert_eq!(warnings.len(), 2);
+    }
+}
@@ -19,12 +19,14 @@ pub mod binding_helpers;
 pub use binding_helpers::{BindGroupBuilder, BindGroupLayoutBuilder};
 
 pub mod collision;
+pub mod compositor;
 pub mod quad;
 pub mod sprite;
 pub mod tiles;
 pub mod vertex;
 pub mod viewport;
 
+pub mod character;
 pub mod event;
 pub mod map;
 pub mod plane;
@@ -33,6 +35,7 @@ pub mod atlas_loader;
 
 pub mod texture_loader;
 
+pub use character::Character;
 pub use event::Event;
 pub use map::Map;
 pub use plane::Plane;
@@ -178,4 +178,55 @@ impl Loader {
     pub fn clear(&self) {
         self.loaded_textures.clear();
     }
+
+    /// Reloads every texture loaded from `path`, after it was changed outside of Luminol.
+    ///
+    /// Textures that are still the same size are overwritten in place, so everything already
+    /// drawing them updates immediately. Textures that changed size or were removed are only
+    /// evicted from the cache, since whatever uses them was laid out for the old size.
+    pub fn reload_changed(
+        &self,
+        filesystem: &impl luminol_filesystem::FileSystem,
+        path: impl AsRef<camino::Utf8Path>,
+    ) -> anyhow::Result<()> {
+        let changed =
+            luminol_filesystem::path_cache::to_lowercase(path.as_ref().with_extension(""));
+        let paths: Vec<_> = self
+            .loaded_textures
+            .iter()
+            .map(|entry| entry.key().clone())
+            .filter(|path| {
+                luminol_filesystem::path_cache::to_lowercase(path.with_extension("")) == changed
+            })
+            .collect();
+
+        for path in paths {
+            let Some(texture) = self.get(&path) else {
+                continue;
+            };
+            if !filesystem.exists(&path)? {
+                self.remove(&path);
+                continue;
+            }
+
+            let image = image::load_from_memory(&filesystem.read(&path)?)?.to_rgba8();
+            if image.width() != texture.width() || image.height() != texture.height() {
+                self.remove(&path);
+                continue;
+            }
+
+            self.render_state.queue.write_texture(
+                texture.texture.as_image_copy(),
+                image.as_raw(),
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(4 * image.width()),
+                    rows_per_image: Some(image.height()),
+                },
+                texture.size(),
+            );
+        }
+
+        Ok(())
+    }
 }
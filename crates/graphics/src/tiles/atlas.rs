@@ -51,21 +51,262 @@ pub struct Atlas {
     pub autotile_frames: [u32; AUTOTILE_AMOUNT as usize],
 }
 
+/// A tileset atlas that lives in memory instead of on the GPU, for rendering without wgpu.
+///
+/// This has the same layout as [`Atlas`], so tiles are found in it the same way.
+#[derive(Clone)]
+pub struct AtlasImage {
+    pub image: image::RgbaImage,
+    pub autotile_width: u32,
+    pub tileset_height: u32,
+    pub autotile_frames: [u32; AUTOTILE_AMOUNT as usize],
+}
+
+/// Where the graphic of a tile ID comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TileSource {
+    /// Tile IDs below 48 are always blank.
+    Empty,
+    /// One of the 48 shapes of an autotile.
+    Autotile {
+        /// Which of the tileset's 7 autotiles this is, starting from 0.
+        index: usize,
+        shape: u32,
+        /// The positions of the four 16x16 pieces the shape is made of in the first frame of the
+        /// autotile graphic, in pixels, in the order top left, top right, bottom left, bottom
+        /// right.
+        pieces: [(u32, u32); 4],
+    },
+    /// A tile of the tileset graphic, counted in tiles from the top left.
+    Tileset { column: u32, row: u32 },
+    /// Tile IDs too large to fit in any atlas, which are drawn as blank tiles.
+    OutOfRange,
+}
+
+impl TileSource {
+    pub fn new(tile: i16) -> Self {
+        let max_tile_id = (MAX_SIZE / TILESET_WIDTH) * ROWS_UNDER_AUTOTILES_TIMES_COLUMNS
+            + TOTAL_AUTOTILE_ID_AMOUNT;
+        let Ok(tile) = u32::try_from(tile) else {
+            return Self::OutOfRange;
+        };
+
+        if tile < AUTOTILE_ID_AMOUNT {
+            Self::Empty
+        } else if tile < TOTAL_AUTOTILE_ID_AMOUNT {
+            let shape = tile % AUTOTILE_ID_AMOUNT;
+            Self::Autotile {
+                index: (tile / AUTOTILE_ID_AMOUNT - 1) as usize,
+                shape,
+                pieces: AUTOTILES[shape as usize]
+                    .map(|sub_tile| (sub_tile % 6 * 16, sub_tile / 6 * 16)),
+            }
+        } else if tile < max_tile_id {
+            Self::Tileset {
+                column: (tile - TOTAL_AUTOTILE_ID_AMOUNT) % TILESET_COLUMNS,
+                row: (tile - TOTAL_AUTOTILE_ID_AMOUNT) / TILESET_COLUMNS,
+            }
+        } else {
+            Self::OutOfRange
+        }
+    }
+}
+
+/// The size of an atlas and how the tileset is split up to fit into it.
+struct AtlasLayout {
+    width: u32,
+    height: u32,
+    /// How many columns of the tileset are under the autotiles
+    rows_under: u32,
+    /// How many columns of the tileset are to the side of the autotiles
+    rows_side: u32,
+}
+
+impl AtlasLayout {
+    fn new(autotile_width: u32, tileset_height: u32) -> Self {
+        if TOTAL_AUTOTILE_HEIGHT + tileset_height < MAX_SIZE {
+            Self {
+                width: autotile_width.max(TILESET_WIDTH), // in case we have less autotiles frames than the tileset is wide
+                height: TOTAL_AUTOTILE_HEIGHT + tileset_height, // we're sure that the tileset can fit into the atlas just fine
+                rows_under: 1,
+                rows_side: 0,
+            }
+        } else {
+            // Find out how many rows are under autotiles
+            // Take the smallest of these
+            let rows_under = u32::min(
+                // How many times can the tileset fit under the autotiles?
+                tileset_height.div_ceil(HEIGHT_UNDER_AUTOTILES),
+                // How many columns of autotiles are there
+                autotile_width.div_ceil(TILESET_WIDTH),
+            );
+            // Find out how many rows would fit on the side by dividing the left over height by MAX_SIZE
+            let rows_side = tileset_height
+                .saturating_sub(rows_under * HEIGHT_UNDER_AUTOTILES)
+                .div_ceil(MAX_SIZE);
+
+            Self {
+                width: ((rows_under + rows_side) * TILESET_WIDTH).max(autotile_width),
+                height: MAX_SIZE,
+                rows_under,
+                rows_side,
+            }
+        }
+    }
+
+    /// Returns `(y, height, (dest_x, dest_y))` for every horizontal strip of the tileset, where
+    /// `y` and `height` are the position and height of the strip in the tileset and `dest_x` and
+    /// `dest_y` are where the strip goes in the atlas.
+    fn tileset_strips(&self, tileset_height: u32) -> Vec<(u32, u32, (u32, u32))> {
+        if TOTAL_AUTOTILE_HEIGHT + tileset_height < MAX_SIZE {
+            return vec![(0, tileset_height, (0, TOTAL_AUTOTILE_HEIGHT))];
+        }
+
+        let under = (0..self.rows_under).map(|i| {
+            let y = HEIGHT_UNDER_AUTOTILES * i;
+            let height = if y + HEIGHT_UNDER_AUTOTILES > tileset_height {
+                tileset_height - y
+            } else {
+                HEIGHT_UNDER_AUTOTILES
+            };
+            (y, height, (TILESET_WIDTH * i, TOTAL_AUTOTILE_HEIGHT))
+        });
+        let side = (0..self.rows_side).map(|i| {
+            let y = (HEIGHT_UNDER_AUTOTILES * self.rows_under) + MAX_SIZE * i;
+            let height = if y + MAX_SIZE > tileset_height {
+                tileset_height - y
+            } else {
+                MAX_SIZE
+            };
+            (y, height, (TILESET_WIDTH * (self.rows_under + i), 0))
+        });
+        under.chain(side).collect()
+    }
+}
+
+/// Calculates the number of frames of each autotile and the width of the widest autotile on the
+/// atlas from the widths of the autotile images.
+fn autotile_dimensions(
+    autotile_widths: impl Fn(usize) -> Option<u32>,
+) -> ([u32; AUTOTILE_AMOUNT as usize], u32) {
+    let autotile_frames = std::array::from_fn(|i| {
+        autotile_widths(i)
+            // Why unwrap with a width of 96? Even though the autotile doesn't exist, it still has an effective width on the atlas of one frame.
+            // Further rendering code breaks down with an autotile width of 0, anyway.
+            .unwrap_or(96)
+            / 96
+    });
+
+    let autotile_width = autotile_frames
+        .iter()
+        .map(|f| f * AUTOTILE_FRAME_WIDTH)
+        .max()
+        .unwrap_or(AUTOTILE_FRAME_WIDTH);
+
+    (autotile_frames, autotile_width)
+}
+
+/// Runs `f(source, dest)` for every 16x16 piece of the autotile with the given index that needs to
+/// be copied into the atlas, where `source` is the position of the piece in the autotile image
+/// and `dest` is the position of the piece in the atlas.
+fn for_each_autotile_piece(index: usize, frames: u32, mut f: impl FnMut((u32, u32), (u32, u32))) {
+    let frame_y = index as u32 * AUTOTILE_ROW_HEIGHT;
+    for frame in 0..frames {
+        let frame_x = frame * AUTOTILE_FRAME_WIDTH;
+        for (index, autotile) in AUTOTILES.into_iter().enumerate() {
+            // Reset x every 8 tiles
+            let autotile_x = index as u32 % AUTOTILE_FRAME_COLS * TILE_SIZE;
+            // Increase y every 8 tiles
+            let autotile_y = index as u32 / AUTOTILE_FRAME_COLS * TILE_SIZE;
+
+            for (index, sub_tile) in autotile.into_iter().enumerate() {
+                let sub_tile_x = index as u32 % 2 * 16;
+                let sub_tile_y = index as u32 / 2 * 16;
+
+                let tile_x = sub_tile % 6 * 16;
+                let tile_y = sub_tile / 6 * 16;
+
+                f(
+                    (tile_x + frame * 96, tile_y),
+                    (
+                        frame_x + autotile_x + sub_tile_x,
+                        frame_y + autotile_y + sub_tile_y,
+                    ),
+                );
+            }
+        }
+    }
+}
+
+/// Loads the tileset image of a tileset, if it has one.
+fn load_tileset_image(
+    filesystem: &impl luminol_filesystem::FileSystem,
+    tileset: &luminol_data::rpg::Tileset,
+) -> anyhow::Result<Option<image::RgbaImage>> {
+    tileset
+        .tileset_name
+        .as_ref()
+        .map(|tileset_name| {
+            let file =
+                filesystem.read(camino::Utf8Path::new("Graphics/Tilesets").join(tileset_name))?;
+            Ok(image::load_from_memory(&file)?.to_rgba8())
+        })
+        .transpose()
+}
+
+/// Calculates the position of the top-left corner of a tile in an atlas, in pixels.
+fn atlas_tile_position(tile: i16, autotile_width: u32) -> (u32, u32) {
+    let tile_u32 = if tile < 0 { 0 } else { tile as u32 };
+
+    let is_autotile = tile_u32 < TOTAL_AUTOTILE_ID_AMOUNT;
+    let max_frame_count = autotile_width / AUTOTILE_FRAME_WIDTH;
+    let max_tiles_under_autotiles = max_frame_count * ROWS_UNDER_AUTOTILES_TIMES_COLUMNS;
+    let is_under_autotiles =
+        !is_autotile && tile_u32 - TOTAL_AUTOTILE_ID_AMOUNT < max_tiles_under_autotiles;
+
+    if !(AUTOTILE_ID_AMOUNT
+        ..(MAX_SIZE / TILESET_WIDTH) * ROWS_UNDER_AUTOTILES_TIMES_COLUMNS
+            + TOTAL_AUTOTILE_ID_AMOUNT)
+        .contains(&tile_u32)
+    {
+        (0, 0)
+    } else if is_autotile {
+        (
+            (tile_u32 - AUTOTILE_ID_AMOUNT) % AUTOTILE_FRAME_COLS * TILE_SIZE,
+            (tile_u32 - AUTOTILE_ID_AMOUNT) / AUTOTILE_FRAME_COLS * TILE_SIZE,
+        )
+    } else if is_under_autotiles {
+        (
+            (tile_u32 % TILESET_COLUMNS
+                + (tile_u32 - TOTAL_AUTOTILE_ID_AMOUNT) / ROWS_UNDER_AUTOTILES_TIMES_COLUMNS
+                    * TILESET_COLUMNS)
+                * TILE_SIZE,
+            ((tile_u32 - TOTAL_AUTOTILE_ID_AMOUNT) / TILESET_COLUMNS % ROWS_UNDER_AUTOTILES
+                + TOTAL_AUTOTILE_ROWS)
+                * TILE_SIZE,
+        )
+    } else {
+        (
+            (tile_u32 % TILESET_COLUMNS
+                + ((tile_u32 - TOTAL_AUTOTILE_ID_AMOUNT - max_tiles_under_autotiles)
+                    / (MAX_SIZE / TILE_SIZE * TILESET_COLUMNS)
+                    + max_frame_count)
+                    * TILESET_COLUMNS)
+                * TILE_SIZE,
+            (tile_u32 - TOTAL_AUTOTILE_ID_AMOUNT - max_tiles_under_autotiles) / TILESET_COLUMNS
+                % (MAX_SIZE / TILE_SIZE)
+                * TILE_SIZE,
+        )
+    }
+}
+
 impl Atlas {
     pub fn new(
         graphics_state: &GraphicsState,
         filesystem: &impl luminol_filesystem::FileSystem,
         tileset: &luminol_data::rpg::Tileset,
     ) -> anyhow::Result<Atlas> {
-        let tileset_img = match &tileset.tileset_name {
-            Some(tileset_name) => {
-                let file = filesystem
-                    .read(camino::Utf8Path::new("Graphics/Tilesets").join(tileset_name))?;
-                let tileset_img = image::load_from_memory(&file)?;
-                Some(tileset_img.to_rgba8())
-            }
-            None => None,
-        };
+        let tileset_img = load_tileset_image(filesystem, tileset)?;
 
         let tileset_height = tileset_img
             .as_ref()
@@ -88,21 +329,8 @@ impl Atlas {
             .try_collect()
             .context("while loading atlas autotiles")?;
 
-        let autotile_frames = std::array::from_fn(|i| {
-            autotiles[i]
-                .as_deref()
-                .map(Texture::width)
-                // Why unwrap with a width of 96? Even though the autotile doesn't exist, it still has an effective width on the atlas of one frame.
-                // Further rendering code breaks down with an autotile width of 0, anyway.
-                .unwrap_or(96)
-                / 96
-        });
-
-        let autotile_width = autotile_frames
-            .iter()
-            .map(|f| f * AUTOTILE_FRAME_WIDTH)
-            .max()
-            .unwrap_or(AUTOTILE_FRAME_WIDTH);
+        let (autotile_frames, autotile_width) =
+            autotile_dimensions(|i| autotiles[i].as_deref().map(Texture::width));
 
         let mut encoder = graphics_state.render_state.device.create_command_encoder(
             &wgpu::CommandEncoderDescriptor {
@@ -110,34 +338,7 @@ impl Atlas {
             },
         );
 
-        let width;
-        let height;
-
-        let rows_under;
-        let rows_side;
-        if TOTAL_AUTOTILE_HEIGHT + tileset_height < MAX_SIZE {
-            width = autotile_width.max(TILESET_WIDTH); // in case we have less autotiles frames than the tileset is wide
-            height = TOTAL_AUTOTILE_HEIGHT + tileset_height; // we're sure that the tileset can fit into the atlas just fine
-
-            rows_under = 1;
-            rows_side = 0;
-        } else {
-            // Find out how many rows are under autotiles
-            // Take the smallest of these
-            rows_under = u32::min(
-                // How many times can the tileset fit under the autotiles?
-                tileset_height.div_ceil(HEIGHT_UNDER_AUTOTILES),
-                // How many columns of autotiles are there
-                autotile_width.div_ceil(TILESET_WIDTH),
-            );
-            // Find out how many rows would fit on the side by dividing the left over height by MAX_SIZE
-            rows_side = tileset_height
-                .saturating_sub(rows_under * HEIGHT_UNDER_AUTOTILES)
-                .div_ceil(MAX_SIZE);
-
-            width = ((rows_under + rows_side) * TILESET_WIDTH).max(autotile_width);
-            height = MAX_SIZE;
-        }
+        let layout = AtlasLayout::new(autotile_width, tileset_height);
 
         let atlas_texture =
             graphics_state
@@ -146,8 +347,8 @@ impl Atlas {
                 .create_texture(&wgpu::TextureDescriptor {
                     label: Some("tileset_atlas"),
                     size: wgpu::Extent3d {
-                        width,
-                        height,
+                        width: layout.width,
+                        height: layout.height,
                         depth_or_array_layers: 1,
                     },
                     dimension: wgpu::TextureDimension::D2,
@@ -171,40 +372,20 @@ impl Atlas {
         {
             let mut autotile_copy = autotile_texture.texture.as_image_copy();
 
-            let frame_y = index as u32 * AUTOTILE_ROW_HEIGHT;
-            for frame in 0..autotile_frames[index] {
-                let frame_x = frame * AUTOTILE_FRAME_WIDTH;
-                for (index, autotile) in AUTOTILES.into_iter().enumerate() {
-                    // Reset x every 8 tiles
-                    let autotile_x = index as u32 % AUTOTILE_FRAME_COLS * TILE_SIZE;
-                    // Increase y every 8 tiles
-                    let autotile_y = index as u32 / AUTOTILE_FRAME_COLS * TILE_SIZE;
-
-                    for (index, sub_tile) in autotile.into_iter().enumerate() {
-                        let sub_tile_x = index as u32 % 2 * 16;
-                        let sub_tile_y = index as u32 / 2 * 16;
-
-                        atlas_copy.origin.x = frame_x + autotile_x + sub_tile_x;
-                        atlas_copy.origin.y = frame_y + autotile_y + sub_tile_y;
-
-                        let tile_x = sub_tile % 6 * 16;
-                        let tile_y = sub_tile / 6 * 16;
-
-                        autotile_copy.origin.x = tile_x + frame * 96;
-                        autotile_copy.origin.y = tile_y;
-
-                        encoder.copy_texture_to_texture(
-                            autotile_copy,
-                            atlas_copy,
-                            wgpu::Extent3d {
-                                width: 16,
-                                height: 16,
-                                depth_or_array_layers: 1,
-                            },
-                        );
-                    }
-                }
-            }
+            for_each_autotile_piece(index, autotile_frames[index], |source, dest| {
+                (atlas_copy.origin.x, atlas_copy.origin.y) = dest;
+                (autotile_copy.origin.x, autotile_copy.origin.y) = source;
+
+                encoder.copy_texture_to_texture(
+                    autotile_copy,
+                    atlas_copy,
+                    wgpu::Extent3d {
+                        width: 16,
+                        height: 16,
+                        depth_or_array_layers: 1,
+                    },
+                );
+            });
         }
 
         graphics_state
@@ -212,44 +393,14 @@ impl Atlas {
             .queue
             .submit(std::iter::once(encoder.finish()));
 
-        atlas_copy.origin.x = 0;
         if let Some(tileset_img) = tileset_img {
-            if TOTAL_AUTOTILE_HEIGHT + tileset_height < MAX_SIZE {
+            for (y, height, dest) in layout.tileset_strips(tileset_height) {
                 write_texture_region(
                     &graphics_state.render_state,
                     &atlas_texture,
-                    tileset_img.view(0, 0, TILESET_WIDTH, tileset_height),
-                    (0, TOTAL_AUTOTILE_HEIGHT),
+                    tileset_img.view(0, y, TILESET_WIDTH, height),
+                    dest,
                 )
-            } else {
-                for i in 0..rows_under {
-                    let y = HEIGHT_UNDER_AUTOTILES * i;
-                    let height = if y + HEIGHT_UNDER_AUTOTILES > tileset_height {
-                        tileset_height - y
-                    } else {
-                        HEIGHT_UNDER_AUTOTILES
-                    };
-                    write_texture_region(
-                        &graphics_state.render_state,
-                        &atlas_texture,
-                        tileset_img.view(0, y, TILESET_WIDTH, height),
-                        (TILESET_WIDTH * i, TOTAL_AUTOTILE_HEIGHT),
-                    )
-                }
-                for i in 0..rows_side {
-                    let y = (HEIGHT_UNDER_AUTOTILES * rows_under) + MAX_SIZE * i;
-                    let height = if y + MAX_SIZE > tileset_height {
-                        tileset_height - y
-                    } else {
-                        MAX_SIZE
-                    };
-                    write_texture_region(
-                        &graphics_state.render_state,
-                        &atlas_texture,
-                        tileset_img.view(0, y, TILESET_WIDTH, height),
-                        (TILESET_WIDTH * (rows_under + i), 0),
-                    )
-                }
             }
         }
 
@@ -266,49 +417,8 @@ impl Atlas {
     }
 
     pub fn calc_quad(&self, tile: i16) -> Quad {
-        let tile_u32 = if tile < 0 { 0 } else { tile as u32 };
-
-        let is_autotile = tile_u32 < TOTAL_AUTOTILE_ID_AMOUNT;
-        let max_frame_count = self.autotile_width / AUTOTILE_FRAME_WIDTH;
-        let max_tiles_under_autotiles = max_frame_count * ROWS_UNDER_AUTOTILES_TIMES_COLUMNS;
-        let is_under_autotiles =
-            !is_autotile && tile_u32 - TOTAL_AUTOTILE_ID_AMOUNT < max_tiles_under_autotiles;
-
-        let atlas_tile_position = if !(AUTOTILE_ID_AMOUNT
-            ..(MAX_SIZE / TILESET_WIDTH) * ROWS_UNDER_AUTOTILES_TIMES_COLUMNS
-                + TOTAL_AUTOTILE_ID_AMOUNT)
-            .contains(&tile_u32)
-        {
-            egui::pos2(0., 0.)
-        } else if is_autotile {
-            egui::pos2(
-                ((tile_u32 - AUTOTILE_ID_AMOUNT) % AUTOTILE_FRAME_COLS * TILE_SIZE) as f32,
-                ((tile_u32 - AUTOTILE_ID_AMOUNT) / AUTOTILE_FRAME_COLS * TILE_SIZE) as f32,
-            )
-        } else if is_under_autotiles {
-            egui::pos2(
-                ((tile_u32 % TILESET_COLUMNS
-                    + (tile_u32 - TOTAL_AUTOTILE_ID_AMOUNT) / ROWS_UNDER_AUTOTILES_TIMES_COLUMNS
-                        * TILESET_COLUMNS)
-                    * TILE_SIZE) as f32,
-                (((tile_u32 - TOTAL_AUTOTILE_ID_AMOUNT) / TILESET_COLUMNS % ROWS_UNDER_AUTOTILES
-                    + TOTAL_AUTOTILE_ROWS)
-                    * TILE_SIZE) as f32,
-            )
-        } else {
-            egui::pos2(
-                ((tile_u32 % TILESET_COLUMNS
-                    + ((tile_u32 - TOTAL_AUTOTILE_ID_AMOUNT - max_tiles_under_autotiles)
-                        / (MAX_SIZE / TILE_SIZE * TILESET_COLUMNS)
-                        + max_frame_count)
-                        * TILESET_COLUMNS)
-                    * TILE_SIZE) as f32,
-                ((tile_u32 - TOTAL_AUTOTILE_ID_AMOUNT - max_tiles_under_autotiles)
-                    / TILESET_COLUMNS
-                    % (MAX_SIZE / TILE_SIZE)
-                    * TILE_SIZE) as f32,
-            )
-        };
+        let (x, y) = atlas_tile_position(tile, self.autotile_width);
+        let atlas_tile_position = egui::pos2(x as f32, y as f32);
 
         Quad::new(
             egui::Rect::from_min_size(
@@ -323,6 +433,109 @@ impl Atlas {
             0.0,
         )
     }
+
+    /// Returns the area a tile takes up in the atlas texture, in pixels, without the adjustments
+    /// [`Atlas::calc_quad`] makes to avoid texture bleeding.
+    ///
+    /// Autotiles are shown at the given animation frame, which wraps around the same way it does
+    /// on the map. Other tiles ignore it.
+    pub fn tile_rect(&self, tile: i16, frame: u32) -> egui::Rect {
+        let (mut x, y) = atlas_tile_position(tile, self.autotile_width);
+        if let TileSource::Autotile { index, .. } = TileSource::new(tile) {
+            x += frame % self.autotile_frames[index].max(1) * AUTOTILE_FRAME_WIDTH;
+        }
+        egui::Rect::from_min_size(
+            egui::pos2(x as f32, y as f32),
+            egui::vec2(TILE_SIZE as f32, TILE_SIZE as f32),
+        )
+    }
+}
+
+impl AtlasImage {
+    /// Builds the atlas for a tileset in memory. Unlike [`Atlas::new`], this doesn't need a GPU.
+    pub fn new(
+        filesystem: &impl luminol_filesystem::FileSystem,
+        tileset: &luminol_data::rpg::Tileset,
+    ) -> anyhow::Result<AtlasImage> {
+        let tileset_img = load_tileset_image(filesystem, tileset)?;
+
+        let tileset_height = tileset_img
+            .as_ref()
+            .map(|i| i.height() / TILE_SIZE * TILE_SIZE)
+            .unwrap_or(256);
+
+        let autotiles: Vec<_> = tileset
+            .autotile_names
+            .iter()
+            .map(|s| -> anyhow::Result<_> {
+                if s.is_empty() {
+                    Ok(None)
+                } else {
+                    let file =
+                        filesystem.read(camino::Utf8Path::new("Graphics/Autotiles").join(s))?;
+                    Ok(Some(image::load_from_memory(&file)?.to_rgba8()))
+                }
+            })
+            .try_collect()
+            .context("while loading atlas autotiles")?;
+
+        let (autotile_frames, autotile_width) =
+            autotile_dimensions(|i| autotiles[i].as_ref().map(image::RgbaImage::width));
+
+        let layout = AtlasLayout::new(autotile_width, tileset_height);
+        let mut image = image::RgbaImage::new(layout.width, layout.height);
+
+        for (index, autotile_img) in autotiles
+            .iter()
+            .enumerate()
+            .flat_map(|(index, autotile_img)| autotile_img.as_ref().map(|img| (index, img)))
+        {
+            for_each_autotile_piece(index, autotile_frames[index], |(x, y), (dest_x, dest_y)| {
+                // Autotile images that are too small are missing some of their pieces, which are
+                // left transparent
+                if x + 16 <= autotile_img.width() && y + 16 <= autotile_img.height() {
+                    image::imageops::replace(
+                        &mut image,
+                        &*autotile_img.view(x, y, 16, 16),
+                        dest_x as i64,
+                        dest_y as i64,
+                    );
+                }
+            });
+        }
+
+        if let Some(tileset_img) = tileset_img {
+            for (y, height, (dest_x, dest_y)) in layout.tileset_strips(tileset_height) {
+                image::imageops::replace(
+                    &mut image,
+                    &*tileset_img.view(0, y, TILESET_WIDTH, height),
+                    dest_x as i64,
+                    dest_y as i64,
+                );
+            }
+        }
+
+        Ok(AtlasImage {
+            image,
+            autotile_width,
+            tileset_height,
+            autotile_frames,
+        })
+    }
+
+    /// Returns the 32x32 image of a tile from the first frame of the atlas, or `None` if the tile
+    /// isn't in the atlas.
+    pub fn tile(&self, tile: i16) -> Option<image::SubImage<&image::RgbaImage>> {
+        if matches!(
+            TileSource::new(tile),
+            TileSource::Empty | TileSource::OutOfRange
+        ) {
+            return None;
+        }
+        let (x, y) = atlas_tile_position(tile, self.autotile_width);
+        (x + TILE_SIZE <= self.image.width() && y + TILE_SIZE <= self.image.height())
+            .then(|| self.image.view(x, y, TILE_SIZE, TILE_SIZE))
+    }
 }
 
 fn write_texture_region<P>(
@@ -22,7 +22,7 @@ use crate::{
     BindGroupBuilder, BindGroupLayoutBuilder, GraphicsState,
 };
 
-pub use atlas::Atlas;
+pub use atlas::{Atlas, AtlasImage, TileSource};
 
 use autotiles::Autotiles;
 use instance::Instances;
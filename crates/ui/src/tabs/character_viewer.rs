@@ -0,0 +1,324 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+use std::sync::Arc;
+
+use luminol_core::image_import::Kind;
+use luminol_data::BlendMode;
+use luminol_graphics::{character::WalkCycle, Character, Texture};
+use strum::IntoEnumIterator;
+
+/// The rows of a character sheet, from top to bottom.
+const DIRECTIONS: [&str; 4] = ["Down", "Left", "Right", "Up"];
+
+/// The character viewer.
+/// Previews character sheets walking in all four directions, and battlers, with the same
+/// hue, opacity and blending options events have.
+pub struct Tab {
+    kind: Kind,
+    files: Option<Vec<camino::Utf8PathBuf>>,
+    selected: Option<camino::Utf8PathBuf>,
+    texture: Option<Arc<Texture>>,
+    /// One sprite per direction for characters, or a single one for battlers.
+    sprites: Vec<Character>,
+
+    hue: i32,
+    opacity: i32,
+    blend_mode: BlendMode,
+    zoom: f32,
+    walk_cycle: WalkCycle,
+    paused: bool,
+    /// The pattern the sprites are currently showing.
+    shown_pattern: u32,
+    last_time: Option<f64>,
+}
+
+impl Default for Tab {
+    fn default() -> Self {
+        Self {
+            kind: Kind::Characters,
+            files: None,
+            selected: None,
+            texture: None,
+            sprites: Vec::new(),
+            hue: 0,
+            opacity: 255,
+            blend_mode: BlendMode::Normal,
+            zoom: 2.,
+            walk_cycle: WalkCycle::default(),
+            paused: false,
+            shown_pattern: 0,
+            last_time: None,
+        }
+    }
+}
+
+impl Tab {
+    fn list_files(&mut self, update_state: &mut luminol_core::UpdateState<'_>) {
+        let directory = self.kind.directory();
+        match update_state.filesystem.read_dir(&directory) {
+            Ok(entries) => {
+                let mut files: Vec<_> = entries
+                    .into_iter()
+                    .filter(|entry| entry.metadata.is_file)
+                    .filter_map(|entry| entry.path.file_stem().map(camino::Utf8PathBuf::from))
+                    .collect();
+                files.sort_unstable();
+                files.dedup();
+                self.files = Some(files);
+            }
+            Err(e) => {
+                self.files = Some(Vec::new());
+                update_state
+                    .toasts
+                    .error(format!("Error listing {directory}: {e}"));
+            }
+        }
+    }
+
+    fn load(&mut self, update_state: &mut luminol_core::UpdateState<'_>) {
+        self.texture = None;
+        self.sprites.clear();
+        let Some(ref name) = self.selected else {
+            return;
+        };
+
+        match update_state.graphics.texture_loader.load_now_dir(
+            update_state.filesystem,
+            self.kind.directory(),
+            name,
+        ) {
+            Ok(texture) => {
+                self.texture = Some(texture);
+                self.rebuild_sprites(&update_state.graphics);
+            }
+            Err(e) => update_state
+                .toasts
+                .error(format!("Error loading {name}: {e}")),
+        }
+    }
+
+    /// Recreates the sprites, which is needed whenever the blend mode changes.
+    fn rebuild_sprites(&mut self, graphics_state: &luminol_graphics::GraphicsState) {
+        let Some(ref texture) = self.texture else {
+            return;
+        };
+
+        let (grid, count) = match self.kind {
+            Kind::Characters => (4, DIRECTIONS.len()),
+            _ => (1, 1),
+        };
+        self.sprites = (0..count)
+            .map(|_| {
+                Character::new(
+                    graphics_state,
+                    texture.clone(),
+                    grid,
+                    grid,
+                    self.blend_mode,
+                    self.hue,
+                    self.opacity,
+                )
+            })
+            .collect();
+        self.shown_pattern = 0;
+        self.update_frames(graphics_state);
+    }
+
+    fn update_frames(&self, graphics_state: &luminol_graphics::GraphicsState) {
+        if self.kind != Kind::Characters {
+            return;
+        }
+        for (row, sprite) in self.sprites.iter().enumerate() {
+            sprite.set_frame(&graphics_state.render_state, self.shown_pattern, row as u32);
+        }
+    }
+
+    fn show_controls(&mut self, ui: &mut egui::Ui, update_state: &luminol_core::UpdateState<'_>) {
+        let render_state = &update_state.graphics.render_state;
+
+        ui.horizontal_wrapped(|ui| {
+            if ui
+                .add(egui::Slider::new(&mut self.hue, 0..=359).text("Hue"))
+                .changed()
+            {
+                for sprite in &self.sprites {
+                    sprite.set_hue(render_state, self.hue);
+                }
+            }
+
+            if ui
+                .add(egui::Slider::new(&mut self.opacity, 0..=255).text("Opacity"))
+                .changed()
+            {
+                for sprite in &self.sprites {
+                    sprite.set_opacity(render_state, self.opacity);
+                }
+            }
+
+            let mut blend_changed = false;
+            egui::ComboBox::from_label("Blending")
+                .selected_text(self.blend_mode.to_string())
+                .show_ui(ui, |ui| {
+                    for mode in BlendMode::iter() {
+                        blend_changed |= ui
+                            .selectable_value(&mut self.blend_mode, mode, mode.to_string())
+                            .changed();
+                    }
+                });
+            if blend_changed {
+                self.rebuild_sprites(&update_state.graphics);
+            }
+
+            ui.add(egui::Slider::new(&mut self.zoom, 1.0..=4.0).text("Zoom"));
+        });
+
+        if self.kind == Kind::Characters {
+            ui.horizontal_wrapped(|ui| {
+                ui.add(egui::Slider::new(&mut self.walk_cycle.move_speed, 1..=6).text("Speed"));
+                ui.add(
+                    egui::Slider::new(&mut self.walk_cycle.move_frequency, 1..=6).text("Frequency"),
+                );
+                ui.checkbox(&mut self.walk_cycle.step_anime, "Stop animation");
+                ui.checkbox(&mut self.paused, "Pause");
+            });
+        }
+    }
+}
+
+impl luminol_core::Tab for Tab {
+    fn name(&self, _update_state: &luminol_core::UpdateState<'_>) -> String {
+        match self.selected {
+            Some(ref name) => format!("Character Viewer: {name}"),
+            None => "Character Viewer".to_string(),
+        }
+    }
+
+    fn id(&self) -> egui::Id {
+        egui::Id::new("luminol_character_viewer_tab")
+    }
+
+    fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        update_state: &mut luminol_core::UpdateState<'_>,
+        _is_focused: bool,
+    ) {
+        if self.files.is_none() {
+            self.list_files(update_state);
+        }
+
+        egui::SidePanel::left("luminol_character_viewer_files").show_inside(ui, |ui| {
+            ui.horizontal(|ui| {
+                let mut kind_changed = false;
+                for kind in [Kind::Characters, Kind::Battlers] {
+                    kind_changed |= ui
+                        .selectable_value(&mut self.kind, kind, kind.to_string())
+                        .changed();
+                }
+                if kind_changed {
+                    self.selected = None;
+                    self.load(update_state);
+                    self.list_files(update_state);
+                }
+
+                if ui.button("Refresh").clicked() {
+                    self.list_files(update_state);
+                }
+            });
+
+            ui.separator();
+
+            let mut clicked = None;
+            egui::ScrollArea::vertical()
+                .auto_shrink([false; 2])
+                .show(ui, |ui| {
+                    for file in self.files.iter().flatten() {
+                        let selected = self.selected.as_ref() == Some(file);
+                        if ui.selectable_label(selected, file.as_str()).clicked() {
+                            clicked = Some(file.clone());
+                        }
+                    }
+                });
+            if clicked.is_some() {
+                self.selected = clicked;
+                self.load(update_state);
+            }
+        });
+
+        egui::CentralPanel::default().show_inside(ui, |ui| {
+            self.show_controls(ui, update_state);
+
+            ui.separator();
+
+            let Some(ref texture) = self.texture else {
+                ui.label("Select a graphic to preview it");
+                return;
+            };
+
+            let time = ui.input(|i| i.time);
+            let animating = self.kind == Kind::Characters && !self.paused;
+            if animating {
+                if let Some(last_time) = self.last_time {
+                    self.walk_cycle.update(time - last_time);
+                }
+                if self.walk_cycle.pattern() != self.shown_pattern {
+                    self.shown_pattern = self.walk_cycle.pattern();
+                    self.update_frames(&update_state.graphics);
+                }
+                ui.ctx().request_repaint();
+            }
+            self.last_time = animating.then_some(time);
+
+            ui.label(format!("{} × {} px", texture.width(), texture.height()));
+
+            egui::ScrollArea::both().show(ui, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    for (index, sprite) in self.sprites.iter().enumerate() {
+                        ui.vertical(|ui| {
+                            if self.kind == Kind::Characters {
+                                ui.label(DIRECTIONS[index]);
+                            }
+
+                            let (rect, _) = ui.allocate_exact_size(
+                                sprite.frame_size * self.zoom,
+                                egui::Sense::hover(),
+                            );
+                            ui.painter().rect_stroke(
+                                rect,
+                                0.,
+                                ui.visuals().widgets.noninteractive.bg_stroke,
+                            );
+                            sprite.paint(update_state.graphics.clone(), ui.painter(), rect);
+                        });
+                    }
+                });
+            });
+        });
+    }
+
+    fn requires_filesystem(&self) -> bool {
+        true
+    }
+}
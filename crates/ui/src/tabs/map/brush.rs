@@ -23,11 +23,34 @@
 // Program grant you additional permission to convey the resulting work.
 
 use itertools::Itertools;
+use rand::Rng;
 use std::usize;
 
 impl super::Tab {
+    /// When starting to draw a shape, save the current layer into the brush layer cache.
+    /// Otherwise, restore the layer from the cache so the shape can be redrawn from scratch.
+    fn save_or_restore_brush_layer(&mut self, map: &mut luminol_data::rpg::Map, tile_layer: usize) {
+        if !self.drawing_shape {
+            // Save the current layer
+            for x in 0..map.data.xsize() {
+                for y in 0..map.data.ysize() {
+                    self.brush_layer_cache[x + y * map.data.xsize()] = map.data[(x, y, tile_layer)];
+                }
+            }
+            self.drawing_shape = true;
+        } else {
+            // Restore the previously stored state of the current layer
+            for y in 0..map.data.ysize() {
+                for x in 0..map.data.xsize() {
+                    map.data[(x, y, tile_layer)] = self.brush_layer_cache[x + y * map.data.xsize()];
+                }
+            }
+        }
+    }
+
     pub(super) fn handle_brush(
         &mut self,
+        update_state: &luminol_core::UpdateState<'_>,
         map_x: usize,
         map_y: usize,
         tile_layer: usize,
@@ -132,24 +155,7 @@ impl super::Tab {
             }
 
             luminol_core::Pencil::Rectangle => {
-                if !self.drawing_shape {
-                    // Save the current layer
-                    for x in 0..map.data.xsize() {
-                        for y in 0..map.data.ysize() {
-                            self.brush_layer_cache[x + y * map.data.xsize()] =
-                                map.data[(x, y, tile_layer)];
-                        }
-                    }
-                    self.drawing_shape = true;
-                } else {
-                    // Restore the previously stored state of the current layer
-                    for y in 0..map.data.ysize() {
-                        for x in 0..map.data.xsize() {
-                            map.data[(x, y, tile_layer)] =
-                                self.brush_layer_cache[x + y * map.data.xsize()];
-                        }
-                    }
-                }
+                self.save_or_restore_brush_layer(map, tile_layer);
 
                 if let Some(drawing_shape_pos) = self.drawing_shape_pos {
                     let bounding_rect = egui::Rect::from_two_pos(drawing_shape_pos, map_pos);
@@ -172,24 +178,7 @@ impl super::Tab {
             }
 
             luminol_core::Pencil::Circle => {
-                if !self.drawing_shape {
-                    // Save the current layer
-                    for x in 0..map.data.xsize() {
-                        for y in 0..map.data.ysize() {
-                            self.brush_layer_cache[x + y * map.data.xsize()] =
-                                map.data[(x, y, tile_layer)];
-                        }
-                    }
-                    self.drawing_shape = true;
-                } else {
-                    // Restore the previously stored state of the current layer
-                    for y in 0..map.data.ysize() {
-                        for x in 0..map.data.xsize() {
-                            map.data[(x, y, tile_layer)] =
-                                self.brush_layer_cache[x + y * map.data.xsize()];
-                        }
-                    }
-                }
+                self.save_or_restore_brush_layer(map, tile_layer);
 
                 // Use Bresenham's algorithm to draw the ellipse.
                 // We consider (x, y) to be the top-left corner of the tile at
@@ -318,6 +307,137 @@ impl super::Tab {
                     self.drawing_shape_pos = Some(map_pos);
                 }
             }
+
+            luminol_core::Pencil::Line => {
+                self.save_or_restore_brush_layer(map, tile_layer);
+
+                // Use Bresenham's algorithm to draw a line from the original tile to the current
+                // tile.
+                if let Some(drawing_shape_pos) = self.drawing_shape_pos {
+                    let mut x = drawing_shape_pos.x as isize;
+                    let mut y = drawing_shape_pos.y as isize;
+                    let end_x = map_x as isize;
+                    let end_y = map_y as isize;
+
+                    let dx = (end_x - x).abs();
+                    let dy = -(end_y - y).abs();
+                    let step_x = if x < end_x { 1 } else { -1 };
+                    let step_y = if y < end_y { 1 } else { -1 };
+
+                    // The error term is the sum of the horizontal and vertical distances between
+                    // the current tile and the ideal line
+                    let mut error = dx + dy;
+
+                    loop {
+                        self.set_tile(
+                            map,
+                            self.tilepicker.get_tile_from_offset(
+                                x as i16 - drawing_shape_pos.x as i16,
+                                y as i16 - drawing_shape_pos.y as i16,
+                            ),
+                            (x as usize, y as usize, tile_layer),
+                        );
+
+                        if x == end_x && y == end_y {
+                            break;
+                        }
+
+                        let error2 = 2 * error;
+                        if error2 >= dy {
+                            error += dy;
+                            x += step_x;
+                        }
+                        if error2 <= dx {
+                            error += dx;
+                            y += step_y;
+                        }
+                    }
+                } else {
+                    self.drawing_shape_pos = Some(map_pos);
+                }
+            }
+
+            luminol_core::Pencil::Scatter => {
+                if self.drawing_shape_pos.is_none() {
+                    self.drawing_shape_pos = Some(map_pos);
+                }
+
+                // Only scatter tiles when the cursor moves onto a different tile, otherwise
+                // holding the cursor still would keep filling in the area around it
+                if self.scatter_last_pos != Some(map_pos) {
+                    self.scatter_last_pos = Some(map_pos);
+
+                    let mut rng = rand::thread_rng();
+                    let radius = (self.scatter_size - 1) / 2;
+                    let min_x = map_x.saturating_sub(radius);
+                    let min_y = map_y.saturating_sub(radius);
+                    let max_x = (map_x + self.scatter_size - 1 - radius).min(map.data.xsize() - 1);
+                    let max_y = (map_y + self.scatter_size - 1 - radius).min(map.data.ysize() - 1);
+
+                    for (y, x) in (min_y..=max_y).cartesian_product(min_x..=max_x) {
+                        if rng.gen_ratio(self.scatter_density as u32, 100) {
+                            self.set_tile(
+                                map,
+                                self.tilepicker.get_tile_from_offset(
+                                    rng.gen_range(0..width),
+                                    rng.gen_range(0..height),
+                                ),
+                                (x, y, tile_layer),
+                            );
+                        }
+                    }
+                }
+            }
+
+            luminol_core::Pencil::ReplaceAll => {
+                // Only replace the tiles once per click
+                if self.drawing_shape_pos.is_none() {
+                    self.drawing_shape_pos = Some(map_pos);
+
+                    let layers = if self.replace_all_layers {
+                        0..map.data.zsize()
+                    } else {
+                        tile_layer..tile_layer + 1
+                    };
+
+                    for layer in layers {
+                        // Changes to the selected layer are recorded in the history when the
+                        // brush stroke ends, but changes to other layers have to be recorded here
+                        let old_layer =
+                            (layer != tile_layer).then(|| map.data.layer_as_slice(layer).to_vec());
+
+                        let positions = (0..map.data.ysize())
+                            .cartesian_product(0..map.data.xsize())
+                            .filter(|&(y, x)| {
+                                luminol_components::SelectedTile::from_id(map.data[(x, y, layer)])
+                                    == initial_tile
+                            })
+                            .collect_vec();
+                        for (y, x) in positions {
+                            self.set_tile(
+                                map,
+                                self.tilepicker.get_tile_from_offset(
+                                    x as i16 - map_x as i16,
+                                    y as i16 - map_y as i16,
+                                ),
+                                (x, y, layer),
+                            );
+                        }
+
+                        if let Some(old_layer) = old_layer {
+                            let entry =
+                                self.finish_layer_edit(update_state, map, layer, &old_layer);
+                            self.brush_history.extend(entry);
+                        }
+                    }
+                }
+            }
+
+            luminol_core::Pencil::Select => {
+                let selection_origin = *self.selection_origin.get_or_insert(map_pos);
+                self.view.selection_rect =
+                    Some(egui::Rect::from_two_pos(selection_origin, map_pos));
+            }
         };
     }
 }
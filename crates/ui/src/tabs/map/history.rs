@@ -21,3 +21,65 @@
 // it with Steamworks API by Valve Corporation, containing parts covered by
 // terms of the Steamworks API by Valve Corporation, the licensors of this
 // Program grant you additional permission to convey the resulting work.
+
+impl super::Tab {
+    /// Undoes or redoes a history entry and returns the entry that reverses it.
+    pub(super) fn apply_history_entry(
+        &mut self,
+        update_state: &luminol_core::UpdateState<'_>,
+        map: &mut luminol_data::rpg::Map,
+        entry: super::HistoryEntry,
+    ) -> super::HistoryEntry {
+        match entry {
+            super::HistoryEntry::Tiles { layer, mut delta } => {
+                for d in delta.iter_mut() {
+                    let position = (d.0, d.1, layer);
+                    let new_id = d.2;
+                    *d = (d.0, d.1, map.data[position]);
+                    map.data[position] = new_id;
+                    self.view
+                        .map
+                        .set_tile(&update_state.graphics.render_state, new_id, position);
+                }
+                super::HistoryEntry::Tiles { layer, delta }
+            }
+
+            super::HistoryEntry::EventMoved { id, x, y } => {
+                let event = map.events.get_mut(id).unwrap();
+                let new_entry = super::HistoryEntry::EventMoved {
+                    id,
+                    x: event.x,
+                    y: event.y,
+                };
+                event.x = x;
+                event.y = y;
+                new_entry
+            }
+
+            super::HistoryEntry::EventCreated(id) => {
+                let event = map.events.remove(id);
+                let sprites = self.view.events.try_remove(id).ok();
+                super::HistoryEntry::EventDeleted { event, sprites }
+            }
+
+            super::HistoryEntry::EventDeleted { event, sprites } => {
+                let id = event.id;
+                map.events.insert(id, event);
+                if let Some(sprites) = sprites {
+                    self.view.events.insert(id, sprites);
+                }
+                super::HistoryEntry::EventCreated(id)
+            }
+
+            // The entries are applied in reverse order. Collecting the reversing entries in the
+            // order they were created means applying them in reverse replays the original order.
+            super::HistoryEntry::Multiple(entries) => super::HistoryEntry::Multiple(
+                entries
+                    .into_iter()
+                    .rev()
+                    .map(|entry| self.apply_history_entry(update_state, map, entry))
+                    .collect(),
+            ),
+        }
+    }
+}
@@ -46,7 +46,9 @@ use itertools::Itertools;
 
 mod brush;
 mod history;
+mod selection;
 mod util;
+mod walkability;
 
 pub struct Tab {
     /// ID of the map that is being edited.
@@ -72,6 +74,33 @@ pub struct Tab {
     /// this is set to the position of the original tile we began drawing on
     drawing_shape_pos: Option<egui::Pos2>,
 
+    /// The percentage of tiles the scatter brush fills in
+    scatter_density: u8,
+    /// The width and height of the area the scatter brush fills in
+    scatter_size: usize,
+    /// The position of the tile the scatter brush last filled in the area around
+    scatter_last_pos: Option<egui::Pos2>,
+    /// Whether the replace all brush replaces tiles in every layer instead of only the selected one
+    replace_all_layers: bool,
+    /// History entries for changes a brush made to layers other than the selected one. These are
+    /// combined with the changes to the selected layer when the brush stroke ends.
+    brush_history: Vec<HistoryEntry>,
+
+    /// When dragging with the selection tool, this is set to the position of the tile the
+    /// selection was started on
+    selection_origin: Option<egui::Pos2>,
+    /// Whether the selection tool selects every layer instead of only the selected one
+    selection_all_layers: bool,
+    /// Whether the selection tool also selects the events inside of the selection
+    selection_events: bool,
+
+    /// Whether the walkability overlay is shown
+    walkability_enabled: bool,
+    /// The tile the walkability analysis starts from
+    walkability_start: Option<(usize, usize)>,
+    /// The tile the walkability overlay shows the shortest path to
+    walkability_goal: Option<(usize, usize)>,
+
     /// Undo history
     history: VecDeque<HistoryEntry>,
     /// When operations are undone, they are put here so that they can be redone
@@ -104,6 +133,8 @@ enum HistoryEntry {
         event: luminol_data::rpg::Event,
         sprites: Option<(luminol_graphics::Event, luminol_graphics::Event)>,
     },
+    /// Contains entries created by a single operation that are undone and redone together.
+    Multiple(Vec<HistoryEntry>),
 }
 
 impl Tab {
@@ -124,7 +155,7 @@ impl Tab {
         let tileset = &tilesets.data[map.tileset_id];
 
         let mut passages = luminol_data::Table2::new(map.data.xsize(), map.data.ysize());
-        luminol_graphics::collision::calculate_passages(
+        luminol_data::tilemap::calculate_passages(
             &tileset.passages,
             &tileset.priorities,
             &map.data,
@@ -151,6 +182,20 @@ impl Tab {
             brush_layer_cache: vec![0; map.data.xsize() * map.data.ysize()],
             drawing_shape_pos: None,
 
+            scatter_density: 25,
+            scatter_size: 3,
+            scatter_last_pos: None,
+            replace_all_layers: false,
+            brush_history: Vec::new(),
+
+            selection_origin: None,
+            selection_all_layers: false,
+            selection_events: true,
+
+            walkability_enabled: false,
+            walkability_start: None,
+            walkability_goal: None,
+
             history: VecDeque::with_capacity(HISTORY_SIZE),
             redo_history: Vec::with_capacity(HISTORY_SIZE),
             tilemap_undo_cache: vec![0; map.data.xsize() * map.data.ysize()],
@@ -190,6 +235,10 @@ impl luminol_core::Tab for Tab {
         update_state: &mut luminol_core::UpdateState<'_>,
         is_focused: bool,
     ) {
+        let mut copy_clicked = false;
+        let mut cut_clicked = false;
+        let mut paste_clicked = false;
+
         // Display the toolbar.
         egui::TopBottomPanel::top(format!("map_{}_toolbar", self.id)).show_inside(ui, |ui| {
             ui.horizontal_wrapped(|ui| {
@@ -270,11 +319,141 @@ impl luminol_core::Tab for Tab {
                 )
                 .on_disabled_hover_text("Toggles darkening unselected layers");
 
-                /*
-                if ui.button("Save map preview").clicked() {
-                    self.tilemap.save_to_disk();
+                ui.separator();
+
+                if ui
+                    .checkbox(&mut self.walkability_enabled, "Walkability")
+                    .on_hover_text(
+                        "Highlight the tiles the player can walk to.\n\
+                        Right click a tile to start from it, \
+                        shift + right click a tile to show the shortest path to it",
+                    )
+                    .changed()
+                    && self.walkability_enabled
+                    && self.walkability_start.is_none()
+                {
+                    self.set_walkability_start_to_player(update_state);
                 }
 
+                if self.walkability_enabled {
+                    // Database ids are 0-based in memory, but map ids aren't
+                    let starts_on_map = update_state.data.system().start_map_id + 1 == self.id;
+                    if ui
+                        .add_enabled(starts_on_map, egui::Button::new("Player start"))
+                        .on_hover_text("Start from the player's starting position")
+                        .on_disabled_hover_text("The player doesn't start on this map")
+                        .clicked()
+                    {
+                        self.set_walkability_start_to_player(update_state);
+                    }
+                    if ui
+                        .add_enabled(
+                            self.walkability_goal.is_some(),
+                            egui::Button::new("Clear path"),
+                        )
+                        .clicked()
+                    {
+                        self.walkability_goal = None;
+                    }
+
+                    if self.walkability_start.is_none() {
+                        ui.label("Right click a tile to start from");
+                    }
+
+                    if let Some(walkability) = &self.view.walkability {
+                        if self.walkability_goal.is_some() && walkability.path.is_empty() {
+                            ui.colored_label(ui.visuals().warn_fg_color, "No path found");
+                        }
+
+                        if !walkability.unreachable_events.is_empty() {
+                            let map = update_state.data.get_map(self.id);
+                            ui.colored_label(
+                                ui.visuals().warn_fg_color,
+                                format!(
+                                    "{} unreachable events",
+                                    walkability.unreachable_events.len()
+                                ),
+                            )
+                            .on_hover_ui(|ui| {
+                                for event in walkability
+                                    .unreachable_events
+                                    .iter()
+                                    .filter_map(|&id| map.events.get(id))
+                                {
+                                    ui.label(format!(
+                                        "{:0>3}: {} ({}, {})",
+                                        event.id, event.name, event.x, event.y
+                                    ));
+                                }
+                            });
+                        }
+                    }
+                }
+
+                if matches!(update_state.toolbar.pencil, luminol_core::Pencil::Scatter) {
+                    ui.separator();
+
+                    ui.add(
+                        egui::Slider::new(&mut self.scatter_density, 1..=100)
+                            .text("Density")
+                            .suffix("%"),
+                    )
+                    .on_hover_text("The percentage of tiles in the brush area that are filled in");
+                    ui.add(egui::Slider::new(&mut self.scatter_size, 1..=15).text("Size"))
+                        .on_hover_text("The width and height of the brush area");
+                }
+
+                if matches!(
+                    update_state.toolbar.pencil,
+                    luminol_core::Pencil::ReplaceAll
+                ) {
+                    ui.separator();
+
+                    ui.checkbox(&mut self.replace_all_layers, "Replace in all layers")
+                        .on_hover_text(
+                            "Replace the tile in every layer instead of only the selected layer",
+                        );
+                }
+
+                if matches!(update_state.toolbar.pencil, luminol_core::Pencil::Select) {
+                    ui.separator();
+
+                    ui.checkbox(&mut self.selection_all_layers, "Select all layers")
+                        .on_hover_text(
+                            "Select the tiles of every layer instead of only the selected layer",
+                        );
+                    ui.checkbox(&mut self.selection_events, "Select events")
+                        .on_hover_text("Also copy, cut and delete the events inside the selection");
+
+                    let has_selection = self.view.selection_rect.is_some();
+                    copy_clicked = ui
+                        .add_enabled(has_selection, egui::Button::new("Copy"))
+                        .clicked();
+                    cut_clicked = ui
+                        .add_enabled(has_selection, egui::Button::new("Cut"))
+                        .clicked();
+                    paste_clicked = ui
+                        .add_enabled(
+                            update_state.clipboard.map.is_some(),
+                            egui::Button::new("Paste"),
+                        )
+                        .on_hover_text("Paste at the top-left corner of the selection")
+                        .clicked();
+                }
+
+                ui.separator();
+
+                if ui
+                    .button("Export as PNG")
+                    .on_hover_text("Save an image of the whole map")
+                    .clicked()
+                {
+                    update_state
+                        .edit_windows
+                        .add_window(crate::windows::map_export::Window::new(self.id));
+                }
+
+                /*
                 if map.preview_move_route.is_some()
                 && ui.button("Clear move route preview").clicked()
                 {
@@ -305,6 +484,16 @@ impl luminol_core::Tab for Tab {
                 let tilesets = update_state.data.tilesets();
                 let tileset = &tilesets.data[map.tileset_id];
 
+                // The selection is only used by the selection tool in tile layers
+                if !matches!(update_state.toolbar.pencil, luminol_core::Pencil::Select)
+                    || !matches!(
+                        self.view.selected_layer,
+                        luminol_components::SelectedLayer::Tiles(_)
+                    )
+                {
+                    self.view.selection_rect = None;
+                }
+
                 // Save the state of the selected layer into the cache
                 if let luminol_components::SelectedLayer::Tiles(tile_layer) =
                     self.view.selected_layer
@@ -325,6 +514,18 @@ impl luminol_core::Tab for Tab {
                     is_focused,
                 );
 
+                // Right clicking picks the tiles used by the walkability overlay
+                if self.walkability_enabled && response.secondary_clicked() {
+                    if let Some(hover_tile) = self.view.hover_tile {
+                        let tile = (hover_tile.x as usize, hover_tile.y as usize);
+                        if ui.input(|i| i.modifiers.shift) {
+                            self.walkability_goal = Some(tile);
+                        } else {
+                            self.walkability_start = Some(tile);
+                        }
+                    }
+                }
+
                 let _layers_max = map.data.zsize();
                 let map_x = self.view.cursor_pos.x as i32;
                 let map_y = self.view.cursor_pos.y as i32;
@@ -368,6 +569,9 @@ impl luminol_core::Tab for Tab {
                         self.drawing_shape = false;
                     }
 
+                    self.selection_origin = None;
+                    self.scatter_last_pos = None;
+
                     if self.drawing_shape_pos.is_some() {
                         self.drawing_shape_pos = None;
                         let delta = (0..map.data.ysize())
@@ -378,14 +582,16 @@ impl luminol_core::Tab for Tab {
                                     .then_some((x, y, old_id))
                             })
                             .collect();
-                        self.push_to_history(
-                            update_state,
-                            &mut map,
-                            HistoryEntry::Tiles {
-                                layer: self.tilemap_undo_cache_layer,
-                                delta,
-                            },
-                        );
+                        let mut entry = HistoryEntry::Tiles {
+                            layer: self.tilemap_undo_cache_layer,
+                            delta,
+                        };
+                        if !self.brush_history.is_empty() {
+                            let mut entries = std::mem::take(&mut self.brush_history);
+                            entries.push(entry);
+                            entry = HistoryEntry::Multiple(entries);
+                        }
+                        self.push_to_history(update_state, &mut map, entry);
                     }
                 }
 
@@ -408,6 +614,7 @@ impl luminol_core::Tab for Tab {
                         && !ui.input(|i| i.modifiers.command)
                     {
                         self.handle_brush(
+                            update_state,
                             map_x as usize,
                             map_y as usize,
                             tile_layer,
@@ -415,6 +622,62 @@ impl luminol_core::Tab for Tab {
                             &mut map,
                         );
                     }
+
+                    if matches!(update_state.toolbar.pencil, luminol_core::Pencil::Select) {
+                        let is_copy_pressed = is_focused
+                            && ui.input(|i| {
+                                i.events.contains(&egui::Event::Copy)
+                                    || (i.modifiers.command && i.key_pressed(egui::Key::C))
+                            });
+                        let is_cut_pressed = is_focused
+                            && ui.input(|i| {
+                                i.events.contains(&egui::Event::Cut)
+                                    || (i.modifiers.command && i.key_pressed(egui::Key::X))
+                            });
+                        let is_paste_pressed = is_focused
+                            && ui.input(|i| {
+                                i.events.iter().any(|e| matches!(e, egui::Event::Paste(_)))
+                                    || (i.modifiers.command && i.key_pressed(egui::Key::V))
+                            });
+
+                        if copy_clicked || cut_clicked || is_copy_pressed || is_cut_pressed {
+                            if let Some(clipboard) = self.copy_selection(&map, tile_layer) {
+                                update_state.clipboard.map = Some(clipboard);
+                            }
+                        }
+
+                        if cut_clicked || is_cut_pressed || is_delete_pressed {
+                            self.delete_selection(update_state, &mut map, tile_layer);
+                        }
+
+                        if is_focused && ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                            self.view.selection_rect = None;
+                        }
+
+                        // Pasting with the keyboard pastes at the cursor, while the paste button
+                        // pastes at the top-left corner of the selection since the cursor is
+                        // outside of the map when the button is clicked
+                        let paste_pos = if paste_clicked {
+                            self.view
+                                .selection_rect
+                                .map_or(self.view.cursor_pos, |rect| rect.min)
+                        } else {
+                            self.view.cursor_pos
+                        };
+                        if paste_clicked || is_paste_pressed {
+                            if let Err(e) = self.paste_clipboard(
+                                update_state,
+                                &mut map,
+                                tileset,
+                                tile_layer,
+                                (paste_pos.x as usize, paste_pos.y as usize),
+                            ) {
+                                update_state
+                                    .toasts
+                                    .error(format!("Error pasting into the map: {e}"));
+                            }
+                        }
+                    }
                 } else if let Some(selected_event_id) = self.view.selected_event_id {
                     if response.double_clicked()
                         || (is_focused && ui.input(|i| i.key_pressed(egui::Key::Enter)))
@@ -506,66 +769,20 @@ impl luminol_core::Tab for Tab {
 
                 // Handle undo/redo keypresses
                 let is_dragged_by_primary = response.dragged_by(egui::PointerButton::Primary);
-                let is_undo_pressed = is_focused
-                    && ui.input(|i| {
-                        i.modifiers.command && !i.modifiers.shift && i.key_pressed(egui::Key::Z)
-                    });
-                let is_redo_pressed = is_focused
-                    && ui.input(|i| {
-                        i.modifiers.command
-                            && (i.modifiers.shift || i.key_pressed(egui::Key::Y))
-                            && (!i.modifiers.shift || i.key_pressed(egui::Key::Z))
-                    });
+                // Skip them if the user clicked in a window since focusing this tab
+                let is_focused =
+                    is_focused && update_state.history.is_focused(luminol_core::Tab::id(self));
+                let is_undo_pressed =
+                    is_focused && ui.input(luminol_core::history::is_undo_pressed);
+                let is_redo_pressed =
+                    is_focused && ui.input(luminol_core::history::is_redo_pressed);
                 if !is_dragged_by_primary && (is_undo_pressed || is_redo_pressed) {
-                    let new_entry = match if is_undo_pressed {
+                    let new_entry = if is_undo_pressed {
                         self.history.pop_back()
                     } else {
                         self.redo_history.pop()
-                    } {
-                        None => None,
-
-                        Some(HistoryEntry::Tiles { layer, mut delta }) => {
-                            for d in delta.iter_mut() {
-                                let position = (d.0, d.1, layer);
-                                let new_id = d.2;
-                                *d = (d.0, d.1, map.data[position]);
-                                map.data[position] = new_id;
-                                self.view.map.set_tile(
-                                    &update_state.graphics.render_state,
-                                    new_id,
-                                    position,
-                                );
-                            }
-                            Some(HistoryEntry::Tiles { layer, delta })
-                        }
-
-                        Some(HistoryEntry::EventMoved { id, x, y }) => {
-                            let event = map.events.get_mut(id).unwrap();
-                            let new_entry = Some(HistoryEntry::EventMoved {
-                                id,
-                                x: event.x,
-                                y: event.y,
-                            });
-                            event.x = x;
-                            event.y = y;
-                            new_entry
-                        }
-
-                        Some(HistoryEntry::EventCreated(id)) => {
-                            let event = map.events.remove(id);
-                            let sprites = self.view.events.try_remove(id).ok();
-                            Some(HistoryEntry::EventDeleted { event, sprites })
-                        }
-
-                        Some(HistoryEntry::EventDeleted { event, sprites }) => {
-                            let id = event.id;
-                            map.events.insert(id, event);
-                            if let Some(sprites) = sprites {
-                                self.view.events.insert(id, sprites);
-                            }
-                            Some(HistoryEntry::EventCreated(id))
-                        }
-                    };
+                    }
+                    .map(|entry| self.apply_history_entry(update_state, &mut map, entry));
 
                     if let Some(new_entry) = new_entry {
                         update_state.modified.set(true);
@@ -602,7 +819,7 @@ impl luminol_core::Tab for Tab {
                 }
 
                 // Update the collision preview
-                luminol_graphics::collision::calculate_passages(
+                luminol_data::tilemap::calculate_passages(
                     &tileset.passages,
                     &tileset.priorities,
                     &map.data,
@@ -625,6 +842,8 @@ impl luminol_core::Tab for Tab {
                         }
                     },
                 );
+
+                self.update_walkability(&map, tileset);
             })
         });
 
@@ -0,0 +1,289 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+impl super::Tab {
+    /// Returns the position and size of the selection as `(x, y, width, height)`, clamped to the
+    /// bounds of the map.
+    fn selection_bounds(
+        &self,
+        map: &luminol_data::rpg::Map,
+    ) -> Option<(usize, usize, usize, usize)> {
+        let rect = self.view.selection_rect?;
+        let x = rect.min.x as usize;
+        let y = rect.min.y as usize;
+        let max_x = (rect.max.x as usize).min(map.data.xsize().checked_sub(1)?);
+        let max_y = (rect.max.y as usize).min(map.data.ysize().checked_sub(1)?);
+        if x > max_x || y > max_y {
+            return None;
+        }
+        Some((x, y, max_x - x + 1, max_y - y + 1))
+    }
+
+    /// Returns the layers the selection tool operates on.
+    fn selection_layers(
+        &self,
+        map: &luminol_data::rpg::Map,
+        tile_layer: usize,
+    ) -> std::ops::Range<usize> {
+        if self.selection_all_layers {
+            0..map.data.zsize()
+        } else {
+            tile_layer..tile_layer + 1
+        }
+    }
+
+    /// Copy the tiles and events inside of the selection.
+    pub(super) fn copy_selection(
+        &self,
+        map: &luminol_data::rpg::Map,
+        tile_layer: usize,
+    ) -> Option<luminol_core::MapClipboard> {
+        let (x, y, width, height) = self.selection_bounds(map)?;
+        let layers = self.selection_layers(map, tile_layer);
+
+        let mut tiles = luminol_data::Table3::new(width, height, layers.len());
+        for (z, layer) in layers.enumerate() {
+            for offset_y in 0..height {
+                for offset_x in 0..width {
+                    tiles[(offset_x, offset_y, z)] = map.data[(x + offset_x, y + offset_y, layer)];
+                }
+            }
+        }
+
+        let events = if self.selection_events {
+            map.events
+                .iter()
+                .filter(|(_, event)| Self::contains_event(event, x, y, width, height))
+                .map(|(_, event)| {
+                    let mut event = event.clone();
+                    event.x -= x as i32;
+                    event.y -= y as i32;
+                    event
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        Some(luminol_core::MapClipboard {
+            tiles,
+            all_layers: self.selection_all_layers,
+            events,
+        })
+    }
+
+    /// Erase the tiles inside of the selection and delete the events inside of it.
+    pub(super) fn delete_selection(
+        &mut self,
+        update_state: &luminol_core::UpdateState<'_>,
+        map: &mut luminol_data::rpg::Map,
+        tile_layer: usize,
+    ) {
+        let Some((x, y, width, height)) = self.selection_bounds(map) else {
+            return;
+        };
+
+        let mut entries = Vec::new();
+
+        for layer in self.selection_layers(map, tile_layer) {
+            let old_layer = map.data.layer_as_slice(layer).to_vec();
+            for offset_y in 0..height {
+                for offset_x in 0..width {
+                    map.data[(x + offset_x, y + offset_y, layer)] = 0;
+                }
+            }
+            self.recompute_autotiles_around(map, (x, y, width, height), layer);
+            entries.extend(self.finish_layer_edit(update_state, map, layer, &old_layer));
+        }
+
+        if self.selection_events {
+            let ids = map
+                .events
+                .iter()
+                .filter(|(_, event)| Self::contains_event(event, x, y, width, height))
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>();
+            for id in ids {
+                let event = map.events.remove(id);
+                let sprites = self.view.events.try_remove(id).ok();
+                entries.push(super::HistoryEntry::EventDeleted { event, sprites });
+            }
+        }
+
+        if !entries.is_empty() {
+            self.push_to_history(update_state, map, super::HistoryEntry::Multiple(entries));
+        }
+    }
+
+    /// Paste the contents of the map clipboard with its top-left corner at the given position.
+    /// The pasted area becomes the new selection.
+    pub(super) fn paste_clipboard(
+        &mut self,
+        update_state: &luminol_core::UpdateState<'_>,
+        map: &mut luminol_data::rpg::Map,
+        tileset: &luminol_data::rpg::Tileset,
+        tile_layer: usize,
+        (x, y): (usize, usize),
+    ) -> anyhow::Result<()> {
+        let Some(clipboard) = update_state.clipboard.map.as_ref() else {
+            return Ok(());
+        };
+
+        // Skip the parts of the clipboard that don't fit in this map
+        let width = clipboard
+            .tiles
+            .xsize()
+            .min(map.data.xsize().saturating_sub(x));
+        let height = clipboard
+            .tiles
+            .ysize()
+            .min(map.data.ysize().saturating_sub(y));
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+
+        let mut entries = Vec::new();
+
+        for z in 0..clipboard.tiles.zsize() {
+            let layer = if clipboard.all_layers { z } else { tile_layer };
+            if layer >= map.data.zsize() {
+                continue;
+            }
+
+            let old_layer = map.data.layer_as_slice(layer).to_vec();
+            for offset_y in 0..height {
+                for offset_x in 0..width {
+                    map.data[(x + offset_x, y + offset_y, layer)] =
+                        clipboard.tiles[(offset_x, offset_y, z)];
+                }
+            }
+            self.recompute_autotiles_around(map, (x, y, width, height), layer);
+            entries.extend(self.finish_layer_edit(update_state, map, layer, &old_layer));
+        }
+
+        if !clipboard.events.is_empty() {
+            let atlas = update_state.graphics.atlas_loader.load_atlas(
+                &update_state.graphics,
+                update_state.filesystem,
+                tileset,
+            )?;
+
+            for event in clipboard.events.iter() {
+                let event_x = x as i32 + event.x;
+                let event_y = y as i32 + event.y;
+
+                // Events can't be placed outside of the map or on top of other events
+                if event_x >= map.data.xsize() as i32
+                    || event_y >= map.data.ysize() as i32
+                    || map
+                        .events
+                        .iter()
+                        .any(|(_, e)| e.x == event_x && e.y == event_y)
+                {
+                    continue;
+                }
+
+                let Some(id) = Self::next_event_id(map) else {
+                    break;
+                };
+
+                let mut event = event.clone();
+                event.id = id;
+                event.x = event_x;
+                event.y = event_y;
+
+                let sprite = luminol_graphics::Event::new(
+                    &update_state.graphics,
+                    update_state.filesystem,
+                    &event,
+                    &atlas,
+                )?;
+                let preview_sprite = luminol_graphics::Event::new(
+                    &update_state.graphics,
+                    update_state.filesystem,
+                    &event,
+                    &atlas,
+                )?;
+                if let Some(sprites) = sprite.zip(preview_sprite) {
+                    self.view.events.insert(id, sprites);
+                }
+
+                map.events.insert(id, event);
+                entries.push(super::HistoryEntry::EventCreated(id));
+            }
+        }
+
+        self.view.selection_rect = Some(egui::Rect::from_min_max(
+            egui::pos2(x as f32, y as f32),
+            egui::pos2((x + width - 1) as f32, (y + height - 1) as f32),
+        ));
+
+        if !entries.is_empty() {
+            self.push_to_history(update_state, map, super::HistoryEntry::Multiple(entries));
+        }
+
+        Ok(())
+    }
+
+    fn contains_event(
+        event: &luminol_data::rpg::Event,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    ) -> bool {
+        (x as i32..(x + width) as i32).contains(&event.x)
+            && (y as i32..(y + height) as i32).contains(&event.y)
+    }
+
+    /// Recompute the autotiles on the border of a rectangle of tiles and the autotiles surrounding
+    /// it, so that the tiles inside of the rectangle connect to the tiles outside of it.
+    fn recompute_autotiles_around(
+        &self,
+        map: &mut luminol_data::rpg::Map,
+        (x, y, width, height): (usize, usize, usize, usize),
+        layer: usize,
+    ) {
+        let max_x = (x + width).min(map.data.xsize() - 1);
+        let max_y = (y + height).min(map.data.ysize() - 1);
+
+        for position_y in y.saturating_sub(1)..=max_y {
+            for position_x in x.saturating_sub(1)..=max_x {
+                // Tiles in the interior of the rectangle only have neighbors inside of the
+                // rectangle, so they don't need to change
+                if position_x > x
+                    && position_x + 1 < x + width
+                    && position_y > y
+                    && position_y + 1 < y + height
+                {
+                    continue;
+                }
+
+                let position = (position_x, position_y, layer);
+                let tile_id = luminol_data::tilemap::recompute_autotile(&map.data, position);
+                map.data[position] = tile_id;
+            }
+        }
+    }
+}
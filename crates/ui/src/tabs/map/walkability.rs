@@ -0,0 +1,120 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+impl super::Tab {
+    /// Recompute the walkability overlay for the current state of the map.
+    pub(super) fn update_walkability(
+        &mut self,
+        map: &luminol_data::rpg::Map,
+        tileset: &luminol_data::rpg::Tileset,
+    ) {
+        let Some(start) = self.walkability_start.filter(|_| self.walkability_enabled) else {
+            self.view.walkability = None;
+            return;
+        };
+
+        let xsize = map.data.xsize();
+        let ysize = map.data.ysize();
+
+        // The player is blocked by the tiles in every layer regardless of which layers are
+        // visible
+        let mut passages = luminol_data::Table2::new(xsize, ysize);
+        luminol_data::tilemap::calculate_passages(
+            &tileset.passages,
+            &tileset.priorities,
+            &map.data,
+            None,
+            (0..map.data.zsize()).rev(),
+            |x, y, passage| passages[(x, y)] = passage,
+        );
+
+        // Events with a graphic also block the player unless they have through enabled
+        for (_, event) in map.events.iter() {
+            let Some(page) = event.pages.first() else {
+                continue;
+            };
+            let is_solid = !page.through
+                && (page.graphic.character_name.is_some() || page.graphic.tile_id.is_some());
+            if is_solid && Self::is_in_bounds(map, event.x, event.y) {
+                passages[(event.x as usize, event.y as usize)] = 15;
+            }
+        }
+
+        let reachable = luminol_data::tilemap::reachable_tiles(&passages, start);
+
+        let path = self
+            .walkability_goal
+            .and_then(|goal| luminol_data::tilemap::find_path(&passages, start, goal))
+            .unwrap_or_default();
+
+        // Events with the action button, player touch or event touch triggers can be triggered
+        // from their own tile or from any tile next to them
+        let unreachable_events = map
+            .events
+            .iter()
+            .filter(|(_, event)| {
+                event
+                    .pages
+                    .iter()
+                    .any(|page| (0..=2).contains(&page.trigger))
+                    && ![(0, 0), (0, 1), (-1, 0), (1, 0), (0, -1)].into_iter().any(
+                        |(offset_x, offset_y)| {
+                            let x = event.x + offset_x;
+                            let y = event.y + offset_y;
+                            Self::is_in_bounds(map, x, y)
+                                && reachable[x as usize + y as usize * xsize]
+                        },
+                    )
+            })
+            .map(|(id, _)| id)
+            .collect();
+
+        self.view.walkability = Some(luminol_components::WalkabilityOverlay {
+            start,
+            reachable,
+            path,
+            unreachable_events,
+        });
+    }
+
+    /// Sets the start of the walkability analysis to the player's starting position, if the
+    /// player starts on this map.
+    pub(super) fn set_walkability_start_to_player(
+        &mut self,
+        update_state: &luminol_core::UpdateState<'_>,
+    ) {
+        let system = update_state.data.system();
+        let map = update_state.data.get_map(self.id);
+        // Database ids are 0-based in memory, but map ids aren't
+        if system.start_map_id + 1 == self.id
+            && Self::is_in_bounds(&map, system.start_x, system.start_y)
+        {
+            self.walkability_start = Some((system.start_x as usize, system.start_y as usize));
+        }
+    }
+
+    fn is_in_bounds(map: &luminol_data::rpg::Map, x: i32, y: i32) -> bool {
+        (0..map.data.xsize() as i32).contains(&x) && (0..map.data.ysize() as i32).contains(&y)
+    }
+}
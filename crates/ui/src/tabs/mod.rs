@@ -21,7 +21,11 @@
 // it with Steamworks API by Valve Corporation, containing parts covered by
 // terms of the Steamworks API by Valve Corporation, the licensors of this
 // Program grant you additional permission to convey the resulting work.
+/// The character and battler viewer.
+pub mod character_viewer;
 /// The map editor.
 pub mod map;
+/// The problems panel.
+pub mod problems;
 /// The getting started screen.
 pub mod started;
@@ -0,0 +1,141 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+use luminol_core::validate::{Problem, Severity};
+
+use crate::windows::find_references::{is_openable, location_text, open_location};
+
+/// The problems panel.
+/// Lists the problems found by validating the project.
+pub struct Tab {
+    problems: Option<Vec<Problem>>,
+    show_warnings: bool,
+}
+
+impl Tab {
+    /// Create a new problems panel.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            problems: None,
+            show_warnings: true,
+        }
+    }
+
+    fn validate(&mut self, update_state: &luminol_core::UpdateState<'_>) {
+        let command_db = &update_state
+            .project_config
+            .as_ref()
+            .expect("project not loaded")
+            .command_db;
+        self.problems = Some(luminol_core::validate::validate(
+            update_state.data,
+            update_state.filesystem,
+            command_db,
+        ));
+    }
+}
+
+impl luminol_core::Tab for Tab {
+    fn name(&self, _update_state: &luminol_core::UpdateState<'_>) -> String {
+        match self.problems {
+            Some(ref problems) => format!("Problems ({})", problems.len()),
+            None => "Problems".to_string(),
+        }
+    }
+
+    fn id(&self) -> egui::Id {
+        egui::Id::new("luminol_problems_tab")
+    }
+
+    fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        update_state: &mut luminol_core::UpdateState<'_>,
+        _is_focused: bool,
+    ) {
+        if self.problems.is_none() {
+            self.validate(update_state);
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button("Revalidate").clicked() {
+                self.validate(update_state);
+            }
+            ui.checkbox(&mut self.show_warnings, "Show warnings");
+        });
+
+        ui.separator();
+
+        let Some(ref problems) = self.problems else {
+            return;
+        };
+
+        if problems.is_empty() {
+            ui.label("No problems found");
+            return;
+        }
+
+        let mut clicked = None;
+        egui::ScrollArea::vertical()
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                for problem in problems
+                    .iter()
+                    .filter(|p| self.show_warnings || p.severity != Severity::Warning)
+                {
+                    ui.horizontal(|ui| {
+                        match problem.severity {
+                            Severity::Error => ui.colored_label(ui.visuals().error_fg_color, "⛔"),
+                            Severity::Warning => ui.colored_label(ui.visuals().warn_fg_color, "⚠"),
+                        }
+                        .on_hover_text(problem.severity.to_string());
+
+                        let text = format!(
+                            "{}: {}",
+                            location_text(update_state, problem.location),
+                            problem.message
+                        );
+                        if ui
+                            .add_enabled(
+                                is_openable(problem.location),
+                                egui::SelectableLabel::new(false, text),
+                            )
+                            .clicked()
+                        {
+                            clicked = Some(problem.location);
+                        }
+                    });
+                }
+            });
+
+        if let Some(location) = clicked {
+            open_location(update_state, location);
+        }
+    }
+
+    fn requires_filesystem(&self) -> bool {
+        true
+    }
+}
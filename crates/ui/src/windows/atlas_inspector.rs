@@ -0,0 +1,385 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+use luminol_graphics::tiles::{Atlas, TileSource};
+
+/// The largest tile ID the tilemap shader can draw.
+const MAX_TILE_ID: i16 = 8191;
+
+/// The tileset atlas inspector.
+/// Shows the atlas a tileset and its autotiles are packed into, where each tile ID ends up in it,
+/// and every shape of every autotile, to help track down broken custom autotiles.
+pub struct Window {
+    tileset_id: usize,
+    atlas: Option<Atlas>,
+    loaded: bool,
+
+    tile_id: i16,
+    /// The tile under the pointer last frame, shown instead of `tile_id` if there is one.
+    hovered_tile: Option<i16>,
+    zoom: f32,
+    animate: bool,
+    frame: u32,
+    ani_time: Option<f64>,
+}
+
+impl Default for Window {
+    fn default() -> Self {
+        Self {
+            tileset_id: 0,
+            atlas: None,
+            loaded: false,
+            tile_id: 48,
+            hovered_tile: None,
+            zoom: 1.,
+            animate: true,
+            frame: 0,
+            ani_time: None,
+        }
+    }
+}
+
+impl Window {
+    fn load(&mut self, update_state: &mut luminol_core::UpdateState<'_>, rebuild: bool) {
+        self.loaded = true;
+        self.atlas = None;
+
+        let tilesets = update_state.data.tilesets();
+        let Some(tileset) = tilesets.data.get(self.tileset_id) else {
+            return;
+        };
+
+        let atlas_loader = &update_state.graphics.atlas_loader;
+        let result = if rebuild {
+            atlas_loader.reload_atlas(&update_state.graphics, update_state.filesystem, tileset)
+        } else {
+            atlas_loader.load_atlas(&update_state.graphics, update_state.filesystem, tileset)
+        };
+        match result {
+            Ok(atlas) => self.atlas = Some(atlas),
+            Err(e) => update_state.toasts.error(format!(
+                "Error loading the atlas of tileset {:0>3}: {e:#}",
+                self.tileset_id + 1
+            )),
+        }
+    }
+
+    /// Describes where a tile comes from and where it is in the atlas.
+    fn describe(atlas: &Atlas, tileset: &luminol_data::rpg::Tileset, tile: i16) -> String {
+        let source = match TileSource::new(tile) {
+            TileSource::Empty => "Blank, tile IDs below 48 are never drawn".to_string(),
+            TileSource::Autotile {
+                index,
+                shape,
+                pieces,
+            } => {
+                let name = match tileset.autotile_names.get(index) {
+                    Some(name) if !name.is_empty() => name.as_str(),
+                    _ => "no graphic",
+                };
+                let pieces = pieces.map(|(x, y)| format!("({x}, {y})")).join(", ");
+                format!(
+                    "Autotile {} ({name}), shape {shape}\nMade of the 16×16 pieces at {pieces} of each frame",
+                    index + 1
+                )
+            }
+            TileSource::Tileset { column, row } => {
+                let mut text = format!("Tileset column {column}, row {row}");
+                if row * 32 >= atlas.tileset_height {
+                    text.push_str("\nPast the end of the tileset graphic, drawn blank");
+                }
+                text
+            }
+            TileSource::OutOfRange => "Too large to fit in the atlas, drawn blank".to_string(),
+        };
+
+        let quad = atlas.calc_quad(tile).tex_coords;
+        format!(
+            "Tile {tile}: {source}\nQuad: ({:.2}, {:.2}) to ({:.2}, {:.2})",
+            quad.min.x, quad.min.y, quad.max.x, quad.max.y
+        )
+    }
+
+    /// Finds the lowest tile ID that is drawn from the given position in the atlas, in pixels.
+    fn tile_at(atlas: &Atlas, pos: egui::Pos2) -> Option<i16> {
+        (48..=MAX_TILE_ID).find(|&tile| {
+            let frames = match TileSource::new(tile) {
+                TileSource::Autotile { index, .. } => atlas.autotile_frames[index].max(1),
+                _ => 1,
+            };
+            (0..frames).any(|frame| atlas.tile_rect(tile, frame).contains(pos))
+        })
+    }
+
+    /// Draws a tile from the atlas, at the given animation frame if it's an autotile.
+    fn tile_image(
+        ui: &mut egui::Ui,
+        atlas: &Atlas,
+        tile: i16,
+        frame: u32,
+        size: f32,
+    ) -> egui::Response {
+        let (rect, response) = ui.allocate_exact_size(egui::vec2(size, size), egui::Sense::click());
+        let atlas_size = atlas.atlas_texture.size_vec2();
+        let tile_rect = atlas.tile_rect(tile, frame);
+        let uv = egui::Rect::from_min_max(
+            (tile_rect.min.to_vec2() / atlas_size).to_pos2(),
+            (tile_rect.max.to_vec2() / atlas_size).to_pos2(),
+        );
+        ui.painter().image(
+            atlas.atlas_texture.texture_id,
+            rect,
+            uv,
+            egui::Color32::WHITE,
+        );
+        response
+    }
+
+    fn show_autotiles(
+        &mut self,
+        ui: &mut egui::Ui,
+        atlas: &Atlas,
+        tileset: &luminol_data::rpg::Tileset,
+    ) {
+        let size = 32. * self.zoom;
+        for (index, frames) in atlas.autotile_frames.iter().copied().enumerate() {
+            let name = tileset
+                .autotile_names
+                .get(index)
+                .filter(|name| !name.is_empty());
+            let header = match name {
+                Some(name) if frames == 0 => format!(
+                    "Autotile {}: {name} (narrower than one 96 px frame)",
+                    index + 1
+                ),
+                Some(name) => format!("Autotile {}: {name} ({frames} frames)", index + 1),
+                None => format!("Autotile {}: (none)", index + 1),
+            };
+
+            egui::CollapsingHeader::new(header)
+                .id_source(index)
+                .default_open(name.is_some())
+                .show(ui, |ui| {
+                    egui::Grid::new(("luminol_atlas_inspector_autotile", index))
+                        .spacing(egui::vec2(2., 2.))
+                        .show(ui, |ui| {
+                            for shape in 0..48 {
+                                let tile = (48 * (index + 1) + shape) as i16;
+                                let response = Self::tile_image(ui, atlas, tile, self.frame, size);
+                                if response.hovered() {
+                                    self.hovered_tile = Some(tile);
+                                }
+                                if response.clicked() {
+                                    self.tile_id = tile;
+                                }
+                                if shape % 8 == 7 {
+                                    ui.end_row();
+                                }
+                            }
+                        });
+                });
+        }
+    }
+
+    fn show_atlas(&mut self, ui: &mut egui::Ui, atlas: &Atlas, shown_tile: i16) {
+        egui::ScrollArea::both()
+            .id_source("luminol_atlas_inspector_atlas")
+            .max_height(400.)
+            .show(ui, |ui| {
+                let (rect, response) = ui.allocate_exact_size(
+                    atlas.atlas_texture.size_vec2() * self.zoom,
+                    egui::Sense::click(),
+                );
+                let painter = ui.painter_at(rect);
+                painter.image(
+                    atlas.atlas_texture.texture_id,
+                    rect,
+                    egui::Rect::from_min_max(egui::Pos2::ZERO, egui::pos2(1., 1.)),
+                    egui::Color32::WHITE,
+                );
+
+                // Outline every place the shown tile is drawn from
+                let frames = match TileSource::new(shown_tile) {
+                    TileSource::Autotile { index, .. } => atlas.autotile_frames[index].max(1),
+                    _ => 1,
+                };
+                for frame in 0..frames {
+                    let tile_rect = atlas.tile_rect(shown_tile, frame);
+                    painter.rect_stroke(
+                        egui::Rect::from_min_max(
+                            rect.min + tile_rect.min.to_vec2() * self.zoom,
+                            rect.min + tile_rect.max.to_vec2() * self.zoom,
+                        ),
+                        0.,
+                        egui::Stroke::new(2., ui.visuals().selection.stroke.color),
+                    );
+                }
+
+                if let Some(pos) = response.hover_pos() {
+                    let pos = ((pos - rect.min) / self.zoom).to_pos2();
+                    if let Some(tile) = Self::tile_at(atlas, pos) {
+                        self.hovered_tile = Some(tile);
+                        if response.clicked() {
+                            self.tile_id = tile;
+                        }
+                    }
+                }
+            });
+    }
+}
+
+impl luminol_core::Window for Window {
+    fn name(&self) -> String {
+        "Tileset Atlas Inspector".to_string()
+    }
+
+    fn id(&self) -> egui::Id {
+        egui::Id::new("luminol_atlas_inspector")
+    }
+
+    fn show(
+        &mut self,
+        ctx: &egui::Context,
+        open: &mut bool,
+        update_state: &mut luminol_core::UpdateState<'_>,
+    ) {
+        if !self.loaded {
+            self.load(update_state, false);
+        }
+
+        // Autotiles animate at the same rate as on the map
+        let time = ctx.input(|i| i.time);
+        if self.animate {
+            match self.ani_time {
+                Some(ani_time) if time - ani_time < 16. / 60. => {}
+                _ => {
+                    self.ani_time = Some(time);
+                    self.frame = self.frame.wrapping_add(1);
+                }
+            }
+            ctx.request_repaint_after(std::time::Duration::from_secs_f64(16. / 60.));
+        } else {
+            self.ani_time = None;
+        }
+
+        let shown_tile = self.hovered_tile.take().unwrap_or(self.tile_id);
+
+        let mut window_open = true;
+        egui::Window::new(self.name())
+            .id(self.id())
+            .open(&mut window_open)
+            .default_width(560.)
+            .show(ctx, |ui| {
+                let mut reload = None;
+                ui.horizontal(|ui| {
+                    let tilesets = update_state.data.tilesets();
+                    let selected_text = tilesets
+                        .data
+                        .get(self.tileset_id)
+                        .map(|tileset| format!("{:0>3}: {}", self.tileset_id + 1, tileset.name))
+                        .unwrap_or_default();
+                    egui::ComboBox::from_label("Tileset")
+                        .selected_text(selected_text)
+                        .show_ui(ui, |ui| {
+                            for (id, tileset) in tilesets.data.iter().enumerate() {
+                                if ui
+                                    .selectable_value(
+                                        &mut self.tileset_id,
+                                        id,
+                                        format!("{:0>3}: {}", id + 1, tileset.name),
+                                    )
+                                    .changed()
+                                {
+                                    reload = Some(false);
+                                }
+                            }
+                        });
+
+                    if ui
+                        .button("Rebuild")
+                        .on_hover_text("Rebuild the atlas from the tileset and autotile graphics")
+                        .clicked()
+                    {
+                        reload = Some(true);
+                    }
+                });
+                if let Some(rebuild) = reload {
+                    self.load(update_state, rebuild);
+                }
+
+                let Some(atlas) = self.atlas.clone() else {
+                    ui.label("The atlas for this tileset could not be loaded");
+                    return;
+                };
+                let tilesets = update_state.data.tilesets();
+                let Some(tileset) = tilesets.data.get(self.tileset_id) else {
+                    return;
+                };
+
+                ui.label(format!(
+                    "Atlas: {} × {} px, autotile area {} px wide, tileset {} px tall",
+                    atlas.atlas_texture.width(),
+                    atlas.atlas_texture.height(),
+                    atlas.autotile_width,
+                    atlas.tileset_height
+                ));
+
+                ui.horizontal(|ui| {
+                    ui.label("Tile ID");
+                    ui.add(egui::DragValue::new(&mut self.tile_id).clamp_range(0..=MAX_TILE_ID));
+                    ui.checkbox(&mut self.animate, "Animate autotiles");
+                    ui.add_enabled(
+                        !self.animate,
+                        egui::DragValue::new(&mut self.frame).prefix("Frame "),
+                    );
+                    ui.add(egui::Slider::new(&mut self.zoom, 0.5..=4.).text("Zoom"));
+                });
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    Self::tile_image(ui, &atlas, shown_tile, self.frame, 64.);
+                    ui.label(Self::describe(&atlas, tileset, shown_tile));
+                });
+
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .id_source("luminol_atlas_inspector_scroll")
+                    .show(ui, |ui| {
+                        egui::CollapsingHeader::new("Autotiles")
+                            .default_open(true)
+                            .show(ui, |ui| self.show_autotiles(ui, &atlas, tileset));
+                        egui::CollapsingHeader::new("Atlas")
+                            .show(ui, |ui| self.show_atlas(ui, &atlas, shown_tile));
+                    });
+            });
+
+        *open = window_open;
+    }
+
+    fn requires_filesystem(&self) -> bool {
+        true
+    }
+}
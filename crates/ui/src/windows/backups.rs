@@ -0,0 +1,181 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+use luminol_core::backups::Snapshot;
+
+/// Browses the snapshots taken before each save and restores files from them.
+#[derive(Default)]
+pub struct Window {
+    snapshots: Option<Vec<Snapshot>>,
+    selected: usize,
+}
+
+impl luminol_core::Window for Window {
+    fn id(&self) -> egui::Id {
+        egui::Id::new("luminol_backups")
+    }
+
+    fn name(&self) -> String {
+        "Backups".to_string()
+    }
+
+    fn show(
+        &mut self,
+        ctx: &egui::Context,
+        open: &mut bool,
+        update_state: &mut luminol_core::UpdateState<'_>,
+    ) {
+        let name = self.name();
+        let id = self.id();
+        let snapshots = self.snapshots.get_or_insert_with(|| {
+            luminol_core::backups::list(update_state.filesystem).unwrap_or_else(|e| {
+                update_state
+                    .toasts
+                    .error(format!("Error reading backups: {e:#}"));
+                vec![]
+            })
+        });
+        let mut refresh = false;
+
+        egui::Window::new(name)
+            .id(id)
+            .open(open)
+            .default_width(400.)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("Refresh").clicked() {
+                        refresh = true;
+                    }
+                    ui.label("Restored files are loaded as unsaved changes.");
+                });
+
+                ui.separator();
+
+                if snapshots.is_empty() {
+                    ui.label("No backups yet. One is taken every time the project is saved.");
+                    return;
+                }
+                self.selected = self.selected.min(snapshots.len() - 1);
+
+                let mut restore = None;
+                let mut restore_point_in_time = false;
+
+                ui.columns(2, |columns| {
+                    egui::ScrollArea::vertical()
+                        .id_source("luminol_backups_snapshots")
+                        .max_height(400.)
+                        .show(&mut columns[0], |ui| {
+                            for (index, snapshot) in snapshots.iter().enumerate() {
+                                ui.selectable_value(
+                                    &mut self.selected,
+                                    index,
+                                    snapshot.name.as_str(),
+                                );
+                            }
+                        });
+
+                    let snapshot = &snapshots[self.selected];
+                    let ui = &mut columns[1];
+
+                    if ui
+                        .button("Restore project")
+                        .on_hover_text(
+                            "Restores every data file to how it was right before this save, \
+                             including files from newer backups",
+                        )
+                        .clicked()
+                    {
+                        restore_point_in_time = true;
+                    }
+
+                    ui.separator();
+
+                    egui::ScrollArea::vertical()
+                        .id_source("luminol_backups_files")
+                        .max_height(400.)
+                        .show(ui, |ui| {
+                            for filename in &snapshot.files {
+                                ui.horizontal(|ui| {
+                                    ui.label(filename.as_str());
+                                    if ui.small_button("Restore").clicked() {
+                                        restore = Some(vec![filename.clone()]);
+                                    }
+                                });
+                            }
+                        });
+                });
+
+                let config = update_state
+                    .project_config
+                    .as_ref()
+                    .expect("project not loaded");
+                let result = if restore_point_in_time {
+                    Some(luminol_core::backups::restore_point_in_time(
+                        update_state.data,
+                        update_state.filesystem,
+                        config,
+                        snapshots,
+                        self.selected,
+                    ))
+                } else {
+                    restore.map(|filenames| {
+                        luminol_core::backups::restore(
+                            update_state.data,
+                            update_state.filesystem,
+                            config,
+                            &snapshots[self.selected],
+                            &filenames,
+                        )
+                        .map(|()| filenames)
+                    })
+                };
+
+                match result {
+                    Some(Ok(filenames)) => {
+                        update_state.modified.set(true);
+                        // Edits in the history were made to the data from before the restore
+                        update_state.history.clear();
+                        super::external_changes::close_editors_for(update_state, &filenames);
+                        update_state.toasts.info(format!(
+                            "Restored {} file(s) from {}",
+                            filenames.len(),
+                            snapshots[self.selected].name
+                        ));
+                    }
+                    Some(Err(e)) => update_state
+                        .toasts
+                        .error(format!("Error restoring backup: {e:#}")),
+                    None => {}
+                }
+            });
+
+        if refresh {
+            self.snapshots = None;
+        }
+    }
+
+    fn requires_filesystem(&self) -> bool {
+        true
+    }
+}
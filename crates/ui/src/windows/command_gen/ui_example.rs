@@ -132,6 +132,20 @@ impl UiExample {
                             }
                         });
                     }
+                    ParameterKind::Actor
+                    | ParameterKind::Class
+                    | ParameterKind::Skill
+                    | ParameterKind::Item
+                    | ParameterKind::Weapon
+                    | ParameterKind::Armor
+                    | ParameterKind::Enemy
+                    | ParameterKind::Troop
+                    | ParameterKind::State
+                    | ParameterKind::Animation
+                    | ParameterKind::CommonEvent => {
+                        let name: &'static str = (&*kind).into();
+                        ui.button(format!("{name}: [001: EXAMPLE]")).clicked();
+                    }
                 }
             }
             Parameter::Dummy => {}
@@ -39,6 +39,23 @@ impl Default for Window {
     }
 }
 
+impl Window {
+    /// Create a common event editor with the common event at `index` already open.
+    pub fn with_event(index: usize, event: &luminol_data::rpg::CommonEvent) -> Self {
+        let mut window = Self {
+            selected_id: index,
+            ..Default::default()
+        };
+        window.tabs.add_tab(CommonEventTab {
+            event: event.clone(),
+            force_close: false,
+            switch_modal: None,
+            command_view: luminol_components::CommandView::new(format!("common_event_{index}")),
+        });
+        window
+    }
+}
+
 impl luminol_core::Window for Window {
     fn name(&self) -> String {
         self.tabs
@@ -166,12 +183,32 @@ impl luminol_core::Tab for CommonEventTab {
 
             if save_event {
                 let mut common_events = update_state.data.common_events();
-
-                common_events.data[self.event.id - 1] = self.event.clone();
+                common_events.modified = true;
+                update_state.modified.set(true);
+
+                let index = self.event.id - 1;
+                let previous =
+                    std::mem::replace(&mut common_events.data[index], self.event.clone());
+                update_state.history.push(
+                    self.id(),
+                    format!("Edit common event {}", self.event.name),
+                    luminol_core::history::Swap::common_event(index, previous),
+                );
             }
 
             ui.label("Name");
             ui.text_edit_singleline(&mut self.event.name);
+
+            if let Some(project_config) = update_state.project_config.as_ref() {
+                if ui.button("Copy as text").clicked() {
+                    luminol_components::CommandView::copy_as_text(
+                        ui.ctx(),
+                        &project_config.command_db,
+                        update_state.data,
+                        &self.event.list,
+                    );
+                }
+            }
         });
 
         ui.separator();
@@ -179,11 +216,11 @@ impl luminol_core::Tab for CommonEventTab {
         egui::ScrollArea::both()
             .auto_shrink([false; 2])
             .show(ui, |ui| {
-                self.command_view.ui(
-                    ui,
-                    &update_state.project_config.as_ref().unwrap().command_db,
-                    &mut self.event.list,
-                );
+                // The project config is briefly missing while a project is being closed
+                if let Some(project_config) = update_state.project_config.as_ref() {
+                    self.command_view
+                        .ui(ui, &project_config.command_db, &mut self.event.list);
+                }
             });
     }
 
@@ -68,6 +68,11 @@ impl luminol_core::Window for Window {
 
             ui.label("Playtest Executable");
             ui.text_edit_singleline(&mut config.project.playtest_exe);
+
+            ui.horizontal(|ui| {
+                ui.label("Backups to keep");
+                ui.add(egui::DragValue::new(&mut config.project.backup_count).clamp_range(0..=100));
+            });
         });
     }
 
@@ -0,0 +1,267 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+use std::io::{Read, Write};
+
+use luminol_core::diff::{Change, Merge, Side};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Diff,
+    Merge,
+}
+
+type PickPromise = poll_promise::Promise<luminol_filesystem::Result<Vec<(Vec<u8>, String)>>>;
+
+enum Loaded {
+    Diff {
+        names: [String; 2],
+        changes: Vec<Change>,
+    },
+    Merge {
+        name: String,
+        merge: Merge,
+        sides: Vec<Side>,
+    },
+}
+
+/// The data diff and merge window.
+/// Compares two versions of a data file, or merges two versions that were changed from a common base.
+pub struct Window {
+    mode: Mode,
+    pick_promise: Option<PickPromise>,
+    save_promise: Option<poll_promise::Promise<luminol_filesystem::Result<()>>>,
+    result: Option<Loaded>,
+}
+
+impl Default for Window {
+    fn default() -> Self {
+        Self {
+            mode: Mode::Merge,
+            pick_promise: None,
+            save_promise: None,
+            result: None,
+        }
+    }
+}
+
+impl Window {
+    fn pick_files(&mut self) {
+        let prompts: &'static [&'static str] = match self.mode {
+            Mode::Diff => &["Old version", "New version"],
+            Mode::Merge => &["Common base version", "Our version", "Their version"],
+        };
+
+        self.pick_promise = Some(luminol_core::spawn_future(async move {
+            let mut files = Vec::with_capacity(prompts.len());
+            for prompt in prompts {
+                let (mut file, name) = luminol_filesystem::host::File::from_file_picker(
+                    prompt,
+                    &["rxdata", "rvdata", "rvdata2"],
+                )
+                .await?;
+                let mut bytes = Vec::new();
+                file.read_to_end(&mut bytes)?;
+                files.push((bytes, name));
+            }
+            Ok(files)
+        }));
+    }
+
+    fn load(mut files: Vec<(Vec<u8>, String)>) -> anyhow::Result<Loaded> {
+        match files.len() {
+            2 => {
+                let (new, new_name) = files.pop().unwrap();
+                let (old, old_name) = files.pop().unwrap();
+                Ok(Loaded::Diff {
+                    changes: luminol_core::diff::diff_bytes(&old, &new)?,
+                    names: [old_name, new_name],
+                })
+            }
+            _ => {
+                let [(base, _), (ours, name), (theirs, _)]: [_; 3] = files
+                    .try_into()
+                    .map_err(|_| anyhow::anyhow!("expected three files"))?;
+                let merge = luminol_core::diff::merge_bytes(&base, &ours, &theirs)?;
+                Ok(Loaded::Merge {
+                    sides: vec![Side::Ours; merge.conflicts.len()],
+                    name,
+                    merge,
+                })
+            }
+        }
+    }
+
+    fn save(merge: &Merge, sides: &[Side]) -> anyhow::Result<luminol_filesystem::host::File> {
+        let mut file = luminol_filesystem::host::File::new()?;
+        file.write_all(&merge.to_bytes(sides)?)?;
+        file.flush()?;
+        Ok(file)
+    }
+}
+
+impl luminol_core::Window for Window {
+    fn id(&self) -> egui::Id {
+        egui::Id::new("luminol_data_merge")
+    }
+
+    fn name(&self) -> String {
+        "Diff and Merge Data Files".to_string()
+    }
+
+    fn show(
+        &mut self,
+        ctx: &egui::Context,
+        open: &mut bool,
+        update_state: &mut luminol_core::UpdateState<'_>,
+    ) {
+        let mut window_open = true;
+        egui::Window::new(self.name())
+            .id(self.id())
+            .open(&mut window_open)
+            .default_width(500.)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.mode, Mode::Merge, "Three-way merge");
+                    ui.radio_value(&mut self.mode, Mode::Diff, "Compare two files");
+                });
+                ui.label(match self.mode {
+                    Mode::Merge => {
+                        "Pick the common base version, then our version, then their version. \
+                         Changes made on only one side are applied automatically."
+                    }
+                    Mode::Diff => "Pick the old version, then the new version.",
+                });
+
+                if self.pick_promise.is_some() {
+                    ui.spinner();
+                } else if ui.button("Pick files").clicked() {
+                    self.pick_files();
+                }
+
+                ui.separator();
+
+                match &mut self.result {
+                    Some(Loaded::Diff { names, changes }) => {
+                        ui.label(format!(
+                            "{} → {}: {} changes",
+                            names[0],
+                            names[1],
+                            changes.len()
+                        ));
+                        egui::ScrollArea::vertical()
+                            .max_height(400.)
+                            .show(ui, |ui| {
+                                for change in changes.iter() {
+                                    ui.label(format!("{}: {}", change.path, change.description));
+                                }
+                            });
+                    }
+
+                    Some(Loaded::Merge { name, merge, sides }) => {
+                        if merge.conflicts.is_empty() {
+                            ui.label(format!("{name} merged without conflicts"));
+                        } else {
+                            ui.label(format!(
+                                "{name}: {} conflicts, pick a side for each",
+                                merge.conflicts.len()
+                            ));
+                        }
+
+                        egui::ScrollArea::vertical()
+                            .max_height(400.)
+                            .show(ui, |ui| {
+                                for (conflict, side) in merge.conflicts.iter().zip(sides.iter_mut())
+                                {
+                                    ui.group(|ui| {
+                                        ui.strong(conflict.path.to_string());
+                                        ui.label(conflict.description.as_str());
+                                        ui.horizontal(|ui| {
+                                            ui.radio_value(side, Side::Ours, "Keep ours");
+                                            ui.radio_value(side, Side::Theirs, "Keep theirs");
+                                        });
+                                    });
+                                }
+                            });
+
+                        if self.save_promise.is_some() {
+                            ui.spinner();
+                        } else if ui.button("Save merged file").clicked() {
+                            match Self::save(merge, sides) {
+                                Ok(file) => {
+                                    let name = name.clone();
+                                    self.save_promise =
+                                        Some(luminol_core::spawn_future(async move {
+                                            file.save(&name, "RPG Maker data files").await
+                                        }));
+                                }
+                                Err(e) => update_state
+                                    .toasts
+                                    .error(format!("Error saving the merged file: {e}")),
+                            }
+                        }
+                    }
+
+                    None => {}
+                }
+            });
+
+        if let Some(p) = self.pick_promise.take() {
+            match p.try_take() {
+                Ok(Ok(files)) => match Self::load(files) {
+                    Ok(result) => self.result = Some(result),
+                    Err(e) => update_state
+                        .toasts
+                        .error(format!("Error reading the data files: {e}")),
+                },
+                Ok(Err(e)) => {
+                    if !matches!(e, luminol_filesystem::Error::CancelledLoading) {
+                        update_state.toasts.error(e.to_string())
+                    }
+                }
+                Err(p) => self.pick_promise = Some(p),
+            }
+        }
+
+        if let Some(p) = self.save_promise.take() {
+            match p.try_take() {
+                Ok(Ok(())) => update_state
+                    .toasts
+                    .info("Saved the merged file successfully!"),
+                Ok(Err(e)) => {
+                    if !matches!(e, luminol_filesystem::Error::CancelledLoading) {
+                        update_state.toasts.error(e.to_string())
+                    }
+                }
+                Err(p) => self.save_promise = Some(p),
+            }
+        }
+
+        *open = window_open;
+    }
+
+    fn requires_filesystem(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,137 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+use std::io::Write;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Csv,
+    Markdown,
+}
+
+/// The dialogue script exporter.
+/// Writes every message and choice in the project to a CSV or Markdown file for proofreading.
+pub struct Window {
+    format: Format,
+    save_promise: Option<poll_promise::Promise<luminol_filesystem::Result<()>>>,
+}
+
+impl Default for Window {
+    fn default() -> Self {
+        Self {
+            format: Format::Csv,
+            save_promise: None,
+        }
+    }
+}
+
+impl Window {
+    /// Collects the dialogue and writes it to a temporary file.
+    fn export(
+        &self,
+        update_state: &luminol_core::UpdateState<'_>,
+    ) -> anyhow::Result<luminol_filesystem::host::File> {
+        let dialogue =
+            luminol_core::command_text::dialogue(update_state.data, update_state.filesystem);
+        let text = match self.format {
+            Format::Csv => luminol_core::command_text::dialogue_to_csv(&dialogue),
+            Format::Markdown => luminol_core::command_text::dialogue_to_markdown(&dialogue),
+        };
+
+        let mut file = luminol_filesystem::host::File::new()?;
+        file.write_all(text.as_bytes())?;
+        file.flush()?;
+        Ok(file)
+    }
+}
+
+impl luminol_core::Window for Window {
+    fn id(&self) -> egui::Id {
+        egui::Id::new("luminol_dialogue_export")
+    }
+
+    fn name(&self) -> String {
+        "Export Dialogue".to_string()
+    }
+
+    fn show(
+        &mut self,
+        ctx: &egui::Context,
+        open: &mut bool,
+        update_state: &mut luminol_core::UpdateState<'_>,
+    ) {
+        let mut window_open = true;
+        egui::Window::new(self.name())
+            .id(self.id())
+            .open(&mut window_open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("Exports every Show Text and Show Choices command in the project.");
+
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.format, Format::Csv, "CSV");
+                    ui.radio_value(&mut self.format, Format::Markdown, "Markdown");
+                });
+
+                ui.separator();
+
+                if self.save_promise.is_some() {
+                    ui.spinner();
+                } else if ui.button("Export").clicked() {
+                    match self.export(update_state) {
+                        Ok(file) => {
+                            let (filename, filter) = match self.format {
+                                Format::Csv => ("dialogue.csv", "CSV files"),
+                                Format::Markdown => ("dialogue.md", "Markdown files"),
+                            };
+                            self.save_promise = Some(luminol_core::spawn_future(async move {
+                                file.save(filename, filter).await
+                            }));
+                        }
+                        Err(e) => update_state
+                            .toasts
+                            .error(format!("Error exporting the dialogue: {e}")),
+                    }
+                }
+            });
+
+        if let Some(p) = self.save_promise.take() {
+            match p.try_take() {
+                Ok(Ok(())) => update_state.toasts.info("Exported dialogue successfully!"),
+                Ok(Err(e)) => {
+                    if !matches!(e, luminol_filesystem::Error::CancelledLoading) {
+                        update_state.toasts.error(e.to_string())
+                    }
+                }
+                Err(p) => self.save_promise = Some(p),
+            }
+        }
+
+        *open = window_open;
+    }
+
+    fn requires_filesystem(&self) -> bool {
+        true
+    }
+}
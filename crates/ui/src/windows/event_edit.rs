@@ -63,14 +63,47 @@ impl luminol_core::Window for Window {
             .with(self.id)
     }
 
-    // This needs an overhaul
+    // This needs an overhaul, only the name can be edited for now
     fn show(
         &mut self,
-        _ctx: &egui::Context,
-        _open: &mut bool,
-        _update_state: &mut luminol_core::UpdateState<'_>,
+        ctx: &egui::Context,
+        open: &mut bool,
+        update_state: &mut luminol_core::UpdateState<'_>,
     ) {
-        todo!()
+        let mut map = update_state.data.get_map(self.map_id);
+        let Some(event) = map.events.get_mut(self.id) else {
+            // The event was deleted
+            *open = false;
+            return;
+        };
+        self.name.clone_from(&event.name);
+        let previous = event.name.clone();
+        let scope = self.id();
+        let mut edited = None;
+
+        egui::Window::new(self.name())
+            .id(scope)
+            .open(open)
+            .show(ctx, |ui| {
+                update_state.history.focus_on_click(ui, scope);
+
+                ui.label("Name");
+                let response = ui.text_edit_singleline(&mut event.name);
+                if response.changed() {
+                    edited = Some(response.id);
+                }
+            });
+
+        if let Some(field_id) = edited {
+            map.modified = true;
+            update_state.modified.set(true);
+            update_state.history.push_merged(
+                scope,
+                field_id.with((self.map_id, self.id)),
+                format!("Rename event {previous}"),
+                luminol_core::history::Swap::event_name(self.map_id, self.id, previous),
+            );
+        }
     }
 
     fn requires_filesystem(&self) -> bool {
@@ -0,0 +1,160 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+/// Asks whether to reload data files that were changed outside of Luminol.
+#[derive(Default)]
+pub struct Window {}
+
+impl luminol_core::Window for Window {
+    fn id(&self) -> egui::Id {
+        egui::Id::new("luminol_external_changes")
+    }
+
+    fn name(&self) -> String {
+        "Files Changed on Disk".to_string()
+    }
+
+    fn show(
+        &mut self,
+        ctx: &egui::Context,
+        open: &mut bool,
+        update_state: &mut luminol_core::UpdateState<'_>,
+    ) {
+        let mut window_open = true;
+
+        egui::Window::new(self.name())
+            .id(self.id())
+            .open(&mut window_open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                let config = update_state
+                    .project_config
+                    .as_ref()
+                    .expect("project not loaded");
+                let modified_files = update_state.data.modified_files(config);
+
+                ui.label("These data files were changed by another program.");
+                ui.label("Reloading them discards the cached version, clears the undo history, and closes any tabs or windows showing them.");
+
+                ui.separator();
+
+                let mut reload = Vec::new();
+                let mut ignore = Vec::new();
+
+                egui::ScrollArea::vertical()
+                    .max_height(300.)
+                    .show(ui, |ui| {
+                        for filename in &update_state.project_manager.changed_data_files {
+                            ui.horizontal(|ui| {
+                                ui.label(filename.as_str());
+                                if modified_files.contains(filename) {
+                                    ui.colored_label(
+                                        ui.visuals().warn_fg_color,
+                                        "has unsaved changes",
+                                    );
+                                }
+
+                                if ui.small_button("Reload").clicked() {
+                                    reload.push(filename.clone());
+                                }
+                                if ui.small_button("Ignore").clicked() {
+                                    ignore.push(filename.clone());
+                                }
+                            });
+                        }
+                    });
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    if ui.button("Reload all").clicked() {
+                        reload = update_state.project_manager.changed_data_files.clone();
+                    }
+                    if ui.button("Ignore all").clicked() {
+                        ignore = update_state.project_manager.changed_data_files.clone();
+                    }
+                });
+
+                if !reload.is_empty() {
+                    update_state.history.clear();
+                }
+                let mut reloaded = Vec::with_capacity(reload.len());
+                for filename in reload {
+                    match update_state
+                        .data
+                        .reload(update_state.filesystem, &filename, config)
+                    {
+                        Ok(()) => reloaded.push(filename),
+                        Err(e) => update_state
+                            .toasts
+                            .error(format!("Error reloading {filename}: {e:#}")),
+                    }
+                }
+                close_editors_for(update_state, &reloaded);
+                ignore.extend(reloaded);
+
+                update_state
+                    .project_manager
+                    .changed_data_files
+                    .retain(|filename| !ignore.contains(filename));
+            });
+
+        // Closing the window ignores whatever is left
+        if !window_open {
+            update_state.project_manager.changed_data_files.clear();
+        }
+        *open = window_open && !update_state.project_manager.changed_data_files.is_empty();
+    }
+
+    fn requires_filesystem(&self) -> bool {
+        true
+    }
+}
+
+/// Closes the tabs and editors showing any of the data files `filenames`, which were just loaded
+/// again from disk or a backup. They keep their own copy of the data, and would otherwise show or
+/// write back the data from before.
+pub(crate) fn close_editors_for(
+    update_state: &mut luminol_core::UpdateState<'_>,
+    filenames: &[String],
+) {
+    for filename in filenames {
+        if let Some(map_id) = luminol_core::map_id_from_filename(filename) {
+            // Event editors belong to their map's tab, so they're closed along with it
+            update_state
+                .edit_tabs
+                .remove_tab_by_id(egui::Id::new("luminol_map").with(map_id));
+        } else if filename == "CommonEvents.rxdata" {
+            let window = super::common_event_edit::Window::default();
+            update_state.edit_windows.remove_window(&window);
+        } else if update_state
+            .project_config
+            .as_ref()
+            .is_some_and(|config| *filename == format!("{}.rxdata", config.project.scripts_path))
+        {
+            let window = super::script_edit::Window::default();
+            update_state.edit_windows.remove_window(&window);
+        }
+    }
+}
@@ -0,0 +1,295 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+use luminol_core::references::{Location, Reference, Target};
+use strum::IntoEnumIterator;
+
+/// The find references window.
+/// Lists every place in the project that uses a switch, variable, or database entry.
+pub struct Window {
+    target: Target,
+    id: usize,
+    results: Option<(Target, usize, Vec<Reference>)>,
+}
+
+impl Default for Window {
+    fn default() -> Self {
+        Self {
+            target: Target::default(),
+            id: 1,
+            results: None,
+        }
+    }
+}
+
+impl luminol_core::Window for Window {
+    fn name(&self) -> String {
+        "Find References".to_string()
+    }
+
+    fn id(&self) -> egui::Id {
+        egui::Id::new("luminol_find_references")
+    }
+
+    fn show(
+        &mut self,
+        ctx: &egui::Context,
+        open: &mut bool,
+        update_state: &mut luminol_core::UpdateState<'_>,
+    ) {
+        let mut window_open = true;
+        egui::Window::new(self.name())
+            .id(self.id())
+            .open(&mut window_open)
+            .default_width(500.)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_source("luminol_find_references_target")
+                        .selected_text(self.target.to_string())
+                        .show_ui(ui, |ui| {
+                            for target in Target::iter() {
+                                ui.selectable_value(&mut self.target, target, target.to_string());
+                            }
+                        });
+
+                    ui.add(egui::DragValue::new(&mut self.id).clamp_range(1..=9999));
+                    ui.label(entry_name(update_state, self.target, self.id));
+
+                    if ui.button("Search 🔎").clicked() {
+                        let command_db = &update_state
+                            .project_config
+                            .as_ref()
+                            .expect("project not loaded")
+                            .command_db;
+                        let references = luminol_core::references::find_references(
+                            update_state.data,
+                            update_state.filesystem,
+                            command_db,
+                            self.target,
+                            self.id,
+                        );
+                        self.results = Some((self.target, self.id, references));
+                    }
+                });
+
+                ui.separator();
+
+                let Some((target, id, ref references)) = self.results else {
+                    return;
+                };
+
+                ui.label(format!(
+                    "{} references to {target} {id:0>3}",
+                    references.len()
+                ));
+
+                let mut clicked = None;
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false, true])
+                    .max_height(384.)
+                    .show(ui, |ui| {
+                        for reference in references {
+                            let text = format!(
+                                "{}: {}",
+                                location_text(update_state, reference.location),
+                                reference.description
+                            );
+                            if ui
+                                .add_enabled(
+                                    is_openable(reference.location),
+                                    egui::SelectableLabel::new(false, text),
+                                )
+                                .clicked()
+                            {
+                                clicked = Some(reference.location);
+                            }
+                        }
+                    });
+
+                if let Some(location) = clicked {
+                    open_location(update_state, location);
+                }
+            });
+
+        *open = window_open;
+    }
+
+    fn requires_filesystem(&self) -> bool {
+        true
+    }
+}
+
+/// Name of the entry `id` of `target` refers to.
+pub(crate) fn entry_name(
+    update_state: &luminol_core::UpdateState<'_>,
+    target: Target,
+    id: usize,
+) -> String {
+    target
+        .entry_name(update_state.data, id)
+        .unwrap_or_else(|| "(invalid)".to_string())
+}
+
+pub(crate) fn location_text(
+    update_state: &luminol_core::UpdateState<'_>,
+    location: Location,
+) -> String {
+    match location {
+        Location::MapEvent {
+            map_id,
+            event_id,
+            page,
+        } => {
+            let map_name = update_state
+                .data
+                .map_infos()
+                .data
+                .get(&map_id)
+                .map(|info| info.name.clone())
+                .unwrap_or_default();
+            let map = update_state.data.get_map(map_id);
+            let event_name = map
+                .events
+                .get(event_id)
+                .map(|e| e.name.clone())
+                .unwrap_or_default();
+            format!(
+                "Map {map_id:0>3} ({map_name}), event {event_id:0>3} ({event_name}), page {}",
+                page + 1
+            )
+        }
+        Location::CommonEvent { index } => {
+            let common_events = update_state.data.common_events();
+            format!(
+                "Common event {:0>3} ({})",
+                index + 1,
+                common_events.data[index].name
+            )
+        }
+        Location::Troop { index, page } => {
+            let troops = update_state.data.troops();
+            format!(
+                "Troop {:0>3} ({}), page {}",
+                index + 1,
+                troops.data[index].name,
+                page + 1
+            )
+        }
+        Location::Map { map_id } => {
+            let map_infos = update_state.data.map_infos();
+            let map_name = map_infos
+                .data
+                .get(&map_id)
+                .map(|info| info.name.as_str())
+                .unwrap_or_default();
+            format!("Map {map_id:0>3} ({map_name})")
+        }
+        Location::Database { target, index } => format!(
+            "{target} {:0>3} ({})",
+            index + 1,
+            entry_name(update_state, target, index + 1)
+        ),
+        Location::Script { index } => {
+            let scripts = update_state.data.scripts();
+            let name = scripts
+                .data
+                .get(index)
+                .map(|script| script.name.as_str())
+                .unwrap_or_default();
+            format!("Script {:0>3} ({name})", index + 1)
+        }
+        Location::Element { id } => {
+            let system = update_state.data.system();
+            let name = system
+                .elements
+                .get(id)
+                .map(String::as_str)
+                .unwrap_or_default();
+            format!("Element {id:0>3} ({name})")
+        }
+        Location::System => "System".to_string(),
+    }
+}
+
+pub(crate) fn open_location(update_state: &mut luminol_core::UpdateState<'_>, location: Location) {
+    match location {
+        Location::MapEvent {
+            map_id, event_id, ..
+        } => match crate::tabs::map::Tab::new(map_id, update_state) {
+            Ok(mut tab) => {
+                tab.view.selected_event_id = Some(event_id);
+                update_state.edit_tabs.add_tab(tab);
+            }
+            Err(e) => update_state.toasts.error(e.to_string()),
+        },
+        Location::Map { map_id } => match crate::tabs::map::Tab::new(map_id, update_state) {
+            Ok(tab) => update_state.edit_tabs.add_tab(tab),
+            Err(e) => update_state.toasts.error(e.to_string()),
+        },
+        Location::CommonEvent { index }
+        | Location::Database {
+            target: Target::CommonEvent,
+            index,
+        } => {
+            let window = super::common_event_edit::Window::with_event(
+                index,
+                &update_state.data.common_events().data[index],
+            );
+            update_state.edit_windows.add_window(window);
+        }
+        Location::Database {
+            target: Target::Item,
+            index,
+        } => {
+            let window = super::items::Window::with_item(index);
+            update_state.edit_windows.add_window(window);
+        }
+        Location::Script { index } => {
+            let script_text = update_state.data.scripts().data[index].script_text.clone();
+            update_state
+                .edit_windows
+                .add_window(super::script_edit::Window::with_script(index, script_text));
+        }
+        // The other databases don't have editors yet
+        Location::Troop { .. }
+        | Location::Database { .. }
+        | Location::Element { .. }
+        | Location::System => {}
+    }
+}
+
+/// Can [`open_location`] open an editor for this location?
+pub(crate) fn is_openable(location: Location) -> bool {
+    matches!(
+        location,
+        Location::MapEvent { .. }
+            | Location::Map { .. }
+            | Location::CommonEvent { .. }
+            | Location::Script { .. }
+            | Location::Database {
+                target: Target::CommonEvent | Target::Item,
+                ..
+            }
+    )
+}
@@ -0,0 +1,115 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+/// The history window.
+/// Lists the edits that can be undone and redone, and jumps back or forward to any of them.
+#[derive(Default)]
+pub struct Window {}
+
+impl luminol_core::Window for Window {
+    fn id(&self) -> egui::Id {
+        egui::Id::new("luminol_history")
+    }
+
+    fn name(&self) -> String {
+        "History".to_string()
+    }
+
+    fn show(
+        &mut self,
+        ctx: &egui::Context,
+        open: &mut bool,
+        update_state: &mut luminol_core::UpdateState<'_>,
+    ) {
+        let mut window_open = true;
+        egui::Window::new(self.name())
+            .id(self.id())
+            .open(&mut window_open)
+            .default_width(300.)
+            .show(ctx, |ui| {
+                let undo_len = update_state.history.undo_entries().len();
+                let redo_len = update_state.history.redo_entries().len();
+                let mut travel_to = None;
+
+                // Unlike the shortcuts, these undo and redo edits made anywhere
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(undo_len > 0, egui::Button::new("Undo"))
+                        .clicked()
+                    {
+                        travel_to = Some(undo_len - 1);
+                    }
+                    if ui
+                        .add_enabled(redo_len > 0, egui::Button::new("Redo"))
+                        .clicked()
+                    {
+                        travel_to = Some(undo_len + 1);
+                    }
+                });
+
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false, true])
+                    .max_height(400.)
+                    .show(ui, |ui| {
+                        if ui
+                            .selectable_label(undo_len == 0, "Initial state")
+                            .clicked()
+                        {
+                            travel_to = Some(0);
+                        }
+
+                        for (index, entry) in update_state.history.undo_entries().enumerate() {
+                            if ui
+                                .selectable_label(index + 1 == undo_len, entry.description.as_str())
+                                .clicked()
+                            {
+                                travel_to = Some(index + 1);
+                            }
+                        }
+
+                        // Edits that were undone are greyed out
+                        for (index, entry) in update_state.history.redo_entries().enumerate() {
+                            let text = egui::RichText::new(entry.description.as_str())
+                                .color(ui.visuals().weak_text_color());
+                            if ui.selectable_label(false, text).clicked() {
+                                travel_to = Some(undo_len + index + 1);
+                            }
+                        }
+                    });
+
+                if let Some(len) = travel_to {
+                    update_state.history.travel_to(len, update_state.data);
+                    update_state.modified.set(true);
+                }
+            });
+
+        *open = window_open;
+    }
+
+    fn requires_filesystem(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,289 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+use std::io::Read;
+
+use luminol_core::image_import::Kind;
+use strum::IntoEnumIterator;
+
+type PickPromise = poll_promise::Promise<luminol_filesystem::Result<(Vec<u8>, String)>>;
+
+/// The extensions of the image formats that can be imported.
+const EXTENSIONS: &[&str] = &["png", "bmp", "jpg", "jpeg", "gif", "tga", "tiff", "webp"];
+
+struct Source {
+    filename: String,
+    image: image::RgbaImage,
+}
+
+/// The image importer.
+/// Converts images to PNG, optionally replacing a background colour with transparency, and saves
+/// them in the right `Graphics` folder after checking their size.
+pub struct Window {
+    pick_promise: Option<PickPromise>,
+    source: Option<Source>,
+
+    kind: Kind,
+    name: String,
+    use_color_key: bool,
+    color_key: [u8; 3],
+
+    preview: Option<egui::TextureHandle>,
+}
+
+impl Default for Window {
+    fn default() -> Self {
+        Self {
+            pick_promise: None,
+            source: None,
+            kind: Kind::default(),
+            name: String::new(),
+            use_color_key: false,
+            // Magenta is what most RPG Maker resources use
+            color_key: [255, 0, 255],
+            preview: None,
+        }
+    }
+}
+
+impl Window {
+    fn pick_file(&mut self) {
+        self.pick_promise = Some(luminol_core::spawn_future(async move {
+            let (mut file, name) =
+                luminol_filesystem::host::File::from_file_picker("Images", EXTENSIONS).await?;
+            let mut bytes = Vec::new();
+            file.read_to_end(&mut bytes)?;
+            Ok((bytes, name))
+        }));
+    }
+
+    /// The image as it will be saved.
+    fn processed(&self) -> Option<image::RgbaImage> {
+        let source = self.source.as_ref()?;
+        let mut image = source.image.clone();
+        if self.use_color_key {
+            luminol_core::image_import::color_key_to_alpha(&mut image, self.color_key);
+        }
+        Some(image)
+    }
+
+    fn update_preview(&mut self, ctx: &egui::Context) {
+        self.preview = self.processed().map(|image| {
+            let size = [image.width() as usize, image.height() as usize];
+            ctx.load_texture(
+                "luminol_image_import_preview",
+                egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw()),
+                egui::TextureOptions::NEAREST,
+            )
+        });
+    }
+
+    fn import(&mut self, update_state: &mut luminol_core::UpdateState<'_>) {
+        let Some(image) = self.processed() else {
+            return;
+        };
+        match luminol_core::image_import::import(
+            update_state.filesystem,
+            &image,
+            self.kind,
+            &self.name,
+        ) {
+            Ok(path) => {
+                luminol_core::file_watcher::reload_graphic(update_state, &path);
+                update_state.toasts.info(format!("Imported {path}"));
+            }
+            Err(e) => update_state
+                .toasts
+                .error(format!("Error importing {}: {e:#}", self.name)),
+        }
+    }
+}
+
+impl luminol_core::Window for Window {
+    fn name(&self) -> String {
+        "Import Image".to_string()
+    }
+
+    fn id(&self) -> egui::Id {
+        egui::Id::new("luminol_image_import")
+    }
+
+    fn show(
+        &mut self,
+        ctx: &egui::Context,
+        open: &mut bool,
+        update_state: &mut luminol_core::UpdateState<'_>,
+    ) {
+        if let Some(p) = self.pick_promise.take() {
+            match p.try_take() {
+                Ok(Ok((bytes, filename))) => match luminol_core::image_import::decode(&bytes) {
+                    Ok(image) => {
+                        self.name = camino::Utf8Path::new(&filename)
+                            .file_stem()
+                            .unwrap_or_default()
+                            .to_string();
+                        self.source = Some(Source { filename, image });
+                        self.update_preview(ctx);
+                    }
+                    Err(e) => update_state
+                        .toasts
+                        .error(format!("Error loading {filename}: {e:#}")),
+                },
+                Ok(Err(e)) => {
+                    if !matches!(e, luminol_filesystem::Error::CancelledLoading) {
+                        update_state.toasts.error(e.to_string())
+                    }
+                }
+                Err(p) => self.pick_promise = Some(p),
+            }
+        }
+
+        let mut window_open = true;
+        egui::Window::new(self.name())
+            .id(self.id())
+            .open(&mut window_open)
+            .default_width(400.)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if self.pick_promise.is_some() {
+                        ui.spinner();
+                    } else if ui.button("Choose image...").clicked() {
+                        self.pick_file();
+                    }
+                    if let Some(source) = &self.source {
+                        ui.label(format!(
+                            "{} ({}×{})",
+                            source.filename,
+                            source.image.width(),
+                            source.image.height()
+                        ));
+                    }
+                });
+
+                let Some(source) = &self.source else {
+                    return;
+                };
+                let (width, height) = source.image.dimensions();
+                let top_left = source.image.get_pixel(0, 0).0;
+
+                egui::Grid::new("luminol_image_import_grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Type");
+                        egui::ComboBox::from_id_source("luminol_image_import_kind")
+                            .selected_text(self.kind.to_string())
+                            .show_ui(ui, |ui| {
+                                for kind in Kind::iter() {
+                                    ui.selectable_value(&mut self.kind, kind, kind.to_string());
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label("Name");
+                        ui.text_edit_singleline(&mut self.name);
+                        ui.end_row();
+
+                        ui.label("Transparency");
+                        ui.horizontal(|ui| {
+                            let mut changed = ui
+                                .checkbox(&mut self.use_color_key, "Make this colour transparent")
+                                .changed();
+                            ui.add_enabled_ui(self.use_color_key, |ui| {
+                                changed |= ui.color_edit_button_srgb(&mut self.color_key).changed();
+                                if ui
+                                    .button("Use top-left pixel")
+                                    .on_hover_text(
+                                        "Most sprites have their background colour there",
+                                    )
+                                    .clicked()
+                                {
+                                    self.color_key = [top_left[0], top_left[1], top_left[2]];
+                                    changed = true;
+                                }
+                            });
+                            if changed {
+                                self.update_preview(ctx);
+                            }
+                        });
+                        ui.end_row();
+                    });
+
+                let problem = self.kind.size_problem(width, height);
+                if let Some(problem) = &problem {
+                    ui.colored_label(ui.visuals().error_fg_color, problem);
+                }
+
+                let name_problem = luminol_core::image_import::name_problem(&self.name);
+                if let Some(problem) = &name_problem {
+                    ui.colored_label(ui.visuals().error_fg_color, problem);
+                }
+
+                let existing = if self.name.is_empty() || name_problem.is_some() {
+                    None
+                } else {
+                    luminol_core::image_import::existing(
+                        update_state.filesystem,
+                        self.kind,
+                        &self.name,
+                    )
+                    .ok()
+                    .flatten()
+                };
+                if let Some((path, source)) = &existing {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        match source {
+                            luminol_filesystem::project::Source::Project => {
+                                format!("This replaces {path}")
+                            }
+                            _ => format!("This overrides {path} from the {source}"),
+                        },
+                    );
+                }
+
+                if let Some(preview) = &self.preview {
+                    egui::ScrollArea::both().max_height(384.).show(ui, |ui| {
+                        ui.add(egui::Image::from_texture(preview).fit_to_original_size(1.));
+                    });
+                }
+
+                ui.separator();
+
+                let can_import =
+                    problem.is_none() && name_problem.is_none() && !self.name.is_empty();
+                if ui
+                    .add_enabled(can_import, egui::Button::new("Import"))
+                    .clicked()
+                {
+                    self.import(update_state);
+                }
+            });
+
+        *open = window_open;
+    }
+
+    fn requires_filesystem(&self) -> bool {
+        true
+    }
+}
@@ -23,9 +23,10 @@
 // Program grant you additional permission to convey the resulting work.
 
 /// Database - Items management window.
+///
+/// Edits are made directly to the data cache and recorded in the history.
+#[derive(Default)]
 pub struct Window {
-    // ? Items ?
-    items: Vec<luminol_data::rpg::Item>,
     selected_item: usize,
 
     // ? Icon Graphic Picker ?
@@ -36,23 +37,18 @@ pub struct Window {
 }
 
 impl Window {
-    pub fn new(data_cache: &luminol_core::Data) -> Self {
-        let items = data_cache.items().data.clone();
-
+    /// Creates an items window with the item at `index` selected.
+    pub fn with_item(index: usize) -> Self {
         Self {
-            items,
-            selected_item: 0,
-
-            _icon_picker: None,
-
-            _menu_se_picker: None,
+            selected_item: index,
+            ..Default::default()
         }
     }
 }
 
 impl luminol_core::Window for Window {
     fn name(&self) -> String {
-        format!("Editing item {}", self.items[self.selected_item].name)
+        "Items".to_string()
     }
 
     fn id(&self) -> egui::Id {
@@ -69,40 +65,37 @@ impl luminol_core::Window for Window {
         open: &mut bool,
         update_state: &mut luminol_core::UpdateState<'_>,
     ) {
-        let _selected_item = &self.items[self.selected_item];
-        let _animations = update_state.data.animations();
-
-        let _common_events = update_state.data.common_events();
-
-        /*#[allow(clippy::cast_sign_loss)]
-        if animations
-            .get(selected_item.animation1_id as usize)
-            .is_none()
-        {
-            info.toasts.error(format!(
-                "Tried to get an animation with an ID of `{}`, but it doesn't exist.",
-                selected_item.animation1_id
-            ));
+        let mut items = update_state.data.items();
+        if items.data.is_empty() {
+            *open = false;
             return;
-        }*/
+        }
+        self.selected_item = self.selected_item.min(items.data.len() - 1);
+        let index = self.selected_item;
+        let previous = items.data[index].clone();
+        let scope = self.id();
+        let mut edited = None;
 
-        egui::Window::new(self.name())
+        egui::Window::new(format!("Editing item {}", previous.name))
             .id(egui::Id::new("item_editor"))
             .default_width(480.)
             .open(open)
             .show(ctx, |ui| {
+                update_state.history.focus_on_click(ui, scope);
+
                 egui::SidePanel::left(egui::Id::new("item_edit_sidepanel")).show_inside(ui, |ui| {
                     ui.label("Items");
                     egui::ScrollArea::both().max_height(600.).show_rows(
                         ui,
                         ui.text_style_height(&egui::TextStyle::Body),
-                        self.items.len(),
+                        items.data.len(),
                         |ui, rows| {
-                            for (id, item) in self.items[rows].iter().enumerate() {
+                            for (id, item) in items.data[rows.clone()].iter().enumerate() {
+                                let id = id + rows.start;
                                 ui.selectable_value(
                                     &mut self.selected_item,
                                     id,
-                                    format!("{:0>3}: {}", id, item.name),
+                                    format!("{:0>3}: {}", id + 1, item.name),
                                 );
                             }
                         },
@@ -112,23 +105,34 @@ impl luminol_core::Window for Window {
                         eprintln!("`Change maximum...` button trigger");
                     }
                 });
-                let selected_item = &mut self.items[self.selected_item];
+                let selected_item = &mut items.data[index];
                 egui::Grid::new("item_edit_central_grid").show(ui, |ui| {
-                    ui.add(luminol_components::Field::new(
-                        "Name",
-                        egui::TextEdit::singleline(&mut selected_item.name),
-                    ));
-
+                    ui.label("Name:");
+                    let response = ui.text_edit_singleline(&mut selected_item.name);
+                    if response.changed() {
+                        edited = Some(response.id);
+                    }
                     ui.end_row();
 
-                    ui.add(luminol_components::Field::new(
-                        "Description",
-                        egui::TextEdit::singleline(&mut selected_item.description),
-                    ));
+                    ui.label("Description:");
+                    let response = ui.text_edit_singleline(&mut selected_item.description);
+                    if response.changed() {
+                        edited = Some(response.id);
+                    }
                     ui.end_row();
-
-                    egui::Grid::new("item_edit_central_left_grid").show(ui, |_ui| {});
                 });
             });
+
+        if let Some(field_id) = edited {
+            items.modified = true;
+            update_state.modified.set(true);
+            update_state.history.push_merged(
+                scope,
+                // Text fields have the same id for every item
+                field_id.with(index),
+                format!("Edit item {}", previous.name),
+                luminol_core::history::Swap::item(index, previous),
+            );
+        }
     }
 }
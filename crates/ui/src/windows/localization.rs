@@ -0,0 +1,273 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+use luminol_core::localization::{ImportReport, Translation};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Po,
+    Csv,
+}
+
+type ImportPromise = poll_promise::Promise<
+    luminol_filesystem::Result<(
+        luminol_filesystem::host::File,
+        String,
+        luminol_filesystem::host::FileSystem,
+    )>,
+>;
+
+/// The localization window.
+/// Extracts every player-facing string to a translation file, and imports translated files into a copy of the project.
+pub struct Window {
+    format: Format,
+    save_promise: Option<poll_promise::Promise<luminol_filesystem::Result<()>>>,
+    import_promise: Option<ImportPromise>,
+    report: Option<ImportReport>,
+}
+
+impl Default for Window {
+    fn default() -> Self {
+        Self {
+            format: Format::Po,
+            save_promise: None,
+            import_promise: None,
+            report: None,
+        }
+    }
+}
+
+impl Window {
+    /// Extracts the strings and writes them to a temporary file.
+    fn extract(
+        &self,
+        update_state: &luminol_core::UpdateState<'_>,
+    ) -> anyhow::Result<luminol_filesystem::host::File> {
+        let entries =
+            luminol_core::localization::extract(update_state.data, update_state.filesystem);
+        let text = match self.format {
+            Format::Po => luminol_core::localization::to_po(&entries),
+            Format::Csv => luminol_core::localization::to_csv(&entries),
+        };
+
+        let mut file = luminol_filesystem::host::File::new()?;
+        file.write_all(text.as_bytes())?;
+        file.flush()?;
+        Ok(file)
+    }
+
+    /// Copies the project to `destination` along with any unsaved changes, then loads the copy's
+    /// data, translates it and saves it over the copy.
+    fn import(
+        update_state: &luminol_core::UpdateState<'_>,
+        mut file: luminol_filesystem::host::File,
+        filename: &str,
+        destination: &luminol_filesystem::host::FileSystem,
+    ) -> anyhow::Result<ImportReport> {
+        let mut text = String::new();
+        file.read_to_string(&mut text)?;
+        let translations: HashMap<String, Translation> = if filename.ends_with(".csv") {
+            luminol_core::localization::parse_csv(&text)?
+        } else {
+            luminol_core::localization::parse_po(&text)?
+        };
+
+        let host = update_state
+            .filesystem
+            .host()
+            .ok_or(luminol_filesystem::Error::NotLoaded)?;
+        #[cfg(not(target_arch = "wasm32"))]
+        if destination.root_path().starts_with(host.root_path()) {
+            anyhow::bail!("The copy can't be placed inside the project folder");
+        }
+        luminol_filesystem::copy_dir(&host, destination, "")?;
+
+        let mut config = update_state
+            .project_config
+            .clone()
+            .ok_or(luminol_filesystem::Error::NotLoaded)?;
+        // Strings are extracted from the data in memory, so the copy has to match it
+        update_state
+            .data
+            .write_modified(destination, camino::Utf8Path::new("Data"), &config)?;
+
+        let mut data = luminol_core::Data::default();
+        data.load(destination, &mut config)?;
+
+        let report = luminol_core::localization::import(&data, destination, &translations);
+        data.save(destination, &config)?;
+
+        Ok(report)
+    }
+}
+
+impl luminol_core::Window for Window {
+    fn id(&self) -> egui::Id {
+        egui::Id::new("luminol_localization")
+    }
+
+    fn name(&self) -> String {
+        "Localization".to_string()
+    }
+
+    fn show(
+        &mut self,
+        ctx: &egui::Context,
+        open: &mut bool,
+        update_state: &mut luminol_core::UpdateState<'_>,
+    ) {
+        let mut window_open = true;
+        egui::Window::new(self.name())
+            .id(self.id())
+            .open(&mut window_open)
+            .default_width(400.)
+            .show(ctx, |ui| {
+                ui.heading("Extract");
+                ui.label(
+                    "Writes every message, choice, database name and description, \
+                     term, element and map name to a file for translators.",
+                );
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.format, Format::Po, "gettext (.po)");
+                    ui.radio_value(&mut self.format, Format::Csv, "CSV");
+                });
+
+                if self.save_promise.is_some() {
+                    ui.spinner();
+                } else if ui.button("Extract").clicked() {
+                    match self.extract(update_state) {
+                        Ok(file) => {
+                            let (filename, filter) = match self.format {
+                                Format::Po => ("strings.pot", "PO templates"),
+                                Format::Csv => ("strings.csv", "CSV files"),
+                            };
+                            self.save_promise = Some(luminol_core::spawn_future(async move {
+                                file.save(filename, filter).await
+                            }));
+                        }
+                        Err(e) => update_state
+                            .toasts
+                            .error(format!("Error extracting strings: {e}")),
+                    }
+                }
+
+                ui.separator();
+
+                ui.heading("Import");
+                ui.label(
+                    "Pick a translated file, then an empty folder. \
+                     The project is copied there and the copy is translated.",
+                );
+
+                if self.import_promise.is_some() {
+                    ui.spinner();
+                } else if ui.button("Import into a copy").clicked() {
+                    self.import_promise = Some(luminol_core::spawn_future(async move {
+                        let (file, filename) = luminol_filesystem::host::File::from_file_picker(
+                            "Translation files",
+                            &["po", "csv"],
+                        )
+                        .await?;
+                        let destination =
+                            luminol_filesystem::host::FileSystem::from_folder_picker().await?;
+                        Ok((file, filename, destination))
+                    }));
+                }
+
+                if let Some(report) = &self.report {
+                    ui.label(format!(
+                        "{} strings translated, {} without a translation",
+                        report.translated, report.untranslated
+                    ));
+
+                    if !report.changed.is_empty() {
+                        ui.collapsing(
+                            format!("{} strings changed since extraction", report.changed.len()),
+                            |ui| {
+                                for key in &report.changed {
+                                    ui.label(key);
+                                }
+                            },
+                        );
+                    }
+
+                    if !report.missing.is_empty() {
+                        ui.collapsing(
+                            format!("{} strings no longer exist", report.missing.len()),
+                            |ui| {
+                                for key in &report.missing {
+                                    ui.label(key);
+                                }
+                            },
+                        );
+                    }
+                }
+            });
+
+        if let Some(p) = self.save_promise.take() {
+            match p.try_take() {
+                Ok(Ok(())) => update_state.toasts.info("Extracted strings successfully!"),
+                Ok(Err(e)) => {
+                    if !matches!(e, luminol_filesystem::Error::CancelledLoading) {
+                        update_state.toasts.error(e.to_string())
+                    }
+                }
+                Err(p) => self.save_promise = Some(p),
+            }
+        }
+
+        if let Some(p) = self.import_promise.take() {
+            match p.try_take() {
+                Ok(Ok((file, filename, destination))) => {
+                    match Self::import(update_state, file, &filename, &destination) {
+                        Ok(report) => {
+                            update_state
+                                .toasts
+                                .info("Imported translations into the copy successfully!");
+                            self.report = Some(report);
+                        }
+                        Err(e) => update_state
+                            .toasts
+                            .error(format!("Error importing translations: {e}")),
+                    }
+                }
+                Ok(Err(e)) => {
+                    if !matches!(e, luminol_filesystem::Error::CancelledLoading) {
+                        update_state.toasts.error(e.to_string())
+                    }
+                }
+                Err(p) => self.import_promise = Some(p),
+            }
+        }
+
+        *open = window_open;
+    }
+
+    fn requires_filesystem(&self) -> bool {
+        true
+    }
+}
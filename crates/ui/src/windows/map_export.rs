@@ -0,0 +1,149 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+use std::io::Write;
+
+/// The map image exporter.
+/// Renders a map into a PNG image and saves it to a location of the user's choice.
+pub struct Window {
+    map_id: usize,
+    options: luminol_graphics::compositor::MapImageOptions,
+    save_promise: Option<poll_promise::Promise<luminol_filesystem::Result<()>>>,
+}
+
+impl Window {
+    pub fn new(map_id: usize) -> Self {
+        Self {
+            map_id,
+            options: Default::default(),
+            save_promise: None,
+        }
+    }
+
+    /// Renders the map and writes it to a temporary file as a PNG image. Graphics that couldn't be
+    /// loaded are shown as warnings.
+    fn render(
+        &self,
+        update_state: &mut luminol_core::UpdateState<'_>,
+    ) -> anyhow::Result<luminol_filesystem::host::File> {
+        let map = update_state
+            .data
+            .get_or_load_map(self.map_id, update_state.filesystem);
+        let tilesets = update_state.data.tilesets();
+        let tileset = &tilesets.data[map.tileset_id];
+
+        let luminol_graphics::compositor::MapImage { image, warnings } =
+            luminol_graphics::compositor::render_map(
+                update_state.filesystem,
+                &map,
+                tileset,
+                &self.options,
+            );
+        for warning in warnings {
+            update_state.toasts.warning(warning);
+        }
+
+        let mut bytes = Vec::new();
+        image.write_to(
+            &mut std::io::Cursor::new(&mut bytes),
+            image::ImageOutputFormat::Png,
+        )?;
+
+        let mut file = luminol_filesystem::host::File::new()?;
+        file.write_all(&bytes)?;
+        file.flush()?;
+        Ok(file)
+    }
+}
+
+impl luminol_core::Window for Window {
+    fn id(&self) -> egui::Id {
+        egui::Id::new("luminol_map_export").with(self.map_id)
+    }
+
+    fn name(&self) -> String {
+        format!("Export Map {} as PNG", self.map_id)
+    }
+
+    fn show(
+        &mut self,
+        ctx: &egui::Context,
+        open: &mut bool,
+        update_state: &mut luminol_core::UpdateState<'_>,
+    ) {
+        let mut window_open = true;
+        egui::Window::new(self.name())
+            .id(self.id())
+            .open(&mut window_open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.checkbox(&mut self.options.events, "Events");
+                ui.checkbox(&mut self.options.panorama, "Panorama");
+                ui.checkbox(&mut self.options.fog, "Fog");
+                ui.checkbox(&mut self.options.passages, "Passages")
+                    .on_hover_text("Draw the passage overlay from the collision view");
+                ui.add(
+                    egui::Slider::new(&mut self.options.scale, 0.125..=4.)
+                        .text("Scale")
+                        .logarithmic(true),
+                );
+
+                ui.separator();
+
+                if self.save_promise.is_some() {
+                    ui.spinner();
+                } else if ui.button("Export").clicked() {
+                    match self.render(update_state) {
+                        Ok(file) => {
+                            let filename = format!("Map{:0>3}.png", self.map_id);
+                            self.save_promise = Some(luminol_core::spawn_future(async move {
+                                file.save(&filename, "PNG images").await
+                            }));
+                        }
+                        Err(e) => update_state
+                            .toasts
+                            .error(format!("Error exporting the map: {e}")),
+                    }
+                }
+            });
+
+        if let Some(p) = self.save_promise.take() {
+            match p.try_take() {
+                Ok(Ok(())) => update_state.toasts.info("Exported map successfully!"),
+                Ok(Err(e)) => {
+                    if !matches!(e, luminol_filesystem::Error::CancelledLoading) {
+                        update_state.toasts.error(e.to_string())
+                    }
+                }
+                Err(p) => self.save_promise = Some(p),
+            }
+        }
+
+        *open = window_open;
+    }
+
+    fn requires_filesystem(&self) -> bool {
+        true
+    }
+}
@@ -36,6 +36,7 @@ impl Window {
         children_data: &BTreeMap<usize, BTreeSet<usize>>,
         mapinfos: &mut luminol_data::rpg::MapInfos,
         open_map_id: &mut Option<usize>,
+        renamed: &mut Option<(usize, String)>,
         ui: &mut egui::Ui,
     ) {
         // We get the map name. It's assumed that there is in fact a map with this ID in mapinfos.
@@ -55,27 +56,42 @@ impl Window {
 
             header
                 .show_header(ui, |ui| {
-                    // Has the user
-                    if ui.text_edit_singleline(&mut map_info.name).double_clicked() {
-                        *open_map_id = Some(id)
-                    }
+                    Self::name_edit(id, map_info, open_map_id, renamed, ui);
                 })
                 .body(|ui| {
                     for id in children_data.get(&id).unwrap() {
                         // Render children.
-                        Self::render_submap(*id, children_data, mapinfos, open_map_id, ui);
+                        Self::render_submap(*id, children_data, mapinfos, open_map_id, renamed, ui);
                     }
                 });
         } else {
             // Just display a label otherwise.
             ui.horizontal(|ui| {
                 ui.add_space(ui.spacing().indent);
-                if ui.text_edit_singleline(&mut map_info.name).double_clicked() {
-                    *open_map_id = Some(id)
-                }
+                Self::name_edit(id, map_info, open_map_id, renamed, ui);
             });
         }
     }
+
+    fn name_edit(
+        id: usize,
+        map_info: &mut luminol_data::rpg::MapInfo,
+        open_map_id: &mut Option<usize>,
+        renamed: &mut Option<(usize, String)>,
+        ui: &mut egui::Ui,
+    ) {
+        let previous_name = map_info.name.clone();
+        let response = ui.text_edit_singleline(&mut map_info.name);
+
+        // Has the user renamed the map?
+        if response.changed() {
+            *renamed = Some((id, previous_name));
+        }
+        // Has the user double clicked the map?
+        if response.double_clicked() {
+            *open_map_id = Some(id)
+        }
+    }
 }
 
 impl luminol_core::Window for Window {
@@ -93,6 +109,8 @@ impl luminol_core::Window for Window {
         egui::Window::new("Map Picker")
             .open(&mut window_open)
             .show(ctx, |ui| {
+                update_state.history.focus_on_click(ui, self.id());
+
                 egui::ScrollArea::both()
                     .auto_shrink([false; 2])
                     .show(ui, |ui| {
@@ -111,6 +129,7 @@ impl luminol_core::Window for Window {
                         children_data.entry(0).or_default(); // If there is no `0` entry (i.e. there are no maps) then add one.
 
                         let mut open_map_id = None;
+                        let mut renamed = None;
 
                         // Now we can actually render all maps.
                         egui::CollapsingHeader::new("root")
@@ -124,13 +143,27 @@ impl luminol_core::Window for Window {
                                         &children_data,
                                         &mut mapinfos,
                                         &mut open_map_id,
+                                        &mut renamed,
                                         ui,
                                     );
                                 }
                             });
 
+                        if renamed.is_some() {
+                            mapinfos.modified = true;
+                        }
                         drop(mapinfos);
 
+                        if let Some((id, previous_name)) = renamed {
+                            update_state.modified.set(true);
+                            update_state.history.push_merged(
+                                self.id(),
+                                egui::Id::new("luminol_map_name").with(id),
+                                format!("Rename map {id}"),
+                                luminol_core::history::Swap::map_name(id, previous_name),
+                            );
+                        }
+
                         if let Some(id) = open_map_id {
                             match crate::tabs::map::Tab::new(id, update_state) {
                                 Ok(tab) => update_state.edit_tabs.add_tab(tab),
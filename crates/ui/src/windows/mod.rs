@@ -27,6 +27,10 @@ pub mod about;
 pub mod appearance;
 /// The archive manager for creating and extracting RGSSAD archives.
 pub mod archive_manager;
+/// Inspecting tileset atlases and autotile shapes.
+pub mod atlas_inspector;
+/// Browsing and restoring backup snapshots.
+pub mod backups;
 /// The common event editor.
 pub mod common_event_edit;
 /// Config window
@@ -34,18 +38,46 @@ pub mod config_window;
 /// Playtest console
 #[cfg(not(target_arch = "wasm32"))]
 pub mod console;
+/// The data file diff and merge tool.
+pub mod data_merge;
+/// The dialogue script exporter.
+pub mod dialogue_export;
 /// The event editor.
 pub mod event_edit;
+/// Reloading data files changed outside of Luminol.
+pub mod external_changes;
+/// Find references to switches, variables and database entries.
+pub mod find_references;
 pub mod global_config_window;
+/// The undo history.
+pub mod history;
+/// Importing images into the Graphics folder.
+pub mod image_import;
 /// The item editor.
 pub mod items;
+/// Extracting and importing translations.
+pub mod localization;
+/// The map image exporter.
+pub mod map_export;
 /// The map picker.
 pub mod map_picker;
 /// Misc windows.
 pub mod misc;
 /// New project window
 pub mod new_project;
+/// Finding asset paths that break on case-sensitive filesystems.
+pub mod portability;
+/// Exporting the project as a zip archive.
+pub mod project_export;
+/// Restoring autosaved data.
+pub mod recovery;
+/// Database reindexing.
+pub mod reindex;
+/// Registering, mounting and inspecting RTPs.
+pub mod rtp_manager;
 /// The script editor
 pub mod script_edit;
 /// The sound test.
 pub mod sound_test;
+/// Global search and replace.
+pub mod text_search;
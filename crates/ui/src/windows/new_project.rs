@@ -219,14 +219,7 @@ impl Window {
         // because we re-export host based on the platform specific filesystem, we don't actually need to change any of this code!
         let host_fs = luminol_filesystem::host::FileSystem::from_folder_picker().await?;
 
-        host_fs.create_dir("Audio")?;
-        host_fs.create_dir("Data")?;
-        host_fs.create_dir("Graphics")?;
-
-        host_fs.create_file(format!("{}.lumproj", config.project.project_name))?;
-
-        let mut data_cache = luminol_core::Data::from_defaults();
-        data_cache.save(&host_fs, &config)?;
+        let data_cache = luminol_core::project_manager::create_project(&host_fs, &config)?;
 
         if download_executable {
             Self::download_executable(&config, &host_fs, progress)
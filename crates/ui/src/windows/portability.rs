@@ -0,0 +1,165 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+use luminol_core::portability::Report;
+
+/// Lists files that only differ by case or extension, and asset references whose case doesn't
+/// match the file they refer to.
+/// Both work on Windows but break on Linux, e.g. when running the game with mkxp.
+#[derive(Default)]
+pub struct Window {
+    report: Option<Report>,
+}
+
+impl Window {
+    fn check(&mut self, update_state: &mut luminol_core::UpdateState<'_>) {
+        // Only the project folder is checked, RTPs are often installed somewhere case-insensitive
+        let Some(host) = update_state.filesystem.host() else {
+            update_state
+                .toasts
+                .error(luminol_filesystem::Error::NotLoaded.to_string());
+            return;
+        };
+
+        match luminol_core::portability::check(update_state.data, &host) {
+            Ok(report) => self.report = Some(report),
+            Err(e) => update_state
+                .toasts
+                .error(format!("Error checking project paths: {e}")),
+        }
+    }
+}
+
+impl luminol_core::Window for Window {
+    fn name(&self) -> String {
+        "Path Portability".to_string()
+    }
+
+    fn id(&self) -> egui::Id {
+        egui::Id::new("luminol_portability")
+    }
+
+    fn show(
+        &mut self,
+        ctx: &egui::Context,
+        open: &mut bool,
+        update_state: &mut luminol_core::UpdateState<'_>,
+    ) {
+        let mut window_open = true;
+        egui::Window::new(self.name())
+            .id(self.id())
+            .open(&mut window_open)
+            .default_width(500.)
+            .show(ctx, |ui| {
+                ui.label(
+                    "Finds paths that only work on case-insensitive filesystems like Windows. \
+                     Games running on Linux (e.g. with mkxp) can't find these files.",
+                );
+
+                let label = if self.report.is_some() {
+                    "Check again"
+                } else {
+                    "Check"
+                };
+                if ui.button(label).clicked() {
+                    self.check(update_state);
+                }
+
+                let Some(report) = &self.report else {
+                    return;
+                };
+
+                ui.separator();
+
+                if report.is_empty() {
+                    ui.label("No problems found");
+                    return;
+                }
+
+                let mut clicked = None;
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false, true])
+                    .max_height(384.)
+                    .show(ui, |ui| {
+                        egui::CollapsingHeader::new(format!(
+                            "Files differing only by case or extension ({})",
+                            report.collisions.len()
+                        ))
+                        .id_source("collisions")
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            for collision in report.collisions.iter() {
+                                ui.label(
+                                    collision
+                                        .paths
+                                        .iter()
+                                        .map(|p| p.as_str())
+                                        .collect::<Vec<_>>()
+                                        .join(", "),
+                                );
+                            }
+                        });
+
+                        egui::CollapsingHeader::new(format!(
+                            "References with the wrong case ({})",
+                            report.mismatches.len()
+                        ))
+                        .id_source("mismatches")
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            for (index, mismatch) in report.mismatches.iter().enumerate() {
+                                ui.push_id(index, |ui| {
+                                    ui.horizontal(|ui| {
+                                        let location = super::find_references::location_text(
+                                            update_state,
+                                            mismatch.location,
+                                        );
+                                        if ui
+                                            .link(format!("{location} ({})", mismatch.description))
+                                            .clicked()
+                                        {
+                                            clicked = Some(mismatch.location);
+                                        }
+                                        ui.label(format!(
+                                            "{} is {}",
+                                            mismatch.reference, mismatch.on_disk
+                                        ));
+                                    });
+                                });
+                            }
+                        });
+                    });
+
+                if let Some(location) = clicked {
+                    super::find_references::open_location(update_state, location);
+                }
+            });
+
+        *open = window_open;
+    }
+
+    fn requires_filesystem(&self) -> bool {
+        true
+    }
+}
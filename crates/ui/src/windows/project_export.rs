@@ -0,0 +1,142 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+use luminol_filesystem::FileSystem;
+use std::io::Write;
+
+/// The project exporter.
+/// Zips up the project folder and saves it to a location of the user's choice.
+#[derive(Default)]
+pub struct Window {
+    include_luminol_dir: bool,
+    save_promise: Option<poll_promise::Promise<luminol_filesystem::Result<()>>>,
+}
+
+impl Window {
+    /// Writes the project folder to a temporary file as a zip archive.
+    fn export(
+        &self,
+        update_state: &luminol_core::UpdateState<'_>,
+    ) -> anyhow::Result<luminol_filesystem::host::File> {
+        let host = update_state
+            .filesystem
+            .host()
+            .ok_or(luminol_filesystem::Error::NotLoaded)?;
+
+        let mut file = luminol_filesystem::host::File::new()?;
+        let archive = luminol_filesystem::zip::FileSystem::create(&mut file);
+        for entry in host.read_dir("")? {
+            if !self.include_luminol_dir && entry.path == ".luminol" {
+                continue;
+            }
+            // Version control history isn't part of the project, and is often bigger than it
+            if entry.path == ".git" {
+                continue;
+            }
+            if entry.metadata.is_file {
+                archive.write(&entry.path, host.read(&entry.path)?)?;
+            } else {
+                luminol_filesystem::copy_dir(&host, &archive, &entry.path)?;
+            }
+        }
+        archive.save()?;
+        drop(archive);
+
+        file.flush()?;
+        Ok(file)
+    }
+}
+
+impl luminol_core::Window for Window {
+    fn id(&self) -> egui::Id {
+        egui::Id::new("luminol_project_export")
+    }
+
+    fn name(&self) -> String {
+        "Export Project as Zip".to_string()
+    }
+
+    fn show(
+        &mut self,
+        ctx: &egui::Context,
+        open: &mut bool,
+        update_state: &mut luminol_core::UpdateState<'_>,
+    ) {
+        let mut window_open = true;
+        egui::Window::new(self.name())
+            .id(self.id())
+            .open(&mut window_open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.checkbox(&mut self.include_luminol_dir, "Include .luminol folder")
+                    .on_hover_text("Luminol's project settings, backups and autosaves");
+
+                if update_state.modified.get() {
+                    ui.label("Unsaved changes won't be included in the zip.");
+                }
+
+                ui.separator();
+
+                if self.save_promise.is_some() {
+                    ui.spinner();
+                } else if ui.button("Export").clicked() {
+                    match self.export(update_state) {
+                        Ok(file) => {
+                            let name = update_state
+                                .project_config
+                                .as_ref()
+                                .map(|config| config.project.project_name.as_str())
+                                .filter(|name| !name.is_empty())
+                                .unwrap_or("Project");
+                            let filename = format!("{name}.zip");
+                            self.save_promise = Some(luminol_core::spawn_future(async move {
+                                file.save(&filename, "Zip archives").await
+                            }));
+                        }
+                        Err(e) => update_state
+                            .toasts
+                            .error(format!("Error exporting the project: {e}")),
+                    }
+                }
+            });
+
+        if let Some(p) = self.save_promise.take() {
+            match p.try_take() {
+                Ok(Ok(())) => update_state.toasts.info("Exported project successfully!"),
+                Ok(Err(e)) => {
+                    if !matches!(e, luminol_filesystem::Error::CancelledLoading) {
+                        update_state.toasts.error(e.to_string())
+                    }
+                }
+                Err(p) => self.save_promise = Some(p),
+            }
+        }
+
+        *open = window_open;
+    }
+
+    fn requires_filesystem(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+use luminol_core::recovery::RecoveredFile;
+
+/// Offers to restore data that was autosaved but never saved or discarded,
+/// usually because Luminol crashed.
+pub struct Window {
+    files: Vec<(RecoveredFile, bool)>,
+}
+
+impl Window {
+    pub fn new(files: Vec<RecoveredFile>) -> Self {
+        Self {
+            files: files.into_iter().map(|file| (file, true)).collect(),
+        }
+    }
+}
+
+impl luminol_core::Window for Window {
+    fn id(&self) -> egui::Id {
+        egui::Id::new("luminol_recovery")
+    }
+
+    fn name(&self) -> String {
+        "Recover Unsaved Changes".to_string()
+    }
+
+    fn show(
+        &mut self,
+        ctx: &egui::Context,
+        open: &mut bool,
+        update_state: &mut luminol_core::UpdateState<'_>,
+    ) {
+        let mut window_open = true;
+        let mut should_close = false;
+
+        egui::Window::new(self.name())
+            .id(self.id())
+            .open(&mut window_open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(
+                    "This project has autosaved changes that were never saved. \
+                    Select the files you want to restore:",
+                );
+
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .max_height(300.)
+                    .show(ui, |ui| {
+                        for (file, selected) in &mut self.files {
+                            let text = if file.on_disk {
+                                file.filename.clone()
+                            } else {
+                                format!("{} (new)", file.filename)
+                            };
+                            ui.checkbox(selected, text);
+                        }
+                    });
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    let any_selected = self.files.iter().any(|(_, selected)| *selected);
+                    if ui
+                        .add_enabled(any_selected, egui::Button::new("Restore selected"))
+                        .clicked()
+                    {
+                        let files: Vec<_> = self
+                            .files
+                            .iter()
+                            .filter(|(_, selected)| *selected)
+                            .map(|(file, _)| file.clone())
+                            .collect();
+                        let config = update_state
+                            .project_config
+                            .as_ref()
+                            .expect("project not loaded");
+
+                        match luminol_core::recovery::restore(
+                            update_state.data,
+                            update_state.filesystem,
+                            config,
+                            &files,
+                        ) {
+                            Ok(()) => {
+                                update_state.modified.set(true);
+                                update_state
+                                    .toasts
+                                    .info(format!("Restored {} autosaved file(s)", files.len()));
+                                should_close = true;
+                            }
+                            Err(e) => update_state
+                                .toasts
+                                .error(format!("Error restoring autosaved data: {e:#}")),
+                        }
+                    }
+
+                    if ui.button("Discard").clicked() {
+                        if let Err(e) = luminol_core::recovery::clear(update_state.filesystem) {
+                            update_state
+                                .toasts
+                                .error(format!("Error discarding autosaved data: {e:#}"));
+                        }
+                        should_close = true;
+                    }
+                });
+            });
+
+        *open = window_open && !should_close;
+    }
+
+    fn requires_filesystem(&self) -> bool {
+        true
+    }
+}
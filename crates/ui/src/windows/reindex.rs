@@ -0,0 +1,206 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+use luminol_core::references::Target;
+use luminol_core::reindex::{Change, Operation};
+use strum::IntoEnumIterator;
+
+use super::find_references::{entry_name, is_openable, location_text, open_location};
+
+/// The database reindexing window.
+/// Inserts or deletes entries in the middle of a database while keeping every reference to the entries after it intact.
+pub struct Window {
+    target: Target,
+    insert: bool,
+    id: usize,
+    preview: Option<(Target, Operation, Vec<Change>)>,
+}
+
+impl Default for Window {
+    fn default() -> Self {
+        Self {
+            target: Target::default(),
+            insert: true,
+            id: 1,
+            preview: None,
+        }
+    }
+}
+
+impl Window {
+    fn operation(&self) -> Operation {
+        if self.insert {
+            Operation::Insert(self.id)
+        } else {
+            Operation::Delete(self.id)
+        }
+    }
+}
+
+impl luminol_core::Window for Window {
+    fn name(&self) -> String {
+        "Reindex Database".to_string()
+    }
+
+    fn id(&self) -> egui::Id {
+        egui::Id::new("luminol_reindex")
+    }
+
+    fn show(
+        &mut self,
+        ctx: &egui::Context,
+        open: &mut bool,
+        update_state: &mut luminol_core::UpdateState<'_>,
+    ) {
+        let mut window_open = true;
+        egui::Window::new(self.name())
+            .id(self.id())
+            .open(&mut window_open)
+            .default_width(500.)
+            .show(ctx, |ui| {
+                let count = luminol_core::reindex::entry_count(update_state.data, self.target);
+
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_source("luminol_reindex_target")
+                        .selected_text(self.target.to_string())
+                        .show_ui(ui, |ui| {
+                            for target in Target::iter() {
+                                ui.selectable_value(&mut self.target, target, target.to_string());
+                            }
+                        });
+
+                    ui.radio_value(&mut self.insert, true, "Insert at");
+                    ui.radio_value(&mut self.insert, false, "Delete");
+
+                    let max = if self.insert { count + 1 } else { count };
+                    ui.add(egui::DragValue::new(&mut self.id).clamp_range(1..=max.max(1)));
+                    if !self.insert || self.id <= count {
+                        ui.label(entry_name(update_state, self.target, self.id));
+                    }
+                });
+
+                let operation = self.operation();
+                let command_db = &update_state
+                    .project_config
+                    .as_ref()
+                    .expect("project not loaded")
+                    .command_db;
+
+                ui.horizontal(|ui| {
+                    if ui.button("Preview").clicked() {
+                        let changes = luminol_core::reindex::preview(
+                            update_state.data,
+                            update_state.filesystem,
+                            command_db,
+                            self.target,
+                            operation,
+                        );
+                        self.preview = Some((self.target, operation, changes));
+                    }
+
+                    let previewed = self
+                        .preview
+                        .as_ref()
+                        .is_some_and(|(t, o, _)| *t == self.target && *o == operation);
+                    if ui
+                        .add_enabled(previewed, egui::Button::new("Apply"))
+                        .on_disabled_hover_text("Preview the changes first")
+                        .clicked()
+                    {
+                        match luminol_core::reindex::apply(
+                            update_state.data,
+                            update_state.filesystem,
+                            command_db,
+                            self.target,
+                            operation,
+                        ) {
+                            Ok(rewritten) => {
+                                update_state.modified.set(true);
+                                // Entries moved, so edits in the history may point at the wrong ones
+                                update_state.history.clear();
+                                update_state
+                                    .toasts
+                                    .info(format!("Rewrote {rewritten} references"));
+                            }
+                            Err(e) => update_state.toasts.error(e.to_string()),
+                        }
+                        self.preview = None;
+                    }
+                });
+
+                ui.label(
+                    egui::RichText::new(
+                        "Close any open database or common event editors before applying, \
+                         otherwise they may overwrite the changes",
+                    )
+                    .weak(),
+                );
+
+                ui.separator();
+
+                let Some((_, _, ref changes)) = self.preview else {
+                    return;
+                };
+
+                ui.label(format!("{} references will be rewritten", changes.len()));
+
+                let mut clicked = None;
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false, true])
+                    .max_height(384.)
+                    .show(ui, |ui| {
+                        for change in changes {
+                            let new = change
+                                .new
+                                .map_or("(none)".to_string(), |id| format!("{id:0>3}"));
+                            let text = format!(
+                                "{}: {} ({:0>3} → {new})",
+                                location_text(update_state, change.location),
+                                change.description,
+                                change.old,
+                            );
+                            if ui
+                                .add_enabled(
+                                    is_openable(change.location),
+                                    egui::SelectableLabel::new(false, text),
+                                )
+                                .clicked()
+                            {
+                                clicked = Some(change.location);
+                            }
+                        }
+                    });
+
+                if let Some(location) = clicked {
+                    open_location(update_state, location);
+                }
+            });
+
+        *open = window_open;
+    }
+
+    fn requires_filesystem(&self) -> bool {
+        true
+    }
+}
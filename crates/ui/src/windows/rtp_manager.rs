@@ -0,0 +1,417 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+use luminol_filesystem::project::Source;
+use luminol_filesystem::FileSystem as _;
+
+#[cfg(not(target_arch = "wasm32"))]
+type PickPromise = poll_promise::Promise<luminol_filesystem::Result<camino::Utf8PathBuf>>;
+
+/// The RTP manager.
+/// Shows which RTPs the project uses and where they're loaded from, lets RTPs be registered by
+/// name, lists which files come from the project and which from an RTP, and copies the RTP
+/// assets the project uses into it.
+#[derive(Default)]
+pub struct Window {
+    #[cfg(not(target_arch = "wasm32"))]
+    new_name: String,
+    #[cfg(not(target_arch = "wasm32"))]
+    new_path: String,
+    /// The folder picker, and the name of the RTP it's picking a path for. `None` is the new RTP.
+    #[cfg(not(target_arch = "wasm32"))]
+    pick_promise: Option<(Option<String>, PickPromise)>,
+
+    directory: String,
+    listing: Vec<(camino::Utf8PathBuf, Option<Source>)>,
+
+    rtp_assets: Option<Vec<luminol_core::rtp_assets::Asset>>,
+    clear_rtps: bool,
+}
+
+impl Window {
+    fn mount_missing(&mut self, update_state: &mut luminol_core::UpdateState<'_>) {
+        let Some(config) = update_state.project_config.as_ref() else {
+            return;
+        };
+        let mounted = update_state.filesystem.sources().len();
+        match update_state
+            .filesystem
+            .mount_missing_rtps(config, update_state.global_config)
+        {
+            Ok(load_result) => {
+                for missing_rtp in load_result.missing_rtps {
+                    update_state.toasts.warning(format!(
+                        "Failed to find suitable path for the RTP {missing_rtp}"
+                    ));
+                }
+                if update_state.filesystem.sources().len() > mounted {
+                    // Anything that failed to load before may be in the RTP now
+                    update_state.graphics.texture_loader.clear();
+                    update_state.graphics.atlas_loader.clear();
+                    update_state.bytes_loader.forget_all();
+                    update_state.toasts.info(
+                        "Mounted RTPs. You may need to reopen maps/windows to see their graphics.",
+                    );
+                }
+            }
+            Err(e) => update_state
+                .toasts
+                .error(format!("Error mounting RTPs: {e}")),
+        }
+    }
+
+    fn copy_rtp_assets(&mut self, update_state: &mut luminol_core::UpdateState<'_>) {
+        let Some(assets) = self.rtp_assets.take() else {
+            return;
+        };
+        if let Err(e) = luminol_core::rtp_assets::copy(update_state.filesystem, &assets) {
+            update_state
+                .toasts
+                .error(format!("Error copying RTP assets: {e:#}"));
+            return;
+        }
+        update_state.toasts.info(format!(
+            "Copied {} RTP assets into the project",
+            assets.len()
+        ));
+
+        if self.clear_rtps {
+            if let Some(config) = update_state.project_config.as_mut() {
+                match luminol_core::rtp_assets::clear_rtps(update_state.filesystem, config) {
+                    Ok(()) => update_state
+                        .toasts
+                        .info("Removed the RTPs from Game.ini, reopen the project to unload them"),
+                    Err(e) => update_state
+                        .toasts
+                        .error(format!("Error removing the RTPs from Game.ini: {e:#}")),
+                }
+            }
+        }
+    }
+
+    fn standalone(&mut self, ui: &mut egui::Ui, update_state: &mut luminol_core::UpdateState<'_>) {
+        ui.label(
+            "Copies the graphics and audio the project's data uses from its RTPs into the project, \
+             so it can run without them. Assets only used by scripts aren't found.",
+        );
+
+        if ui.button("Find RTP assets").clicked() {
+            match luminol_core::rtp_assets::find(update_state.data, update_state.filesystem) {
+                Ok(assets) => self.rtp_assets = Some(assets),
+                Err(e) => update_state
+                    .toasts
+                    .error(format!("Error finding RTP assets: {e}")),
+            }
+        }
+
+        let Some(assets) = &self.rtp_assets else {
+            return;
+        };
+        if assets.is_empty() {
+            ui.label("The project doesn't use any assets from its RTPs.");
+            return;
+        }
+
+        ui.label(format!("{} assets are read from RTPs:", assets.len()));
+        egui::ScrollArea::vertical()
+            .id_source("luminol_rtp_manager_rtp_assets")
+            .auto_shrink([false, true])
+            .max_height(192.)
+            .show(ui, |ui| {
+                for asset in assets.iter() {
+                    ui.label(format!("{} ({})", asset.path, asset.rtp));
+                }
+            });
+
+        ui.checkbox(&mut self.clear_rtps, "Remove RTPs from Game.ini afterwards");
+        if ui.button("Copy into project").clicked() {
+            self.copy_rtp_assets(update_state);
+        }
+    }
+
+    fn list_directory(&mut self, update_state: &mut luminol_core::UpdateState<'_>) {
+        self.listing.clear();
+        let result = update_state
+            .filesystem
+            .read_dir(&self.directory)
+            .and_then(|entries| {
+                entries
+                    .into_iter()
+                    .map(|entry| {
+                        let source = update_state.filesystem.source_of(&entry.path)?;
+                        Ok((entry.path, source))
+                    })
+                    .collect::<luminol_filesystem::Result<Vec<_>>>()
+            });
+        match result {
+            Ok(mut listing) => {
+                listing.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+                self.listing = listing;
+            }
+            Err(e) => update_state
+                .toasts
+                .error(format!("Error listing {}: {e}", self.directory)),
+        }
+    }
+
+    fn project_rtps(
+        &mut self,
+        ui: &mut egui::Ui,
+        update_state: &mut luminol_core::UpdateState<'_>,
+    ) {
+        let Some(config) = update_state.project_config.as_ref() else {
+            return;
+        };
+        let names = luminol_filesystem::project::rtp_names(config);
+        if names.is_empty() {
+            ui.label("This project doesn't use any RTPs.");
+            return;
+        }
+
+        let sources = update_state.filesystem.sources();
+        let mut any_missing = false;
+        egui::Grid::new("luminol_rtp_manager_project_rtps")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                for name in names.iter() {
+                    ui.label(name);
+                    let path = sources.iter().find_map(|source| match source {
+                        Source::Rtp { name: n, path } if n == name => Some(path),
+                        _ => None,
+                    });
+                    match path {
+                        Some(path) => ui.label(path.as_str()),
+                        None => {
+                            any_missing = true;
+                            ui.colored_label(ui.visuals().warn_fg_color, "Missing")
+                        }
+                    };
+                    ui.end_row();
+                }
+            });
+
+        if any_missing
+            && ui
+                .button("Mount missing RTPs")
+                .on_hover_text("Looks for the missing RTPs again, without reopening the project")
+                .clicked()
+        {
+            self.mount_missing(update_state);
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn pick_folder(&mut self, name: Option<String>) {
+        let promise = luminol_core::spawn_future(async move {
+            let host = luminol_filesystem::host::FileSystem::from_folder_picker().await?;
+            Ok(host.root_path().to_path_buf())
+        });
+        self.pick_promise = Some((name, promise));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn registered_rtps(
+        &mut self,
+        ui: &mut egui::Ui,
+        update_state: &mut luminol_core::UpdateState<'_>,
+    ) {
+        ui.label("RTPs can be folders or zip archives. Paths set here are used instead of installed RTPs.");
+
+        let picking = self.pick_promise.is_some();
+        let mut names: Vec<_> = update_state
+            .global_config
+            .rtp_paths
+            .keys()
+            .cloned()
+            .collect();
+        names.sort_unstable();
+
+        let mut removed = None;
+        egui::Grid::new("luminol_rtp_manager_registered_rtps")
+            .num_columns(4)
+            .striped(true)
+            .show(ui, |ui| {
+                for name in names {
+                    ui.label(&name);
+                    if let Some(path) = update_state.global_config.rtp_paths.get_mut(&name) {
+                        ui.text_edit_singleline(path);
+                    }
+                    if ui
+                        .add_enabled(!picking, egui::Button::new("Browse..."))
+                        .clicked()
+                    {
+                        self.pick_folder(Some(name.clone()));
+                    }
+                    if ui.button("Remove").clicked() {
+                        removed = Some(name);
+                    }
+                    ui.end_row();
+                }
+
+                ui.add(egui::TextEdit::singleline(&mut self.new_name).hint_text("Name"));
+                ui.add(egui::TextEdit::singleline(&mut self.new_path).hint_text("Path"));
+                if ui
+                    .add_enabled(!picking, egui::Button::new("Browse..."))
+                    .clicked()
+                {
+                    self.pick_folder(None);
+                }
+                let can_add = !self.new_name.is_empty() && !self.new_path.is_empty();
+                if ui.add_enabled(can_add, egui::Button::new("Add")).clicked() {
+                    update_state.global_config.rtp_paths.insert(
+                        std::mem::take(&mut self.new_name),
+                        std::mem::take(&mut self.new_path),
+                    );
+                }
+                ui.end_row();
+            });
+
+        if let Some(name) = removed {
+            update_state.global_config.rtp_paths.remove(&name);
+        }
+
+        if let Some((name, p)) = self.pick_promise.take() {
+            match p.try_take() {
+                Ok(Ok(path)) => match name {
+                    Some(name) => {
+                        update_state
+                            .global_config
+                            .rtp_paths
+                            .insert(name, path.into_string());
+                    }
+                    None => self.new_path = path.into_string(),
+                },
+                Ok(Err(e)) => {
+                    if !matches!(e, luminol_filesystem::Error::CancelledLoading) {
+                        update_state.toasts.error(e.to_string())
+                    }
+                }
+                Err(p) => self.pick_promise = Some((name, p)),
+            }
+        }
+    }
+
+    fn file_sources(
+        &mut self,
+        ui: &mut egui::Ui,
+        update_state: &mut luminol_core::UpdateState<'_>,
+    ) {
+        ui.label("Files are looked up in this order:");
+        for (index, source) in update_state.filesystem.sources().iter().enumerate() {
+            match source {
+                Source::Rtp { path, .. } => ui.label(format!("{}. {source} ({path})", index + 1)),
+                _ => ui.label(format!("{}. {source}", index + 1)),
+            };
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Folder");
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.directory)
+                    .hint_text("e.g. Graphics/Characters"),
+            );
+            let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if ui.button("List").clicked() || submitted {
+                self.list_directory(update_state);
+            }
+        });
+
+        if self.listing.is_empty() {
+            return;
+        }
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, true])
+            .max_height(256.)
+            .show(ui, |ui| {
+                egui::Grid::new("luminol_rtp_manager_listing")
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (path, source) in self.listing.iter() {
+                            ui.label(path.file_name().unwrap_or(path.as_str()));
+                            match source {
+                                Some(source) => ui.label(source.to_string()),
+                                None => ui.weak("Not found"),
+                            };
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+}
+
+impl luminol_core::Window for Window {
+    fn name(&self) -> String {
+        "RTP Manager".to_string()
+    }
+
+    fn id(&self) -> egui::Id {
+        egui::Id::new("luminol_rtp_manager")
+    }
+
+    fn show(
+        &mut self,
+        ctx: &egui::Context,
+        open: &mut bool,
+        update_state: &mut luminol_core::UpdateState<'_>,
+    ) {
+        let mut window_open = true;
+        egui::Window::new(self.name())
+            .id(self.id())
+            .open(&mut window_open)
+            .default_width(500.)
+            .show(ctx, |ui| {
+                egui::CollapsingHeader::new("Project RTPs")
+                    .default_open(true)
+                    .show(ui, |ui| self.project_rtps(ui, update_state));
+
+                egui::CollapsingHeader::new("Registered RTPs")
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        #[cfg(not(target_arch = "wasm32"))]
+                        self.registered_rtps(ui, update_state);
+                        #[cfg(target_arch = "wasm32")]
+                        ui.label(
+                            "Place each RTP in the project's folder, \
+                             either in RTP/<name> or as RTP/<name>.zip",
+                        );
+                    });
+
+                egui::CollapsingHeader::new("File sources")
+                    .default_open(false)
+                    .show(ui, |ui| self.file_sources(ui, update_state));
+
+                egui::CollapsingHeader::new("Make standalone")
+                    .default_open(false)
+                    .show(ui, |ui| self.standalone(ui, update_state));
+            });
+
+        *open = window_open;
+    }
+
+    fn requires_filesystem(&self) -> bool {
+        true
+    }
+}
@@ -35,6 +35,15 @@ impl Default for Window {
     }
 }
 
+impl Window {
+    /// Create a script editor with the script at `index` already open.
+    pub fn with_script(index: usize, script_text: String) -> Self {
+        let mut window = Self::default();
+        window.tabs.add_tab(ScriptTab::new(index, script_text));
+        window
+    }
+}
+
 impl luminol_core::Window for Window {
     fn name(&self) -> String {
         self.tabs
@@ -59,6 +68,8 @@ impl luminol_core::Window for Window {
             .id(egui::Id::new("script_editor_window"))
             .show(ctx, |ui| {
                 egui::SidePanel::left("script_edit_script_panel").show_inside(ui, |ui| {
+                    update_state.history.focus_on_click(ui, self.id());
+
                     egui::ScrollArea::both()
                         .auto_shrink([false; 2])
                         .show(ui, |ui| {
@@ -66,22 +77,26 @@ impl luminol_core::Window for Window {
 
                             let mut insert_index = None;
                             let mut del_index = None;
+                            let mut renamed = None;
 
                             let scripts_len = scripts.data.len();
                             for (index, script) in scripts.data.iter_mut().enumerate() {
-                                let response = ui
-                                    .text_edit_singleline(&mut script.name)
-                                    .context_menu(|ui| {
-                                        if ui.button("Insert").clicked() {
-                                            insert_index = Some(index);
+                                let previous_name = script.name.clone();
+                                let response = ui.text_edit_singleline(&mut script.name);
+                                if response.changed() {
+                                    renamed = Some((index, previous_name, response.id));
+                                }
+                                let response = response.context_menu(|ui| {
+                                    if ui.button("Insert").clicked() {
+                                        insert_index = Some(index);
+                                    }
+
+                                    ui.add_enabled_ui(scripts_len > 1, |ui| {
+                                        if ui.button("Delete").clicked() {
+                                            del_index = Some(index);
                                         }
-
-                                        ui.add_enabled_ui(scripts_len > 1, |ui| {
-                                            if ui.button("Delete").clicked() {
-                                                del_index = Some(index);
-                                            }
-                                        });
                                     });
+                                });
 
                                 if response.double_clicked() {
                                     self.tabs
@@ -89,7 +104,19 @@ impl luminol_core::Window for Window {
                                 }
                             }
 
+                            if let Some((index, previous_name, id)) = renamed {
+                                scripts.modified = true;
+                                update_state.modified.set(true);
+                                update_state.history.push_merged(
+                                    self.id(),
+                                    id,
+                                    format!("Rename script {index}"),
+                                    luminol_core::history::Swap::script_name(index, previous_name),
+                                );
+                            }
+
                             if let Some(index) = insert_index {
+                                let previous = scripts.data.clone();
                                 scripts.data.insert(
                                     index,
                                     luminol_data::rpg::Script {
@@ -97,10 +124,25 @@ impl luminol_core::Window for Window {
                                         script_text: String::new(),
                                     },
                                 );
+                                scripts.modified = true;
+                                update_state.modified.set(true);
+                                update_state.history.push(
+                                    self.id(),
+                                    format!("Insert script {index}"),
+                                    luminol_core::history::Swap::scripts(previous),
+                                );
                             }
 
                             if let Some(index) = del_index {
-                                scripts.data.remove(index);
+                                let previous = scripts.data.clone();
+                                let script = scripts.data.remove(index);
+                                scripts.modified = true;
+                                update_state.modified.set(true);
+                                update_state.history.push(
+                                    self.id(),
+                                    format!("Delete script {}", script.name),
+                                    luminol_core::history::Swap::scripts(previous),
+                                );
                             }
                         });
                 });
@@ -170,7 +212,17 @@ impl luminol_core::Tab for ScriptTab {
                 let mut scripts = update_state.data.scripts();
                 scripts.modified = true;
 
-                scripts.data[self.index].script_text = self.script_text.clone();
+                let previous = std::mem::replace(
+                    &mut scripts.data[self.index].script_text,
+                    self.script_text.clone(),
+                );
+                if previous != self.script_text {
+                    update_state.history.push(
+                        self.id(),
+                        format!("Edit script {}", scripts.data[self.index].name),
+                        luminol_core::history::Swap::script_text(self.index, previous),
+                    );
+                }
             }
         });
 
@@ -0,0 +1,276 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+use std::collections::HashSet;
+
+use luminol_core::references::{Location, Target};
+use luminol_core::text_search::{Field, Query, TextMatch};
+
+use super::find_references::{is_openable, location_text, open_location};
+
+/// The search and replace window.
+/// Searches text commands, scripts, database names and map names across the whole project.
+#[derive(Default)]
+pub struct Window {
+    pattern: String,
+    replacement: String,
+    use_regex: bool,
+    case_sensitive: bool,
+
+    results: Vec<(TextMatch, bool)>,
+    error: Option<String>,
+}
+
+impl Window {
+    fn query(&self) -> Result<Query, regex::Error> {
+        Query::new(
+            &self.pattern,
+            &self.replacement,
+            self.use_regex,
+            self.case_sensitive,
+        )
+    }
+
+    fn search(&mut self, update_state: &luminol_core::UpdateState<'_>) {
+        self.results.clear();
+        match self.query() {
+            Ok(query) => {
+                self.error = None;
+                self.results = luminol_core::text_search::search(
+                    update_state.data,
+                    update_state.filesystem,
+                    &query,
+                )
+                .into_iter()
+                .map(|m| (m, true))
+                .collect();
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+
+    fn replace(&mut self, update_state: &mut luminol_core::UpdateState<'_>) {
+        let query = match self.query() {
+            Ok(query) => query,
+            Err(e) => {
+                self.error = Some(e.to_string());
+                return;
+            }
+        };
+
+        let selected = self
+            .results
+            .iter()
+            .filter(|(_, selected)| *selected)
+            .map(|(m, _)| (m.location, m.field))
+            .collect::<HashSet<_>>();
+        let count = luminol_core::text_search::replace(
+            update_state.data,
+            update_state.filesystem,
+            &query,
+            &selected,
+        );
+
+        if count > 0 {
+            update_state.modified.set(true);
+            // Undoing edits made before the replacement would put the old text back
+            update_state.history.clear();
+            Self::close_stale_editors(update_state, &selected);
+        }
+        update_state
+            .toasts
+            .info(format!("Replaced text in {count} places"));
+
+        self.search(update_state);
+    }
+
+    /// Closes the editors that keep their own copy of text that was just replaced, since they would
+    /// write the old text back when applied.
+    fn close_stale_editors(
+        update_state: &mut luminol_core::UpdateState<'_>,
+        selected: &HashSet<(Location, Field)>,
+    ) {
+        let replaced = |f: fn(&Location) -> bool| selected.iter().any(|(location, _)| f(location));
+
+        if replaced(|location| {
+            matches!(
+                location,
+                Location::CommonEvent { .. }
+                    | Location::Database {
+                        target: Target::CommonEvent,
+                        ..
+                    }
+            )
+        }) {
+            let window = super::common_event_edit::Window::default();
+            update_state.edit_windows.remove_window(&window);
+        }
+        if replaced(|location| matches!(location, Location::Script { .. })) {
+            let window = super::script_edit::Window::default();
+            update_state.edit_windows.remove_window(&window);
+        }
+    }
+
+    fn field_text(field: Field) -> String {
+        match field {
+            Field::Name => "Name".to_string(),
+            Field::Description => "Description".to_string(),
+            Field::Text { line, .. } => format!("Line {}: Text", line + 1),
+            Field::Choice { line, index, .. } => format!("Line {}: Choice {}", line + 1, index + 1),
+            Field::Script => "Script".to_string(),
+            Field::Word(word) => format!("Word \"{word}\""),
+        }
+    }
+}
+
+impl luminol_core::Window for Window {
+    fn name(&self) -> String {
+        "Search and Replace".to_string()
+    }
+
+    fn id(&self) -> egui::Id {
+        egui::Id::new("luminol_text_search")
+    }
+
+    fn show(
+        &mut self,
+        ctx: &egui::Context,
+        open: &mut bool,
+        update_state: &mut luminol_core::UpdateState<'_>,
+    ) {
+        let mut window_open = true;
+        egui::Window::new(self.name())
+            .id(self.id())
+            .open(&mut window_open)
+            .default_width(600.)
+            .show(ctx, |ui| {
+                egui::Grid::new("luminol_text_search_grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Find");
+                        let response = ui.text_edit_singleline(&mut self.pattern);
+                        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                            self.search(update_state);
+                        }
+                        ui.end_row();
+
+                        ui.label("Replace with");
+                        ui.text_edit_singleline(&mut self.replacement);
+                        ui.end_row();
+                    });
+
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.use_regex, "Regex")
+                        .on_hover_text("Use $1, $name, etc. in the replacement to insert groups");
+                    ui.checkbox(&mut self.case_sensitive, "Case sensitive");
+                });
+
+                ui.horizontal(|ui| {
+                    if ui.button("Search 🔎").clicked() {
+                        self.search(update_state);
+                    }
+
+                    let any_selected = self.results.iter().any(|(_, selected)| *selected);
+                    if ui
+                        .add_enabled(any_selected, egui::Button::new("Replace selected"))
+                        .on_hover_text(
+                            "Open script, common event and item editors that show the \
+                             replaced text are closed, so they can't write the old text back",
+                        )
+                        .clicked()
+                    {
+                        self.replace(update_state);
+                    }
+                });
+
+                if let Some(error) = &self.error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} matches", self.results.len()));
+                    if ui.small_button("Select all").clicked() {
+                        self.results.iter_mut().for_each(|(_, s)| *s = true);
+                    }
+                    if ui.small_button("Select none").clicked() {
+                        self.results.iter_mut().for_each(|(_, s)| *s = false);
+                    }
+                });
+
+                let mut clicked = None;
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false, true])
+                    .max_height(384.)
+                    .show(ui, |ui| {
+                        for (index, (text_match, selected)) in self.results.iter_mut().enumerate() {
+                            ui.push_id(index, |ui| {
+                                ui.horizontal(|ui| {
+                                    ui.checkbox(selected, "");
+                                    let location = location_text(update_state, text_match.location);
+                                    let link = egui::Link::new(format!(
+                                        "{location} ({})",
+                                        Self::field_text(text_match.field)
+                                    ));
+                                    if ui
+                                        .add_enabled(is_openable(text_match.location), link)
+                                        .clicked()
+                                    {
+                                        clicked = Some(text_match.location);
+                                    }
+                                });
+                                ui.indent("preview", |ui| {
+                                    ui.label(
+                                        egui::RichText::new(preview(&text_match.text))
+                                            .strikethrough()
+                                            .weak(),
+                                    );
+                                    ui.label(preview(&text_match.replaced));
+                                });
+                            });
+                        }
+                    });
+
+                if let Some(location) = clicked {
+                    open_location(update_state, location);
+                }
+            });
+
+        *open = window_open;
+    }
+
+    fn requires_filesystem(&self) -> bool {
+        true
+    }
+}
+
+/// Shortens text (mainly scripts) so it fits in the result list.
+fn preview(text: &str) -> String {
+    const MAX_LEN: usize = 200;
+    match text.char_indices().nth(MAX_LEN) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
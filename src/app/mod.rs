@@ -54,6 +54,10 @@ pub struct App {
     project_config: Option<luminol_config::project::Config>,
 
     toolbar: luminol_core::ToolbarState,
+    clipboard: luminol_core::Clipboard,
+    history: luminol_core::History,
+    autosave: luminol_core::recovery::Autosave,
+    file_watcher: luminol_core::file_watcher::FileWatcher,
 
     modified: luminol_core::ModifiedState,
     project_manager: luminol_core::ProjectManager,
@@ -229,6 +233,10 @@ impl App {
             global_config,
             project_config,
             toolbar: luminol_core::ToolbarState::default(),
+            clipboard: luminol_core::Clipboard::default(),
+            history: luminol_core::History::default(),
+            autosave: luminol_core::recovery::Autosave::default(),
+            file_watcher: luminol_core::file_watcher::FileWatcher::default(),
 
             modified,
             project_manager: luminol_core::ProjectManager::new(&cc.egui_ctx),
@@ -280,6 +288,8 @@ impl luminol_eframe::App for App {
             project_config: &mut self.project_config,
             global_config: &mut self.global_config,
             toolbar: &mut self.toolbar,
+            clipboard: &mut self.clipboard,
+            history: &mut self.history,
             modified: self.modified.clone(),
             project_manager: &mut self.project_manager,
         };
@@ -329,10 +339,42 @@ impl luminol_eframe::App for App {
         // Update all windows.
         self.windows.display_without_edit(ctx, &mut update_state);
 
+        // Undo or redo edits in the tab or window the user last clicked in.
+        // Text fields handle these shortcuts themselves while they have focus.
+        if ctx.memory(|m| m.focus().is_none()) {
+            let (is_undo_pressed, is_redo_pressed) = ctx.input(|i| {
+                (
+                    luminol_core::history::is_undo_pressed(i),
+                    luminol_core::history::is_redo_pressed(i),
+                )
+            });
+            if is_undo_pressed {
+                update_state.undo();
+            } else if is_redo_pressed {
+                update_state.redo();
+            }
+        }
+
         // Handle loading and closing projects, and if applicable, show the modal asking the user
         // if they want to save their changes.
         update_state.manage_projects(true);
 
+        // Offer to restore autosaved data if the project that was just opened has any.
+        if let Some(files) = update_state.project_manager.recovered_files.take() {
+            update_state
+                .edit_windows
+                .add_window(luminol_ui::windows::recovery::Window::new(files));
+        }
+
+        self.autosave.update(&mut update_state);
+
+        // Reload graphics changed by other programs, and ask before reloading changed data.
+        if self.file_watcher.update(&mut update_state) {
+            update_state
+                .edit_windows
+                .add_window(luminol_ui::windows::external_changes::Window::default());
+        }
+
         // If we don't do this tabs added by windows won't be added.
         // It also cleans up code nicely.
         self.tabs
@@ -104,6 +104,24 @@ impl TopBar {
                 }
 
                 save_project |= ui.button("Save Project").clicked();
+
+                if ui.button("RTP Manager").clicked() {
+                    update_state
+                        .edit_windows
+                        .add_window(luminol_ui::windows::rtp_manager::Window::default());
+                }
+
+                if ui.button("Backups").clicked() {
+                    update_state
+                        .edit_windows
+                        .add_window(luminol_ui::windows::backups::Window::default());
+                }
+
+                if ui.button("Export Project as Zip").clicked() {
+                    update_state
+                        .edit_windows
+                        .add_window(luminol_ui::windows::project_export::Window::default());
+                }
             });
 
             ui.separator();
@@ -138,6 +156,16 @@ impl TopBar {
                 ui.close_menu();
             }
 
+            ui.add_enabled_ui(update_state.filesystem.project_loaded(), |ui| {
+                if ui.button("History").clicked() {
+                    update_state
+                        .edit_windows
+                        .add_window(luminol_ui::windows::history::Window::default());
+                }
+            });
+
+            ui.separator();
+
             if ui.button("Preferences").clicked() {
                 update_state
                     .edit_windows
@@ -171,7 +199,7 @@ impl TopBar {
                 if ui.button("Items").clicked() {
                     update_state
                         .edit_windows
-                        .add_window(luminol_ui::windows::items::Window::new(update_state.data));
+                        .add_window(luminol_ui::windows::items::Window::default());
                 }
 
                 if ui.button("Common Events").clicked() {
@@ -209,6 +237,68 @@ impl TopBar {
                     .edit_windows
                     .add_window(luminol_ui::windows::archive_manager::Window::default());
             }
+
+            if ui.button("Diff and Merge Data Files").clicked() {
+                update_state
+                    .edit_windows
+                    .add_window(luminol_ui::windows::data_merge::Window::default());
+            }
+
+            ui.add_enabled_ui(update_state.filesystem.project_loaded(), |ui| {
+                if ui.button("Find References").clicked() {
+                    update_state
+                        .edit_windows
+                        .add_window(luminol_ui::windows::find_references::Window::default());
+                }
+
+                if ui.button("Search and Replace").clicked() {
+                    update_state
+                        .edit_windows
+                        .add_window(luminol_ui::windows::text_search::Window::default());
+                }
+
+                if ui.button("Reindex Database").clicked() {
+                    update_state
+                        .edit_windows
+                        .add_window(luminol_ui::windows::reindex::Window::default());
+                }
+
+                if ui.button("Export Dialogue").clicked() {
+                    update_state
+                        .edit_windows
+                        .add_window(luminol_ui::windows::dialogue_export::Window::default());
+                }
+
+                if ui.button("Localization").clicked() {
+                    update_state
+                        .edit_windows
+                        .add_window(luminol_ui::windows::localization::Window::default());
+                }
+
+                if ui.button("Import Image").clicked() {
+                    update_state
+                        .edit_windows
+                        .add_window(luminol_ui::windows::image_import::Window::default());
+                }
+
+                if ui.button("Path Portability").clicked() {
+                    update_state
+                        .edit_windows
+                        .add_window(luminol_ui::windows::portability::Window::default());
+                }
+
+                if ui.button("Character Viewer").clicked() {
+                    update_state
+                        .edit_tabs
+                        .add_tab(luminol_ui::tabs::character_viewer::Tab::default());
+                }
+
+                if ui.button("Problems").clicked() {
+                    update_state
+                        .edit_tabs
+                        .add_tab(luminol_ui::tabs::problems::Tab::new());
+                }
+            });
         });
 
         ui.separator();
@@ -264,6 +354,14 @@ impl TopBar {
                     .edit_windows
                     .add_window(luminol_ui::windows::misc::FilesystemDebug::default());
             }
+
+            ui.add_enabled_ui(update_state.filesystem.project_loaded(), |ui| {
+                if ui.button("Tileset Atlas Inspector").clicked() {
+                    update_state
+                        .edit_windows
+                        .add_window(luminol_ui::windows::atlas_inspector::Window::default());
+                }
+            });
         });
 
         #[cfg(not(target_arch = "wasm32"))]
@@ -340,6 +438,11 @@ impl TopBar {
                 match update_state.data.save(update_state.filesystem, config) {
                     Ok(_) => {
                         update_state.modified.set(false);
+                        if let Err(e) = luminol_core::recovery::clear(update_state.filesystem) {
+                            update_state
+                                .toasts
+                                .warning(format!("Failed to clear autosaved data: {e:#}"));
+                        }
                         update_state.toasts.info("Saved project successfully!")
                     }
                     Err(e) => update_state.toasts.error(e.to_string()),
@@ -0,0 +1,86 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+//! Headless diffs and merges of data files, for use from git:
+//!
+//! `luminol --diff <old file> <new file>`
+//!
+//! `luminol --merge <base file> <our file> <their file> [--output <path>]`
+//!
+//! To let git merge data files, add `Data/*.rxdata merge=luminol` to `.gitattributes`
+//! and this to `.git/config`:
+//!
+//! ```text
+//! [merge "luminol"]
+//!     name = Luminol data merge
+//!     driver = luminol --merge %O %A %B
+//! ```
+//!
+//! Diffs can be shown with `git difftool -y -x "luminol --diff" -- Data/Map001.rxdata`.
+
+use anyhow::Context;
+
+const USAGE: &str = "\
+usage: luminol --diff <old file> <new file>
+       luminol --merge <base file> <our file> <their file> [--output <path>]
+
+--merge writes the merged file over <our file> unless --output is given.
+Conflicts keep our side, are listed on stderr and make the exit code 1.";
+
+fn read(path: &str) -> anyhow::Result<Vec<u8>> {
+    std::fs::read(path).with_context(|| format!("while reading {path}"))
+}
+
+/// Runs `--diff` or `--merge` with the arguments that come after it, and returns the exit code.
+pub fn run(command: &str, args: impl Iterator<Item = String>) -> anyhow::Result<i32> {
+    let args: Vec<_> = args.collect();
+    match (command, args.as_slice()) {
+        ("--diff", [old, new]) => {
+            let changes = luminol_core::diff::diff_bytes(&read(old)?, &read(new)?)
+                .context("while comparing the files")?;
+            for change in changes {
+                println!("{}: {}", change.path, change.description);
+            }
+            Ok(0)
+        }
+        ("--merge", [base, ours, theirs, rest @ ..]) => {
+            let output = match rest {
+                [] => ours,
+                [flag, output] if flag == "--output" => output,
+                _ => anyhow::bail!(USAGE),
+            };
+
+            let merge = luminol_core::diff::merge_bytes(&read(base)?, &read(ours)?, &read(theirs)?)
+                .context("while merging the files")?;
+            let bytes = merge.to_bytes(&[])?;
+            std::fs::write(output, bytes).with_context(|| format!("while writing {output}"))?;
+
+            for conflict in &merge.conflicts {
+                eprintln!("conflict at {}: {}", conflict.path, conflict.description);
+            }
+            Ok(i32::from(!merge.conflicts.is_empty()))
+        }
+        _ => anyhow::bail!(USAGE),
+    }
+}
@@ -0,0 +1,107 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of Luminol.
+//
+// Luminol is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luminol is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luminol.  If not, see <http://www.gnu.org/licenses/>.
+//
+//     Additional permission under GNU GPL version 3 section 7
+//
+// If you modify this Program, or any covered work, by linking or combining
+// it with Steamworks API by Valve Corporation, containing parts covered by
+// terms of the Steamworks API by Valve Corporation, the licensors of this
+// Program grant you additional permission to convey the resulting work.
+
+//! Headless map image export, for generating map images without opening the editor:
+//!
+//! `luminol --export-map <project path> <map id> <output path> [options]`
+
+use anyhow::Context;
+
+const USAGE: &str = "\
+usage: luminol --export-map <project path> <map id> <output path> [options]
+
+options:
+    --scale <scale>  size of the image relative to the size of the map in pixels (default: 1)
+    --no-events      don't draw events
+    --panorama       draw the tileset's panorama
+    --fog            draw the tileset's fog
+    --passages       draw the passage overlay";
+
+/// Runs the map exporter with the arguments that come after `--export-map`, and returns the exit
+/// code.
+pub fn run(mut args: impl Iterator<Item = String>) -> anyhow::Result<i32> {
+    let (Some(project_path), Some(map_id), Some(output_path)) =
+        (args.next(), args.next(), args.next())
+    else {
+        anyhow::bail!(USAGE);
+    };
+    let map_id: usize = map_id.parse().context("invalid map id")?;
+
+    let mut options = luminol_graphics::compositor::MapImageOptions::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--scale" => {
+                options.scale = args
+                    .next()
+                    .context("missing value for --scale")?
+                    .parse()
+                    .context("invalid value for --scale")?;
+            }
+            "--no-events" => options.events = false,
+            "--panorama" => options.panorama = true,
+            "--fog" => options.fog = true,
+            "--passages" => options.passages = true,
+            _ => anyhow::bail!("unknown option {arg}\n\n{USAGE}"),
+        }
+    }
+
+    let mut filesystem = luminol_filesystem::project::FileSystem::new();
+    let mut project_config = None;
+    let mut global_config = luminol_config::global::Config::default();
+    let load_result = filesystem
+        .load_project_from_path(&mut project_config, &mut global_config, &project_path)
+        .with_context(|| format!("while loading the project at {project_path}"))?;
+    for rtp in load_result.missing_rtps {
+        eprintln!("warning: RTP {rtp} is missing, some graphics may fail to load");
+    }
+
+    let mut data = luminol_core::Data::default();
+    data.load(
+        &filesystem,
+        project_config
+            .as_mut()
+            .context("the project has no config")?,
+    )?;
+
+    if !data.map_infos().data.contains_key(&map_id) {
+        anyhow::bail!("there is no map with the id {map_id}");
+    }
+    let map = data.get_or_load_map(map_id, &filesystem);
+    let tilesets = data.tilesets();
+    let tileset = tilesets
+        .data
+        .get(map.tileset_id)
+        .context("the map's tileset does not exist")?;
+
+    let luminol_graphics::compositor::MapImage { image, warnings } =
+        luminol_graphics::compositor::render_map(&filesystem, &map, tileset, &options);
+    for warning in warnings {
+        eprintln!("warning: {warning}");
+    }
+    image
+        .save(&output_path)
+        .with_context(|| format!("while saving {output_path}"))?;
+
+    Ok(0)
+}
@@ -31,6 +31,10 @@ use wasm_bindgen::prelude::*;
 const ICON: &[u8] = include_bytes!("../assets/icon-256.png");
 
 mod app;
+#[cfg(not(target_arch = "wasm32"))]
+mod data_merge;
+#[cfg(not(target_arch = "wasm32"))]
+mod export_map;
 mod lumi;
 
 #[cfg(all(feature = "steamworks", target_arch = "wasm32"))]
@@ -41,6 +45,23 @@ mod steam;
 
 #[cfg(not(target_arch = "wasm32"))]
 fn main() {
+    // Commands that run without starting the editor, e.g. `--merge` as a git merge driver
+    let command = std::env::args_os().nth(1);
+    let result = match command.as_ref().and_then(|arg| arg.to_str()) {
+        Some("--export-map") => Some(export_map::run(std::env::args().skip(2))),
+        Some(command @ ("--diff" | "--merge")) => {
+            Some(data_merge::run(command, std::env::args().skip(2)))
+        }
+        _ => None,
+    };
+    if let Some(result) = result {
+        let code = result.unwrap_or_else(|e| {
+            eprintln!("{e:?}");
+            2
+        });
+        std::process::exit(code);
+    }
+
     #[cfg(feature = "steamworks")]
     let steamworks = match steam::Steamworks::new() {
         Ok(s) => s,